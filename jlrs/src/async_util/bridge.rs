@@ -0,0 +1,266 @@
+//! Bridge Julia `Channel`s and Rust async channels.
+//!
+//! A Julia `Channel{T}` can be bridged to a Rust async channel in either direction. Values that
+//! Julia code `put!`s into a channel bridged with [`AsyncJulia::bridge_from_julia`] are unboxed
+//! and can be received from the returned receiver. Values sent to the sender returned by
+//! [`AsyncJulia::bridge_to_julia`] are converted to Julia data and can be `take!`n from the
+//! bridged channel. The Rust channel must implement the traits from [`async_util::channel`].
+//!
+//! Both directions are handled by a task that runs until the bridge is closed. A bridge from
+//! Julia is closed when the Julia channel has been closed and all values in it have been taken,
+//! or when the receiver has been dropped. A bridge to Julia is closed when all senders have been
+//! dropped, which also closes the Julia channel. The result of this task is sent back when the
+//! bridge has been closed.
+//!
+//! Backpressure works in both directions: the bridge waits until there's room in the receiving
+//! channel before it takes a new value from the sending channel.
+//!
+//! [`AsyncJulia::bridge_from_julia`]: crate::runtime::async_rt::AsyncJulia::bridge_from_julia
+//! [`AsyncJulia::bridge_to_julia`]: crate::runtime::async_rt::AsyncJulia::bridge_to_julia
+//! [`async_util::channel`]: crate::async_util::channel
+
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+
+use crate::{
+    async_util::{
+        channel::{ChannelReceiver, ChannelSender, OneshotSender},
+        envelope::PendingTaskEnvelope,
+    },
+    call::{Call, CallAsync},
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia, unbox::Unbox},
+    data::{
+        layout::nothing::Nothing,
+        managed::{module::Module, union_all::UnionAll, value::Value, Managed},
+        types::typecheck::Typecheck,
+    },
+    error::{JlrsResult, TypeError, CANNOT_DISPLAY_TYPE, CANNOT_DISPLAY_VALUE},
+    memory::{
+        context::stack::Stack,
+        target::{
+            frame::{AsyncGcFrame, GcFrame},
+            unrooted::Unrooted,
+        },
+    },
+};
+
+pub(crate) struct FromJuliaBridge<F, S, O, T> {
+    func: F,
+    sender: S,
+    res_sender: O,
+    _item: PhantomData<T>,
+}
+
+impl<F, S, O, T> FromJuliaBridge<F, S, O, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    S: ChannelSender<T>,
+    O: OneshotSender<JlrsResult<()>>,
+    T: IntoJulia + Unbox<Output = T> + Typecheck + Send + 'static,
+{
+    #[inline]
+    pub(crate) fn new(func: F, sender: S, res_sender: O) -> Self {
+        FromJuliaBridge {
+            func,
+            sender,
+            res_sender,
+            _item: PhantomData,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, S, O, T> PendingTaskEnvelope for FromJuliaBridge<F, S, O, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    S: ChannelSender<T>,
+    O: OneshotSender<JlrsResult<()>>,
+    T: IntoJulia + Unbox<Output = T> + Typecheck + Send + 'static,
+{
+    async fn call(self: Box<Self>, stack: &'static Stack) {
+        let FromJuliaBridge {
+            func,
+            sender,
+            res_sender,
+            ..
+        } = *self;
+
+        // Safety: the stack slots can be reallocated because it doesn't contain any frames
+        // yet. The channel is rooted in the base frame, everything that is rooted while a value
+        // is taken is popped before the next value is taken.
+        let res = unsafe {
+            let (owner, mut frame) = AsyncGcFrame::base(stack);
+
+            let res = match func(&mut frame).and_then(|ch| check_channel::<T>(&mut frame, ch)) {
+                Ok(channel) => {
+                    let offset = stack.size();
+
+                    loop {
+                        let mut frame = owner.reconstruct(offset);
+                        match take::<T>(&mut frame, channel).await {
+                            Ok(Some(value)) => {
+                                if sender.send(value).await.is_err() {
+                                    break Ok(());
+                                }
+                            }
+                            Ok(None) => break Ok(()),
+                            Err(e) => break Err(e),
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            };
+
+            std::mem::drop(owner);
+            res
+        };
+
+        res_sender.send(res);
+    }
+}
+
+pub(crate) struct ToJuliaBridge<F, R, O, T> {
+    func: F,
+    receiver: R,
+    res_sender: O,
+    _item: PhantomData<T>,
+}
+
+impl<F, R, O, T> ToJuliaBridge<F, R, O, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    R: ChannelReceiver<T>,
+    O: OneshotSender<JlrsResult<()>>,
+    T: IntoJulia + Send + 'static,
+{
+    #[inline]
+    pub(crate) fn new(func: F, receiver: R, res_sender: O) -> Self {
+        ToJuliaBridge {
+            func,
+            receiver,
+            res_sender,
+            _item: PhantomData,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, R, O, T> PendingTaskEnvelope for ToJuliaBridge<F, R, O, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    R: ChannelReceiver<T>,
+    O: OneshotSender<JlrsResult<()>>,
+    T: IntoJulia + Send + 'static,
+{
+    async fn call(self: Box<Self>, stack: &'static Stack) {
+        let ToJuliaBridge {
+            func,
+            mut receiver,
+            res_sender,
+            ..
+        } = *self;
+
+        // Safety: the stack slots can be reallocated because it doesn't contain any frames
+        // yet. The channel is rooted in the base frame, everything that is rooted while a value
+        // is put is popped before the next value is put.
+        let res = unsafe {
+            let (owner, mut frame) = AsyncGcFrame::base(stack);
+
+            let res = match func(&mut frame).and_then(|ch| check_channel::<T>(&mut frame, ch)) {
+                Ok(channel) => {
+                    let offset = stack.size();
+
+                    loop {
+                        let value = match receiver.recv().await {
+                            Ok(value) => value,
+                            Err(_) => break close(channel),
+                        };
+
+                        let mut frame = owner.reconstruct(offset);
+                        if let Err(e) = put(&mut frame, channel, value).await {
+                            break Err(e);
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            };
+
+            std::mem::drop(owner);
+            res
+        };
+
+        res_sender.send(res);
+    }
+}
+
+// Returns an error if `channel` is not a `Channel{T}`.
+unsafe fn check_channel<'base, T: IntoJulia>(
+    frame: &mut GcFrame<'base>,
+    channel: Value<'base, 'static>,
+) -> JlrsResult<Value<'base, 'static>> {
+    let channel_type = Module::typed_global_cached::<UnionAll, _, _>(&frame, "Base.Channel")?;
+    let elem_type = T::julia_type(&mut *frame).as_value();
+    let ty = channel_type
+        .as_value()
+        .apply_type(&mut *frame, [elem_type])
+        .into_jlrs_result()?;
+
+    if !channel.isa(ty) {
+        Err(TypeError::NotA {
+            value: channel.display_string_or(CANNOT_DISPLAY_VALUE),
+            field_type: ty.display_string_or(CANNOT_DISPLAY_TYPE),
+        })?
+    }
+
+    Ok(channel)
+}
+
+// Takes the next value from `channel`. Returns `None` if the channel has been closed and is empty.
+async unsafe fn take<T>(
+    frame: &mut AsyncGcFrame<'static>,
+    channel: Value<'static, 'static>,
+) -> JlrsResult<Option<T>>
+where
+    T: Unbox<Output = T> + Typecheck,
+{
+    // Iterating over a channel takes values from it until it has been closed.
+    let iterate = Module::typed_global_cached::<Value, _, _>(&frame, "Base.iterate")?;
+    let next = iterate
+        .call_async(&mut *frame, [channel])
+        .await
+        .into_jlrs_result()?;
+
+    if next.is::<Nothing>() {
+        return Ok(None);
+    }
+
+    next.get_nth_field(&mut *frame, 0)?.unbox::<T>().map(Some)
+}
+
+// Puts `value` into `channel`, waits while the channel is full.
+async unsafe fn put<T: IntoJulia>(
+    frame: &mut AsyncGcFrame<'static>,
+    channel: Value<'static, 'static>,
+    value: T,
+) -> JlrsResult<()> {
+    let put = Module::typed_global_cached::<Value, _, _>(&frame, "Base.put!")?;
+    let value = Value::new(&mut *frame, value);
+    put.call_async(&mut *frame, [channel, value])
+        .await
+        .into_jlrs_result()?;
+
+    Ok(())
+}
+
+// Closes `channel`, Julia tasks waiting on it are notified.
+unsafe fn close(channel: Value<'static, 'static>) -> JlrsResult<()> {
+    let unrooted = Unrooted::new();
+    let close = Module::typed_global_cached::<Value, _, _>(&unrooted, "Base.close")?;
+    close
+        .call1(unrooted, channel)
+        .map_err(|e| e.as_value())
+        .into_jlrs_result()?;
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+//! Cancel async tasks.
+//!
+//! An [`AsyncTask`] that has been sent to the async runtime with
+//! [`AsyncJulia::cancellable_task`] can be cancelled with the [`CancellationHandle`] that is
+//! returned alongside the dispatcher. Cancelling a task throws an `InterruptException` into the
+//! Julia task it's currently waiting on, if any, and stops the `AsyncTask` at its next await
+//! point. The result of a cancelled task is an error, [`CancellationError::Cancelled`].
+//!
+//! Julia tasks can only be interrupted at yield points. If the Julia task is stuck in a loop that
+//! never yields it keeps running after the `AsyncTask` has been cancelled, but the runtime no
+//! longer waits for it.
+//!
+//! [`AsyncTask`]: crate::async_util::task::AsyncTask
+//! [`AsyncJulia::cancellable_task`]: crate::runtime::async_rt::AsyncJulia::cancellable_task
+//! [`CancellationError::Cancelled`]: crate::error::CancellationError::Cancelled
+
+use std::{
+    cell::RefCell,
+    ffi::c_void,
+    pin::Pin,
+    ptr::{null_mut, NonNull},
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use futures::Future;
+
+use crate::{
+    call::Call,
+    data::managed::{module::Module, private::ManagedPriv, task::Task, value::Value, Managed},
+    memory::target::unrooted::Unrooted,
+    private::Private,
+};
+
+/// A handle that can be used to cancel an async task.
+///
+/// The handle can be cloned and shared freely between threads. Cancelling a task that has
+/// already completed has no effect.
+#[derive(Clone)]
+pub struct CancellationHandle {
+    state: Arc<CancellationState>,
+}
+
+impl CancellationHandle {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        CancellationHandle {
+            state: Arc::new(CancellationState {
+                cancelled: AtomicBool::new(false),
+                waker: Mutex::new(None),
+                julia_task: AtomicPtr::new(null_mut()),
+            }),
+        }
+    }
+
+    /// Cancel the task.
+    ///
+    /// The task is stopped by the runtime thread that's handling it, this method only requests
+    /// cancellation and returns immediately.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+        let waker = self.state.waker.lock().unwrap().take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the task has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+
+    #[inline]
+    pub(crate) fn state(&self) -> &Arc<CancellationState> {
+        &self.state
+    }
+}
+
+pub(crate) struct CancellationState {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+    // The Julia task that is currently being awaited. It's only set and read by the thread that
+    // handles the async task, the task is rooted while it's set.
+    julia_task: AtomicPtr<c_void>,
+}
+
+impl CancellationState {
+    #[inline]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub(crate) fn set_julia_task(&self, task: Task) {
+        self.julia_task
+            .store(task.unwrap(Private).cast(), Ordering::Release)
+    }
+
+    #[inline]
+    pub(crate) fn clear_julia_task(&self, task: Task) {
+        let _ = self.julia_task.compare_exchange(
+            task.unwrap(Private).cast(),
+            null_mut(),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+    }
+
+    // Safety: must be called from the thread that handles the async task, before any of the
+    // frames of that task have been dropped.
+    unsafe fn interrupt_julia_task(&self) {
+        let task = self.julia_task.swap(null_mut(), Ordering::AcqRel);
+        if let Some(task) = NonNull::new(task) {
+            interrupt(Task::wrap_non_null(task.cast(), Private));
+        }
+    }
+}
+
+/// Throw an `InterruptException` into `task` unless it has already completed.
+///
+/// Returns `true` if the exception has been scheduled.
+///
+/// Safety: the async runtime must have been initialized, and `task` must be rooted.
+pub(crate) unsafe fn interrupt(task: Task) -> bool {
+    let unrooted = Unrooted::new();
+    let func = match Module::typed_global_cached::<Value, _, _>(
+        &unrooted,
+        "Main.JlrsCancellation.interrupt",
+    ) {
+        Ok(func) => func,
+        Err(_) => return false,
+    };
+
+    match func.call1(unrooted, task.as_value()) {
+        Ok(res) => res
+            .as_value()
+            .unbox::<bool>()
+            .map_or(false, |b| b.as_bool()),
+        Err(_) => false,
+    }
+}
+
+thread_local! {
+    // The cancellation state of the async task that is currently being polled on this thread.
+    static CURRENT: RefCell<Option<Arc<CancellationState>>> =
+        const { RefCell::new(None) };
+}
+
+// Returns the cancellation state of the async task that is currently being polled on this
+// thread, if it can be cancelled.
+#[inline]
+pub(crate) fn current_cancellation() -> Option<Arc<CancellationState>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+// Wraps the future of a cancellable async task. Resolves to `None` if the task has been
+// cancelled, in which case the Julia task that was being awaited has been interrupted. The
+// wrapped future must be dropped before the frame it uses is popped.
+pub(crate) struct Cancellable<F: ?Sized> {
+    future: Pin<Box<F>>,
+    state: Arc<CancellationState>,
+}
+
+impl<F> Cancellable<F>
+where
+    F: Future + ?Sized,
+{
+    #[inline]
+    pub(crate) fn new(future: Pin<Box<F>>, state: Arc<CancellationState>) -> Self {
+        Cancellable { future, state }
+    }
+}
+
+impl<F> Future for Cancellable<F>
+where
+    F: Future + ?Sized,
+{
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if !self.state.is_cancelled() {
+            *self.state.waker.lock().unwrap() = Some(cx.waker().clone());
+
+            // The task might have been cancelled before the waker was stored.
+            if !self.state.is_cancelled() {
+                let prev = CURRENT.with(|current| current.replace(Some(self.state.clone())));
+                let res = self.future.as_mut().poll(cx);
+                CURRENT.with(|current| *current.borrow_mut() = prev);
+                return res.map(Some);
+            }
+        }
+
+        // Safety: this future is polled by the thread that handles the task, the frame the Julia
+        // task is rooted in hasn't been popped yet.
+        unsafe { self.state.interrupt_julia_task() };
+
+        Poll::Ready(None)
+    }
+}
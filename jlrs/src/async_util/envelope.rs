@@ -1,23 +1,42 @@
-use std::{ffi::c_void, marker::PhantomData, num::NonZeroUsize, path::PathBuf, sync::Arc};
+use std::{
+    ffi::c_void, marker::PhantomData, num::NonZeroUsize, panic::AssertUnwindSafe, path::PathBuf,
+    sync::Arc, time::Duration,
+};
 
 use async_trait::async_trait;
+use futures::{
+    future::{select, Either},
+    FutureExt,
+};
 
 use super::{channel::Channel, task::PersistentTask};
 use crate::{
     async_util::{
-        channel::{ChannelReceiver, OneshotSender},
+        cancellation::{Cancellable, CancellationHandle, CancellationState},
+        channel::{ChannelReceiver, ChannelSender, OneshotSender},
         future::JuliaFuture,
         task::AsyncTask,
     },
-    call::Call,
-    data::managed::{module::Module, string::JuliaString, value::Value, Managed},
-    error::{JlrsError, JlrsResult},
+    call::{Call, CallAsync},
+    convert::{from_julia::FromJulia, into_jlrs_result::IntoJlrsResult},
+    data::{
+        layout::nothing::Nothing,
+        managed::{module::Module, string::JuliaString, value::Value, Managed},
+    },
+    error::{CancellationError, JlrsError, JlrsResult, RuntimeError},
     memory::{
         context::stack::Stack,
         stack_frame::StackFrame,
-        target::frame::{AsyncGcFrame, GcFrame},
+        target::{
+            frame::{AsyncGcFrame, GcFrame},
+            reusable_slot::ReusableSlot,
+        },
+    },
+    runtime::async_rt::{
+        shutdown::{current_shutdown, ShutdownState},
+        supervision::{Liveness, PersistentHealth, SupervisionPolicy},
+        PersistentHandle, PersistentMessage,
     },
-    runtime::async_rt::{PersistentHandle, PersistentMessage},
 };
 
 pub(crate) type InnerPersistentMessage<P> = Box<
@@ -150,6 +169,7 @@ where
 
 pub(crate) struct PersistentComms<C, P, O> {
     sender: O,
+    policy: SupervisionPolicy,
     _task: PhantomData<P>,
     _channel: PhantomData<C>,
 }
@@ -164,6 +184,17 @@ where
     pub(crate) fn new(sender: O) -> Self {
         PersistentComms {
             sender,
+            policy: SupervisionPolicy::never(),
+            _task: PhantomData,
+            _channel: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn supervised(sender: O, policy: SupervisionPolicy) -> Self {
+        PersistentComms {
+            sender,
+            policy,
             _task: PhantomData,
             _channel: PhantomData,
         }
@@ -181,6 +212,7 @@ where
         PendingTask {
             task: Some(task),
             sender,
+            cancellation: None,
             _kind: PhantomData,
         }
     }
@@ -194,6 +226,7 @@ where
 pub(crate) struct PendingTask<O, T, Kind> {
     task: Option<T>,
     sender: O,
+    cancellation: Option<Arc<CancellationState>>,
     _kind: PhantomData<Kind>,
 }
 
@@ -207,13 +240,24 @@ where
         PendingTask {
             task: Some(task),
             sender,
+            cancellation: None,
             _kind: PhantomData,
         }
     }
 
     #[inline]
-    fn split(self) -> (A, O) {
-        (self.task.unwrap(), self.sender)
+    pub(crate) fn new_cancellable(task: A, sender: O, handle: &CancellationHandle) -> Self {
+        PendingTask {
+            task: Some(task),
+            sender,
+            cancellation: Some(handle.state().clone()),
+            _kind: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn split(self) -> (A, O, Option<Arc<CancellationState>>) {
+        (self.task.unwrap(), self.sender, self.cancellation)
     }
 }
 
@@ -227,6 +271,7 @@ where
         PendingTask {
             task: None,
             sender,
+            cancellation: None,
             _kind: PhantomData,
         }
     }
@@ -247,6 +292,7 @@ where
         PendingTask {
             task: None,
             sender,
+            cancellation: None,
             _kind: PhantomData,
         }
     }
@@ -260,6 +306,12 @@ where
 #[async_trait(?Send)]
 pub(crate) trait PendingTaskEnvelope: Send {
     async fn call(mut self: Box<Self>, stack: &'static Stack);
+
+    // Returns `true` if this task runs until all of its handles have been dropped.
+    #[inline]
+    fn is_persistent(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait(?Send)]
@@ -269,7 +321,7 @@ where
     A: AsyncTask,
 {
     async fn call(mut self: Box<Self>, stack: &'static Stack) {
-        let (mut task, result_sender) = self.split();
+        let (mut task, result_sender, cancellation) = self.split();
 
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
         // yet. The frame is dropped at the end of the scope, the nested hierarchy of scopes is
@@ -277,7 +329,21 @@ where
         let res = unsafe {
             let (owner, frame) = AsyncGcFrame::base(&stack);
 
-            let res = task.call_run(frame).await;
+            let res = match cancellation {
+                Some(cancellation) if cancellation.is_cancelled() => {
+                    Err(CancellationError::Cancelled.into())
+                }
+                Some(cancellation) => {
+                    // If the task is cancelled, the future is dropped before the frame is popped.
+                    let fut = Cancellable::new(task.call_run(frame), cancellation);
+                    match fut.await {
+                        Some(res) => res,
+                        None => Err(CancellationError::Cancelled.into()),
+                    }
+                }
+                None => task.call_run(frame).await,
+            };
+
             std::mem::drop(owner);
             res
         };
@@ -339,38 +405,241 @@ where
     O: OneshotSender<JlrsResult<PersistentHandle<P>>>,
     P: PersistentTask,
 {
+    #[inline]
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
     async fn call(mut self: Box<Self>, stack: &'static Stack) {
-        let (mut persistent, handle_sender) = self.split();
-        let handle_sender = handle_sender.sender;
-        let (sender, mut receiver) = C::channel(NonZeroUsize::new(P::CHANNEL_CAPACITY));
+        let (mut persistent, comms) = self.split();
+        let PersistentComms { sender, policy, .. } = comms;
+        let (channel_sender, mut receiver) = C::channel(NonZeroUsize::new(P::CHANNEL_CAPACITY));
+
+        // The handle is sent after `init` has completed successfully for the first time. The
+        // channel is not recreated when the task is restarted, calls that are made while the
+        // task is restarting wait in the channel.
+        let mut handle_sender = Some((sender, channel_sender));
+        let health = Arc::new(PersistentHealth::new());
+
+        // Stop accepting new calls when the runtime is shut down.
+        let shutdown = current_shutdown();
+
         // Safety: the stack slots can be reallocated because it doesn't contain any frames
         // yet. The frame is dropped at the end of the scope, the nested hierarchy of scopes is
-        // maintained.
+        // maintained. The base frame is reconstructed every time the task is (re)initialized.
         unsafe {
-            let (owner, frame) = AsyncGcFrame::base(&stack);
+            let (owner, _) = AsyncGcFrame::base(stack);
+
+            loop {
+                let frame = owner.reconstruct(0);
+                let failure = match persistent.call_init(frame).await {
+                    Ok(mut state) => {
+                        health.set_liveness(Liveness::Running);
+                        if let Some((handle_sender, channel_sender)) = handle_sender.take() {
+                            let handle =
+                                PersistentHandle::new(Arc::new(channel_sender), health.clone());
+                            handle_sender.send(Ok(handle));
+                        }
+
+                        let offset = stack.size();
+                        let failure = loop {
+                            let recv = receiver.recv();
+                            let res = match shutdown {
+                                Some(ref shutdown) => {
+                                    match select(recv, shutdown.requested()).await {
+                                        Either::Left((res, _)) => res,
+                                        Either::Right(_) => break None,
+                                    }
+                                }
+                                None => recv.await,
+                            };
+
+                            let mut msg = match res {
+                                Ok(msg) => msg.msg,
+                                Err(_) => break None,
+                            };
+
+                            let frame = owner.reconstruct(offset);
+                            let input = msg.input();
+                            let res =
+                                AssertUnwindSafe(persistent.call_run(frame, &mut state, input))
+                                    .catch_unwind()
+                                    .await;
+
+                            match res {
+                                Ok(Err(e)) if policy.restarts_on_error() => {
+                                    let error = e.to_string();
+                                    msg.respond(Err(e));
+                                    break Some(error);
+                                }
+                                Ok(res) => msg.respond(res),
+                                Err(_) => {
+                                    msg.respond(Err(RuntimeError::TaskPanicked.into()));
+                                    break Some(RuntimeError::TaskPanicked.to_string());
+                                }
+                            }
+                        };
 
-            match persistent.call_init(frame).await {
-                Ok(mut state) => {
-                    handle_sender.send(Ok(PersistentHandle::new(Arc::new(sender))));
+                        let frame = owner.reconstruct(offset);
+                        persistent.exit(frame, &mut state).await;
+                        failure
+                    }
+                    Err(e)
+                        if handle_sender.is_some()
+                            && policy.next_backoff(health.restart_count()).is_none() =>
+                    {
+                        // The task can't be restarted and no handle has been created yet.
+                        health.failed(e.to_string());
+                        if let Some((handle_sender, _)) = handle_sender.take() {
+                            handle_sender.send(Err(e));
+                        }
+                        None
+                    }
+                    Err(e) => Some(e.to_string()),
+                };
+
+                let error = match failure {
+                    Some(error) => error,
+                    None => break,
+                };
+
+                health.failed(error);
+                let backoff = match policy.next_backoff(health.restart_count()) {
+                    Some(backoff) => backoff,
+                    None => break,
+                };
+
+                health.set_liveness(Liveness::Restarting);
+                let frame = owner.reconstruct(0);
+                match wait_for_restart(frame, backoff, shutdown.as_deref()).await {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        // The runtime has been shut down before `init` completed successfully.
+                        if let Some((handle_sender, _)) = handle_sender.take() {
+                            handle_sender.send(Err(RuntimeError::ShuttingDown.into()));
+                        }
+                        break;
+                    }
+                    Err(e) => {
+                        // The task can't be restarted without a backoff.
+                        health.failed(e.to_string());
+                        if let Some((handle_sender, _)) = handle_sender.take() {
+                            handle_sender.send(Err(e));
+                        }
+                        break;
+                    }
+                }
 
-                    let offset = stack.size();
+                health.restarted();
+            }
 
-                    loop {
-                        let mut msg = match receiver.recv().await {
-                            Ok(msg) => msg.msg,
-                            Err(_) => break,
-                        };
+            health.set_liveness(Liveness::Stopped);
+            std::mem::drop(owner);
+        }
+    }
+}
 
-                        let frame = owner.reconstruct(offset);
-                        let res = persistent.call_run(frame, &mut state, msg.input()).await;
+// Waits for `backoff` before a persistent task is restarted. Returns `Ok(false)` if the runtime
+// is shut down while waiting, and an error if the backoff can't be awaited.
+async unsafe fn wait_for_restart(
+    mut frame: AsyncGcFrame<'static>,
+    backoff: Duration,
+    shutdown: Option<&ShutdownState>,
+) -> JlrsResult<bool> {
+    if let Some(shutdown) = shutdown {
+        if shutdown.is_requested() {
+            return Ok(false);
+        }
+    }
 
-                        msg.respond(res);
-                    }
+    if backoff.is_zero() {
+        return Ok(true);
+    }
+
+    let sleep = Module::typed_global_cached::<Value, _, _>(&frame, "Base.sleep")?;
+    let secs = Value::new(&mut frame, backoff.as_secs_f64());
+    let wait = sleep.call_async(&mut frame, [secs]);
+
+    match shutdown {
+        Some(shutdown) => match select(Box::pin(wait), shutdown.requested()).await {
+            Either::Left(_) => Ok(true),
+            Either::Right(_) => Ok(false),
+        },
+        None => {
+            wait.await.ok();
+            Ok(true)
+        }
+    }
+}
+
+pub(crate) struct StreamTask<F, S, T> {
+    func: F,
+    sender: S,
+    _item: PhantomData<T>,
+}
+
+impl<F, S, T> StreamTask<F, S, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    S: ChannelSender<JlrsResult<T>>,
+    T: FromJulia + Send + 'static,
+{
+    #[inline]
+    pub(crate) fn new(func: F, sender: S) -> Self {
+        StreamTask {
+            func,
+            sender,
+            _item: PhantomData,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<F, S, T> PendingTaskEnvelope for StreamTask<F, S, T>
+where
+    for<'base> F: 'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    S: ChannelSender<JlrsResult<T>>,
+    T: FromJulia + Send + 'static,
+{
+    async fn call(mut self: Box<Self>, stack: &'static Stack) {
+        let StreamTask { func, sender, .. } = *self;
+
+        // Safety: the stack slots can be reallocated because it doesn't contain any frames
+        // yet. The iterable and the iteration state are rooted in the base frame, everything
+        // that is rooted while an element is computed is popped before the next element is
+        // computed.
+        unsafe {
+            let (owner, mut frame) = AsyncGcFrame::base(&stack);
 
-                    let frame = owner.reconstruct(offset);
-                    persistent.exit(frame, &mut state).await;
+            match func(&mut frame) {
+                Ok(iterable) => {
+                    let mut state_slot = frame.reusable_slot();
+                    let mut state = None;
+                    let offset = stack.size();
+
+                    loop {
+                        let mut frame = owner.reconstruct(offset);
+                        let next =
+                            next_element::<T>(&mut frame, iterable, &mut state, &mut state_slot)
+                                .await;
+
+                        match next {
+                            Ok(Some(elem)) => {
+                                if sender.send(Ok(elem)).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Ok(None) => break,
+                            Err(e) => {
+                                sender.send(Err(e)).await.ok();
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    sender.send(Err(e)).await.ok();
                 }
-                Err(e) => handle_sender.send(Err(e)),
             }
 
             std::mem::drop(owner);
@@ -378,6 +647,30 @@ where
     }
 }
 
+// Calls `Base.iterate` as a new task and converts the next element. Returns `None` if the
+// iterable has been exhausted. The new iteration state is rooted in `state_slot`.
+async unsafe fn next_element<T: FromJulia>(
+    frame: &mut AsyncGcFrame<'static>,
+    iterable: Value<'static, 'static>,
+    state: &mut Option<Value<'static, 'static>>,
+    state_slot: &mut ReusableSlot<'static>,
+) -> JlrsResult<Option<T>> {
+    let iterate = Module::typed_global_cached::<Value, _, _>(&frame, "Base.iterate")?;
+    let next = match *state {
+        Some(s) => iterate.call_async(&mut *frame, [iterable, s]).await,
+        None => iterate.call_async(&mut *frame, [iterable]).await,
+    }
+    .into_jlrs_result()?;
+
+    if next.is::<Nothing>() {
+        return Ok(None);
+    }
+
+    let elem = next.get_nth_field(&mut *frame, 0)?;
+    *state = Some(next.get_nth_field(&mut *state_slot, 1)?.as_value());
+    T::from_julia(&frame, elem).map(Some)
+}
+
 pub(crate) struct BlockingTask<F, O, T> {
     func: F,
     sender: O,
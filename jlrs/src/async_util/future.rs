@@ -1,4 +1,12 @@
-use std::{ffi::c_void, fmt::Display, marker::PhantomData, pin::Pin, ptr::NonNull, sync::Arc};
+use std::{
+    ffi::c_void,
+    fmt::Display,
+    marker::PhantomData,
+    pin::Pin,
+    ptr::NonNull,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::{
     task::{Context, Poll, Waker},
@@ -10,7 +18,9 @@ use jlrs_macros::julia_version;
 use crate::{
     args::Values,
     call::{Call, WithKeywords},
+    convert::into_jlrs_result::IntoJlrsResult,
     data::managed::{
+        datatype::DataType,
         erase_scope_lifetime,
         module::{JlrsCore, Module},
         private::ManagedPriv,
@@ -18,12 +28,15 @@ use crate::{
         value::Value,
         Managed,
     },
-    error::{JuliaResult, CANNOT_DISPLAY_VALUE},
+    error::{CancellationError, JlrsResult, JuliaResult, CANNOT_DISPLAY_VALUE},
     gc_safe::GcSafeMutex,
     memory::target::{frame::AsyncGcFrame, private::TargetPriv, unrooted::Unrooted},
     private::Private,
 };
 
+#[cfg(feature = "async-rt")]
+use crate::async_util::cancellation::{current_cancellation, CancellationState};
+
 pub(crate) struct TaskState<'frame, 'data> {
     completed: bool,
     waker: Option<Waker>,
@@ -53,6 +66,8 @@ impl Display for AsyncMethod {
 
 pub(crate) struct JuliaFuture<'frame, 'data> {
     shared_state: Arc<GcSafeMutex<TaskState<'frame, 'data>>>,
+    #[cfg(feature = "async-rt")]
+    cancellation: Option<Arc<CancellationState>>,
 }
 
 impl<'frame, 'data> JuliaFuture<'frame, 'data> {
@@ -132,12 +147,7 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
                 .cast_unchecked::<Task>()
         };
 
-        {
-            let mut locked = shared_state.lock();
-            locked.task = Some(task);
-        }
-
-        JuliaFuture { shared_state }
+        Self::with_task(shared_state, task)
     }
 
     #[inline]
@@ -238,12 +248,7 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
                 .cast_unchecked::<Task>()
         };
 
-        {
-            let mut locked = shared_state.lock();
-            locked.task = Some(task);
-        }
-
-        JuliaFuture { shared_state }
+        Self::with_task(shared_state, task)
     }
 
     fn new_future_with_keywords<'kw, 'value, V, const N: usize>(
@@ -316,12 +321,50 @@ impl<'frame, 'data> JuliaFuture<'frame, 'data> {
                 .cast_unchecked::<Task>()
         };
 
+        Self::with_task(shared_state, task)
+    }
+
+    // Returns the task that is awaited by this future.
+    #[inline]
+    pub(crate) fn task(&self) -> Task<'frame> {
+        // JuliaFuture is not created if task cannot be set
+        self.shared_state.lock().task.unwrap()
+    }
+
+    fn with_task(
+        shared_state: Arc<GcSafeMutex<TaskState<'frame, 'data>>>,
+        task: Task<'frame>,
+    ) -> Self {
         {
             let mut locked = shared_state.lock();
             locked.task = Some(task);
         }
 
-        JuliaFuture { shared_state }
+        // If the async task is cancelled while this future is pending, the Julia task is
+        // interrupted.
+        #[cfg(feature = "async-rt")]
+        let cancellation = current_cancellation();
+        #[cfg(feature = "async-rt")]
+        if let Some(cancellation) = cancellation.as_ref() {
+            cancellation.set_julia_task(task);
+        }
+
+        JuliaFuture {
+            shared_state,
+            #[cfg(feature = "async-rt")]
+            cancellation,
+        }
+    }
+}
+
+#[cfg(feature = "async-rt")]
+impl<'frame, 'data> Drop for JuliaFuture<'frame, 'data> {
+    fn drop(&mut self) {
+        if let Some(cancellation) = self.cancellation.as_ref() {
+            if let Some(task) = self.shared_state.lock().task {
+                cancellation.clear_julia_task(task);
+            }
+        }
     }
 }
 
@@ -368,6 +411,66 @@ impl<'frame, 'data> Future for JuliaFuture<'frame, 'data> {
     }
 }
 
+// Awaits `future`, the task is interrupted if it hasn't completed before `timeout` has elapsed.
+pub(crate) async fn with_timeout<'frame, 'data>(
+    frame: &mut AsyncGcFrame<'frame>,
+    future: JuliaFuture<'frame, 'data>,
+    timeout: Duration,
+) -> JlrsResult<JuliaResult<'frame, 'data>> {
+    // Safety: module contents are globally rooted, JlrsCancellation is defined when the async
+    // runtime is initialized and the task is rooted.
+    let timer = unsafe {
+        Module::typed_global_cached::<Value, _, _>(&frame, "Main.JlrsCancellation.interruptafter")
+            .and_then(|interrupt_after| {
+                let secs = Value::new(&mut *frame, timeout.as_secs_f64());
+                interrupt_after
+                    .call2(&mut *frame, future.task().as_value(), secs)
+                    .into_jlrs_result()
+            })
+    };
+
+    let timer = match timer {
+        Ok(timer) => timer,
+        Err(e) => {
+            // The task has already been scheduled. It's interrupted and awaited before the error
+            // is returned so it never outlives this call.
+            #[cfg(feature = "async-rt")]
+            // Safety: the task is rooted.
+            unsafe {
+                crate::async_util::cancellation::interrupt(future.task());
+            }
+
+            future.await.ok();
+            return Err(e);
+        }
+    };
+
+    let start = Instant::now();
+    let res = future.await;
+
+    // Safety: module contents are globally rooted, closing a timer is safe.
+    unsafe {
+        let close = Module::typed_global_cached::<Value, _, _>(&frame, "Base.close")?;
+        close.call1(Unrooted::new(), timer).ok();
+    }
+
+    match res {
+        Err(e) if start.elapsed() >= timeout => {
+            // Safety: module contents are globally rooted.
+            let interrupt_exception = unsafe {
+                Module::typed_global_cached::<DataType, _, _>(&frame, "Base.InterruptException")?
+            };
+
+            if e.isa(interrupt_exception.as_value()) {
+                Err(CancellationError::TimedOut { timeout })?
+            }
+
+            Ok(Err(e))
+        }
+        res => Ok(res),
+    }
+}
+
 // This function is called using `ccall` to indicate a task has completed.
 #[cfg(feature = "async-rt")]
 pub(crate) unsafe extern "C" fn wake_task(state: *const GcSafeMutex<TaskState>) {
@@ -1,8 +1,14 @@
 //! Async tasks and channels that can be used with an async runtime.
 
 pub mod affinity;
+#[cfg(feature = "async-rt")]
+pub mod bridge;
+#[cfg(feature = "async-rt")]
+pub mod cancellation;
 pub mod channel;
 #[cfg(feature = "async-rt")]
 pub(crate) mod envelope;
 pub(crate) mod future;
+#[cfg(feature = "async-rt")]
+pub mod stream;
 pub mod task;
@@ -0,0 +1,63 @@
+//! Stream data from Julia to Rust.
+//!
+//! Julia producers like a `Channel` or an iterator over the steps of a simulation can be consumed
+//! incrementally from Rust with [`AsyncJulia::stream`]. The runtime iterates over the Julia
+//! object by calling `Base.iterate` as a new Julia task for every element, converts every element
+//! with [`FromJulia`], and sends the result to a channel. The receiving half of this channel is
+//! wrapped in a [`JuliaStream`], which implements `futures::Stream`.
+//!
+//! The channel must implement the traits from [`async_util::channel`]. If it's bounded the
+//! runtime stops iterating while the channel is full, so a slow consumer applies backpressure to
+//! the Julia producer. When the stream is dropped the runtime stops iterating at the next element.
+//!
+//! [`AsyncJulia::stream`]: crate::runtime::async_rt::AsyncJulia::stream
+//! [`FromJulia`]: crate::convert::from_julia::FromJulia
+//! [`async_util::channel`]: crate::async_util::channel
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{stream, Stream};
+
+use crate::{async_util::channel::ChannelReceiver, error::JlrsResult};
+
+/// A stream of values produced by a Julia iterable.
+///
+/// Every item is the next element of the iterable converted to `T`, or the error that occurred
+/// while it was computed or converted. The stream ends after the iterable has been exhausted or
+/// an error has been returned.
+pub struct JuliaStream<T> {
+    inner: Pin<Box<dyn Stream<Item = JlrsResult<T>> + Send>>,
+}
+
+impl<T> JuliaStream<T>
+where
+    T: Send + 'static,
+{
+    pub(crate) fn new<R>(receiver: R) -> Self
+    where
+        R: ChannelReceiver<JlrsResult<T>>,
+    {
+        let inner = stream::unfold(receiver, |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(item) => Some((item, receiver)),
+                Err(_) => None,
+            }
+        });
+
+        JuliaStream {
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl<T> Stream for JuliaStream<T> {
+    type Item = JlrsResult<T>;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
@@ -382,9 +382,10 @@ cfg_if::cfg_if! {
                 function::Function
             },
             async_util::{
-                future::JuliaFuture,
+                future::{with_timeout, JuliaFuture},
             }
         };
+        use std::time::Duration;
 
         /// This trait provides async methods to create and schedule `Task`s that resolve when the
         /// `Task` has completed. Sync methods are also provided which only schedule the `Task`,
@@ -451,6 +452,33 @@ cfg_if::cfg_if! {
                 Ok(res)
             }
 
+            /// Creates and schedules a new task with `Base.Threads.@spawn`, and returns a future
+            /// that resolves when this task is finished.
+            ///
+            /// If the task hasn't completed after `timeout` has elapsed, an `InterruptException` is
+            /// thrown into it. If the task is stopped by this exception,
+            /// [`CancellationError::TimedOut`] is returned. Julia tasks can only be interrupted at
+            /// yield points, so the future resolves only after the task has been interrupted.
+            ///
+            /// Since Julia 1.9 this task is spawned on the `:default` thread pool.
+            ///
+            /// # Safety
+            ///
+            /// This method lets you call arbitrary Julia functions which can't be checked for
+            /// correctness. More information can be found in the [`safety`] module. This method doesn't
+            /// check if any of the arguments is currently borrowed from Rust.
+            ///
+            /// [`safety`]: crate::safety
+            /// [`CancellationError::TimedOut`]: crate::error::CancellationError::TimedOut
+            async unsafe fn call_async_with_timeout<'target, 'value, V, const N: usize>(
+                self,
+                frame: &mut AsyncGcFrame<'target>,
+                args: V,
+                timeout: Duration,
+            ) -> JlrsResult<JuliaResult<'target, 'data>>
+            where
+                V: Values<'value, 'data, N>;
+
             /// Does the same thing as [`CallAsync::call_async`], but the task is returned rather than an
             /// awaitable `Future`. This method should only be called in [`PersistentTask::init`],
             /// otherwise it's not guaranteed this task can make progress.
@@ -902,6 +930,20 @@ cfg_if::cfg_if! {
                 JuliaFuture::new(frame, erase_scope_lifetime(self), args).await
             }
 
+            #[inline]
+            async unsafe fn call_async_with_timeout<'target, 'value, V, const N: usize>(
+                self,
+                frame: &mut AsyncGcFrame<'target>,
+                args: V,
+                timeout: Duration,
+            ) -> JlrsResult<JuliaResult<'target, 'data>>
+            where
+                V: Values<'value, 'data, N>,
+            {
+                let future = JuliaFuture::new(frame, erase_scope_lifetime(self), args);
+                with_timeout(frame, future, timeout).await
+            }
+
             #[julia_version(since = "1.9")]
             #[inline]
             async unsafe fn call_async_interactive<'target, 'value, V, const N: usize>(
@@ -1035,6 +1077,19 @@ cfg_if::cfg_if! {
                 JuliaFuture::new(frame, erase_scope_lifetime(self.as_value()), args).await
             }
 
+            #[inline]
+            async unsafe fn call_async_with_timeout<'target, 'value, V, const N: usize>(
+                self,
+                frame: &mut AsyncGcFrame<'target>,
+                args: V,
+                timeout: Duration,
+            ) -> JlrsResult<JuliaResult<'target, 'data>>
+            where
+                V: Values<'value, 'data, N>,
+            {
+                self.as_value().call_async_with_timeout(frame, args, timeout).await
+            }
+
             #[julia_version(since = "1.9")]
             #[inline]
             async unsafe fn call_async_interactive<'target, 'value, V, const N: usize>(
@@ -1136,6 +1191,20 @@ cfg_if::cfg_if! {
                 JuliaFuture::new_with_keywords(frame, self, args).await
             }
 
+            #[inline]
+            async unsafe fn call_async_with_timeout<'target, 'value, V, const N: usize>(
+                self,
+                frame: &mut AsyncGcFrame<'target>,
+                args: V,
+                timeout: Duration,
+            ) -> JlrsResult<JuliaResult<'target, 'data>>
+            where
+                V: Values<'value, 'data, N>,
+            {
+                let future = JuliaFuture::new_with_keywords(frame, self, args);
+                with_timeout(frame, future, timeout).await
+            }
+
             #[julia_version(since = "1.9")]
             #[inline]
             async unsafe fn call_async_interactive<'target, 'value, V, const N: usize>(
@@ -5,12 +5,13 @@
 // TODO
 
 use std::{
-    cell::UnsafeCell,
+    cell::{RefCell, UnsafeCell},
     ffi::c_void,
-    fmt::Debug,
+    fmt::{Debug, Display, Formatter},
     hint::spin_loop,
+    panic::{catch_unwind, AssertUnwindSafe},
     ptr::NonNull,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{atomic::AtomicBool, Arc, Once},
 };
 
 use atomic::Ordering;
@@ -26,6 +27,7 @@ use crate::{
         managed::{
             module::{JlrsCore, Module},
             private::ManagedPriv,
+            string::JuliaString,
             symbol::Symbol,
             value::{Value, ValueRet},
             Managed,
@@ -44,6 +46,7 @@ use crate::{
         },
     },
     private::Private,
+    redirect::{redirect_output, restore_output, StdStream},
     InstallJlrsCore,
 };
 
@@ -59,6 +62,29 @@ thread_local! {
     }
 }
 
+// The location of a panic isn't available from its payload, the panic hook installed by
+// `CCall::catch_panic` stores it here before calling the previous hook.
+static PANIC_HOOK: Once = Once::new();
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let prev_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(location) = info.location() {
+                let location = location.to_string();
+                PANIC_LOCATION
+                    .try_with(|loc| *loc.borrow_mut() = Some(location))
+                    .ok();
+            }
+
+            prev_hook(info)
+        }));
+    });
+}
+
 unsafe fn init_pool() -> &'static GcSafeMutex<ThreadPool> {
     POOL.get_or_init(|| {
         let name = POOL_NAME.get_or_init(|| {
@@ -89,6 +115,34 @@ unsafe extern "C" fn set_pool_name(module: Module) {
     });
 }
 
+/// A panic caught by [`CCall::catch_panic`].
+#[derive(Debug)]
+pub struct CaughtPanic {
+    message: String,
+    location: Option<String>,
+}
+
+impl CaughtPanic {
+    /// The panic message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The location where the panic occurred, if it's available.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+}
+
+impl Display for CaughtPanic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.location {
+            Some(ref location) => write!(f, "Rust panicked at {}: {}", location, self.message),
+            None => write!(f, "Rust panicked: {}", self.message),
+        }
+    }
+}
+
 /// Interact with Julia from a Rust function called through `ccall`.
 ///
 /// When you call Rust from Julia through `ccall`, Julia has already been initialized and trying to
@@ -139,6 +193,25 @@ impl<'context> CCall<'context> {
         }
     }
 
+    /// Redirect Julia's `stdout` or `stderr` to `writer`. Everything Julia writes to the
+    /// redirected stream is written to `writer` until the stream is restored with
+    /// [`CCall::restore_output`].
+    ///
+    /// See the [`redirect`] module for more information.
+    ///
+    /// [`redirect`]: crate::redirect
+    pub fn redirect_output<W>(&mut self, stream: StdStream, writer: W) -> JlrsResult<()>
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        self.scope(|frame| redirect_output(&frame, stream, writer))
+    }
+
+    /// Restore Julia's original `stdout` or `stderr`.
+    pub fn restore_output(&mut self, stream: StdStream) -> JlrsResult<()> {
+        self.scope(|frame| restore_output(&frame, stream))
+    }
+
     /// Create a [`LocalGcFrame`], call the given closure, and return its result.
     #[inline]
     pub unsafe fn local_scope<T, F, const N: usize>(func: F) -> JlrsResult<T>
@@ -252,6 +325,51 @@ impl<'context> CCall<'context> {
         jl_throw(exception.ptr().as_ptr())
     }
 
+    /// Call `func` and catch the panic if it panics.
+    ///
+    /// The caught panic contains the panic message and the location where the panic occurred.
+    /// The location is only available if the panic hook hasn't been replaced after the first
+    /// call to this method. This method is called by the wrappers of functions and methods
+    /// exported with the `#[catch_unwind]` attribute by the `julia_module` macro.
+    pub fn catch_panic<T, F>(func: F) -> Result<T, CaughtPanic>
+    where
+        F: FnOnce() -> T,
+    {
+        install_panic_hook();
+
+        match catch_unwind(AssertUnwindSafe(func)) {
+            Ok(res) => Ok(res),
+            Err(payload) => {
+                let message = if let Some(msg) = payload.downcast_ref::<&str>() {
+                    msg.to_string()
+                } else if let Some(msg) = payload.downcast_ref::<String>() {
+                    msg.clone()
+                } else {
+                    String::from("Box<dyn Any>")
+                };
+
+                let location = PANIC_LOCATION.with(|loc| loc.borrow_mut().take());
+                Err(CaughtPanic { message, location })
+            }
+        }
+    }
+
+    /// Throw a `JlrsCore.JlrsError` that contains the message and location of `panic`.
+    ///
+    /// # Safety
+    ///
+    /// The same restrictions apply as for [`CCall::throw_exception`].
+    pub unsafe fn throw_panic(panic: CaughtPanic) -> ! {
+        let exception = CCall::local_scope::<_, _, 1>(|mut frame| {
+            let msg = JuliaString::new(&mut frame, panic.to_string()).as_value();
+            let err = JlrsCore::jlrs_error(&frame).instantiate_unchecked(&frame, [msg]);
+            Ok(err.leak())
+        })
+        .unwrap();
+
+        CCall::throw_exception(exception)
+    }
+
     #[inline]
     pub unsafe fn throw_borrow_exception() -> ! {
         let unrooted = Unrooted::new();
@@ -14,12 +14,17 @@
 //! that are used to construct the appropriate types to be used in the function and ccall
 //! signatures.
 //!
+//! An exported function can throw a specific Julia exception by returning `Result<T, E>` where
+//! `E` implements [`IntoJuliaException`].
+//!
 //! You shoudldn't manually implement these traits, they're automatically implemented
 //! by `JlrsCore.Reflect` if supported.
 //!
 //! [`julia_module`]: ::jlrs_macros::julia_module
+//! [`IntoJuliaException`]: crate::convert::into_julia_exception::IntoJuliaException
 
 use crate::{
+    convert::into_julia_exception::IntoJuliaException,
     data::{
         managed::{module::JlrsCore, value::ValueRet},
         types::construct_type::ConstructType,
@@ -137,6 +142,46 @@ unsafe impl CCallReturn for () {
     }
 }
 
+unsafe impl<T: CCallReturn, E: IntoJuliaException> CCallReturn for Result<T, E> {
+    type FunctionReturnType = T::FunctionReturnType;
+    type CCallReturnType = T::CCallReturnType;
+    type ReturnAs = T;
+
+    #[inline]
+    unsafe fn return_or_throw(self) -> Self::ReturnAs {
+        #[cfg(feature = "ccall")]
+        {
+            match self {
+                Ok(t) => t,
+                Err(e) => {
+                    let e = crate::ccall::CCall::local_scope::<_, _, 2>(|mut frame| {
+                        match e.into_julia_exception(&mut frame) {
+                            Ok(exc) => Ok(exc.leak()),
+                            Err(e) => {
+                                let msg = JuliaString::new(
+                                    &mut frame,
+                                    format!("cannot convert error to exception: {}", e),
+                                )
+                                .as_value();
+                                let err = JlrsCore::jlrs_error(&frame)
+                                    .instantiate_unchecked(&frame, [msg]);
+                                Ok(err.leak())
+                            }
+                        }
+                    })
+                    .unwrap();
+                    crate::ccall::CCall::throw_exception(e)
+                }
+            }
+        }
+
+        #[cfg(not(feature = "ccall"))]
+        unimplemented!(
+            "CCallReturn::return_or_throw can only be called if the `ccall` feature is enabled"
+        )
+    }
+}
+
 unsafe impl<T: CCallReturn> CCallReturn for JlrsResult<T> {
     type FunctionReturnType = T::FunctionReturnType;
     type CCallReturnType = T::CCallReturnType;
@@ -0,0 +1,704 @@
+//! Convert Julia data, including strings and collections, to owned Rust data.
+//!
+//! [`Unbox`] is limited to data that can be copied from Julia to Rust as is, which makes it
+//! impossible to use it to convert a `Vector{String}` or a `Dict{Symbol, Any}` to Rust. The
+//! [`FromJulia`] trait defined in this module doesn't have this limitation, it walks the Julia
+//! data and converts it to owned Rust data. It's implemented for primitive types, strings,
+//! `Option`s, tuples, and most collections from the standard library:
+//!
+//! | Julia type                              | Rust type                               |
+//! |-----------------------------------------|-----------------------------------------|
+//! | `Bool`, `Char`, `Int8`, ..., `Float64`  | `bool`, `char`, `i8`, ..., `f64`        |
+//! | `String`, `Symbol`                      | `String`                                |
+//! | `Union{Nothing, T}`                     | `Option<T>`                             |
+//! | `Vector{T}`                             | `Vec<T>`, `VecDeque<T>`                 |
+//! | `AbstractDict{K, V}`, `NamedTuple`      | `HashMap<K, V>`, `BTreeMap<K, V>`       |
+//! | `AbstractSet{T}`, `Vector{T}`           | `HashSet<T>`, `BTreeSet<T>`             |
+//! | `Tuple{T1, T2, ...}`                    | `(T1, T2, ...)`                         |
+//!
+//! These implementations can be nested arbitrarily, e.g. a `Vector{Dict{Symbol, Any}}` can be
+//! converted to a `Vec<HashMap<String, Option<f64>>>` as long as all values are either `nothing`
+//! or a `Float64`.
+//!
+//! `FromJulia` can be derived for structs whose fields all implement `FromJulia`. The fields are
+//! looked up by name, so the derived implementation can be used with any Julia value that has
+//! fields with matching names and compatible types, including `NamedTuple`s. Fields of tuple
+//! structs are looked up by position.
+//!
+//! The path of the data that is being converted is tracked with a [`ConversionPath`]. If the
+//! conversion fails, the error contains this path to make it easy to find what data couldn't be
+//! converted, e.g. `x.points[3].label: expected String, found Int64`. Indices in this path are
+//! 1-based like they are in Julia.
+//!
+//! [`Unbox`]: crate::convert::unbox::Unbox
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::{nothing::Nothing, tuple::Tuple},
+        managed::{
+            array::{dimensions::Dims, Array},
+            function::Function,
+            string::JuliaString,
+            symbol::Symbol,
+            value::Value,
+            Managed,
+        },
+        types::typecheck::NamedTuple,
+    },
+    error::{JlrsError, JlrsResult, TypeError, CANNOT_DISPLAY_TYPE},
+    inline_static_ref,
+    memory::target::{unrooted::Unrooted, Target},
+};
+
+/// Trait implemented by types that can be created from Julia data with
+/// [`FromJulia::from_julia`].
+///
+/// Unlike [`Unbox`], this trait isn't limited to data that can be copied as is. See the
+/// [module-level docs] for an overview of the types that implement this trait.
+///
+/// [`Unbox`]: crate::convert::unbox::Unbox
+/// [module-level docs]: self
+pub trait FromJulia: Sized {
+    /// Convert `value` to `Self`.
+    ///
+    /// If the conversion fails, the path in the returned error starts with `value`. The `target`
+    /// is only used to root temporary data.
+    #[inline]
+    fn from_julia<'target, Tgt>(target: Tgt, value: Value) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        Self::from_julia_named(target, value, "value")
+    }
+
+    /// Convert `value` to `Self`.
+    ///
+    /// If the conversion fails, the path in the returned error starts with `name`. The `target`
+    /// is only used to root temporary data.
+    #[inline]
+    fn from_julia_named<'target, Tgt>(target: Tgt, value: Value, name: &str) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        Self::from_julia_at(target, value, &mut ConversionPath::new(name))
+    }
+
+    /// Convert `value`, which is found at `path`, to `Self`.
+    ///
+    /// Implementations that convert nested data must push the relevant segment to `path` before
+    /// converting that data, and pop it afterwards.
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>;
+}
+
+/// The path of the data that is being converted by [`FromJulia`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionPath {
+    root: String,
+    segments: Vec<PathSegment>,
+}
+
+/// A single segment of a [`ConversionPath`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /// A field, displayed as `.name`.
+    Field(String),
+    /// A 0-based index, displayed 1-based as `[n]`.
+    Index(usize),
+    /// A key of a dictionary, displayed as `[key]`.
+    Key(String),
+}
+
+impl ConversionPath {
+    /// Create a new path that starts at `root`.
+    #[inline]
+    pub fn new<S: Into<String>>(root: S) -> Self {
+        ConversionPath {
+            root: root.into(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Returns the segments of this path.
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Push a field to this path.
+    #[inline]
+    pub fn push_field<S: Into<String>>(&mut self, name: S) {
+        self.segments.push(PathSegment::Field(name.into()))
+    }
+
+    /// Push an index to this path. Indexing starts at 0.
+    #[inline]
+    pub fn push_index(&mut self, idx: usize) {
+        self.segments.push(PathSegment::Index(idx))
+    }
+
+    /// Push the key of a dictionary to this path.
+    #[inline]
+    pub fn push_key<S: Into<String>>(&mut self, key: S) {
+        self.segments.push(PathSegment::Key(key.into()))
+    }
+
+    /// Pop the last segment from this path.
+    #[inline]
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Returns a `TypeError::UnexpectedTypeAt` error for `value`, which was expected to be an
+    /// instance of `expected`.
+    pub fn unexpected_type(&self, expected: &str, value: Value) -> Box<JlrsError> {
+        TypeError::UnexpectedTypeAt {
+            path: self.to_string(),
+            expected: expected.into(),
+            found: value.datatype().display_string_or(CANNOT_DISPLAY_TYPE),
+        }
+        .into()
+    }
+
+    /// Returns a `TypeError::InvalidValueAt` error with `reason`.
+    pub fn invalid_value<S: Into<String>>(&self, reason: S) -> Box<JlrsError> {
+        TypeError::InvalidValueAt {
+            path: self.to_string(),
+            reason: reason.into(),
+        }
+        .into()
+    }
+}
+
+impl fmt::Display for ConversionPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.root)?;
+        for segment in self.segments.iter() {
+            match segment {
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::Index(idx) => write!(f, "[{}]", idx + 1)?,
+                PathSegment::Key(key) => write!(f, "[{}]", key)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert the field `name` of `value` to `T`.
+///
+/// This function is used by the derive macro, `name` is pushed to `path` while the field is
+/// converted.
+pub fn field_from_julia<'target, T, Tgt>(
+    target: Tgt,
+    value: Value,
+    name: &str,
+    path: &mut ConversionPath,
+) -> JlrsResult<T>
+where
+    T: FromJulia,
+    Tgt: Target<'target>,
+{
+    path.push_field(name);
+    let idx = value
+        .field_names()
+        .iter()
+        .position(|n| n.as_bytes() == name.as_bytes());
+    let res = match idx {
+        Some(idx) => nth_field(target, value, idx, path),
+        None => Err(TypeError::MissingFieldAt {
+            path: path.to_string(),
+            type_name: value.datatype().display_string_or(CANNOT_DISPLAY_TYPE),
+        }
+        .into()),
+    };
+    path.pop();
+    res
+}
+
+/// Convert the `idx`th field of `value` to `T`.
+///
+/// This function is used by the derive macro, `idx` is pushed to `path` while the field is
+/// converted.
+pub fn nth_field_from_julia<'target, T, Tgt>(
+    target: Tgt,
+    value: Value,
+    idx: usize,
+    path: &mut ConversionPath,
+) -> JlrsResult<T>
+where
+    T: FromJulia,
+    Tgt: Target<'target>,
+{
+    path.push_index(idx);
+    let res = if idx < value.n_fields() {
+        nth_field(target, value, idx, path)
+    } else {
+        Err(TypeError::MissingFieldAt {
+            path: path.to_string(),
+            type_name: value.datatype().display_string_or(CANNOT_DISPLAY_TYPE),
+        }
+        .into())
+    };
+    path.pop();
+    res
+}
+
+macro_rules! impl_from_julia_primitive {
+    ($($type:ty => $name:literal),+) => {
+        $(
+            impl FromJulia for $type {
+                #[inline]
+                fn from_julia_at<'target, Tgt>(
+                    _: Tgt,
+                    value: Value,
+                    path: &mut ConversionPath,
+                ) -> JlrsResult<Self>
+                where
+                    Tgt: Target<'target>,
+                {
+                    if !value.is::<$type>() {
+                        return Err(path.unexpected_type($name, value));
+                    }
+
+                    // Safety: the type has been checked.
+                    unsafe { Ok(value.unbox_unchecked::<$type>()) }
+                }
+            }
+        )+
+    };
+}
+
+impl_from_julia_primitive!(
+    u8 => "UInt8",
+    u16 => "UInt16",
+    u32 => "UInt32",
+    u64 => "UInt64",
+    usize => "UInt",
+    i8 => "Int8",
+    i16 => "Int16",
+    i32 => "Int32",
+    i64 => "Int64",
+    isize => "Int",
+    f32 => "Float32",
+    f64 => "Float64"
+);
+
+impl FromJulia for bool {
+    #[inline]
+    fn from_julia_at<'target, Tgt>(
+        _: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        if !value.is::<bool>() {
+            return Err(path.unexpected_type("Bool", value));
+        }
+
+        // Safety: the type has been checked.
+        unsafe { Ok(value.unbox_unchecked::<bool>().as_bool()) }
+    }
+}
+
+impl FromJulia for char {
+    #[inline]
+    fn from_julia_at<'target, Tgt>(
+        _: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        if !value.is::<char>() {
+            return Err(path.unexpected_type("Char", value));
+        }
+
+        // Safety: the type has been checked.
+        unsafe {
+            value
+                .unbox_unchecked::<char>()
+                .try_as_char()
+                .ok_or_else(|| path.invalid_value("invalid Unicode scalar value"))
+        }
+    }
+}
+
+impl FromJulia for String {
+    fn from_julia_at<'target, Tgt>(
+        _: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        // Safety: the types have been checked.
+        let s = unsafe {
+            if value.is::<JuliaString>() {
+                value.cast_unchecked::<JuliaString>().as_str()
+            } else if value.is::<Symbol>() {
+                value.cast_unchecked::<Symbol>().as_str()
+            } else {
+                return Err(path.unexpected_type("String", value));
+            }
+        };
+
+        match s {
+            Ok(s) => Ok(s.into()),
+            Err(_) => Err(path.invalid_value("string is not valid UTF-8")),
+        }
+    }
+}
+
+impl<T: FromJulia> FromJulia for Option<T> {
+    #[inline]
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        if value.is::<Nothing>() {
+            Ok(None)
+        } else {
+            T::from_julia_at(target, value, path).map(Some)
+        }
+    }
+}
+
+impl<T: FromJulia> FromJulia for Vec<T> {
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut out = Vec::new();
+        for_each_element(target, value, path, |target, elem, path| {
+            out.push(T::from_julia_at(target, elem, path)?);
+            Ok(())
+        })?;
+
+        Ok(out)
+    }
+}
+
+impl<T: FromJulia> FromJulia for VecDeque<T> {
+    #[inline]
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        Vec::from_julia_at(target, value, path).map(VecDeque::from)
+    }
+}
+
+impl<K, V, S> FromJulia for HashMap<K, V, S>
+where
+    K: FromJulia + Eq + Hash,
+    V: FromJulia,
+    S: BuildHasher + Default,
+{
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut out = HashMap::default();
+        for_each_pair(target, value, path, |target, k, v, path| {
+            let k = K::from_julia_at(target, k, path)?;
+            let v = V::from_julia_at(target, v, path)?;
+            out.insert(k, v);
+            Ok(())
+        })?;
+
+        Ok(out)
+    }
+}
+
+impl<K, V> FromJulia for BTreeMap<K, V>
+where
+    K: FromJulia + Ord,
+    V: FromJulia,
+{
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut out = BTreeMap::new();
+        for_each_pair(target, value, path, |target, k, v, path| {
+            let k = K::from_julia_at(target, k, path)?;
+            let v = V::from_julia_at(target, v, path)?;
+            out.insert(k, v);
+            Ok(())
+        })?;
+
+        Ok(out)
+    }
+}
+
+impl<T, S> FromJulia for HashSet<T, S>
+where
+    T: FromJulia + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut out = HashSet::default();
+        for_each_set_element(target, value, path, |target, elem, path| {
+            out.insert(T::from_julia_at(target, elem, path)?);
+            Ok(())
+        })?;
+
+        Ok(out)
+    }
+}
+
+impl<T> FromJulia for BTreeSet<T>
+where
+    T: FromJulia + Ord,
+{
+    fn from_julia_at<'target, Tgt>(
+        target: Tgt,
+        value: Value,
+        path: &mut ConversionPath,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut out = BTreeSet::new();
+        for_each_set_element(target, value, path, |target, elem, path| {
+            out.insert(T::from_julia_at(target, elem, path)?);
+            Ok(())
+        })?;
+
+        Ok(out)
+    }
+}
+
+macro_rules! impl_from_julia_tuple {
+    ($n:expr, $($types:ident => $idx:tt),+) => {
+        impl<$($types: FromJulia),+> FromJulia for ($($types,)+) {
+            fn from_julia_at<'target, Tgt>(
+                target: Tgt,
+                value: Value,
+                path: &mut ConversionPath,
+            ) -> JlrsResult<Self>
+            where
+                Tgt: Target<'target>,
+            {
+                if !value.is::<Tuple>() {
+                    return Err(path.unexpected_type("Tuple", value));
+                }
+
+                let n_fields = value.n_fields();
+                if n_fields != $n {
+                    return Err(path.invalid_value(format!(
+                        "expected a tuple with {} elements, found {} elements",
+                        $n, n_fields
+                    )));
+                }
+
+                Ok(($(nth_field_from_julia::<$types, _>(&target, value, $idx, path)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_julia_tuple!(1, T1 => 0);
+impl_from_julia_tuple!(2, T1 => 0, T2 => 1);
+impl_from_julia_tuple!(3, T1 => 0, T2 => 1, T3 => 2);
+impl_from_julia_tuple!(4, T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+impl_from_julia_tuple!(5, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4);
+impl_from_julia_tuple!(6, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5);
+impl_from_julia_tuple!(7, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6);
+impl_from_julia_tuple!(8, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6, T8 => 7);
+
+// Convert the `idx`th field of `value`, which must exist, to `T`.
+fn nth_field<'target, T, Tgt>(
+    target: Tgt,
+    value: Value,
+    idx: usize,
+    path: &mut ConversionPath,
+) -> JlrsResult<T>
+where
+    T: FromJulia,
+    Tgt: Target<'target>,
+{
+    target.local_scope::<_, _, 1>(|mut frame| {
+        let field = value
+            .get_nth_field(&mut frame, idx)
+            .map_err(|_| path.invalid_value("field is undefined"))?;
+        T::from_julia_at(&frame, field, path)
+    })
+}
+
+// Call `func` with each element of `value`, which must be a `Vector`.
+fn for_each_element<'target, Tgt, F>(
+    target: Tgt,
+    value: Value,
+    path: &mut ConversionPath,
+    mut func: F,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+    F: FnMut(Unrooted, Value, &mut ConversionPath) -> JlrsResult<()>,
+{
+    if !value.is::<Array>() {
+        return Err(path.unexpected_type("Vector", value));
+    }
+
+    // Safety: the type has been checked.
+    let arr = unsafe { value.cast_unchecked::<Array>() };
+    // Safety: the dimensions aren't mutated while they're in use.
+    let dims = unsafe { arr.dimensions() };
+    if dims.rank() != 1 {
+        return Err(path.unexpected_type("Vector", value));
+    }
+
+    let len = dims.size();
+    // Safety: the array isn't mutated while it's being accessed.
+    let mut accessor = unsafe { arr.indeterminate_data() };
+    for idx in 0..len {
+        path.push_index(idx);
+        let res = target.local_scope::<_, _, 1>(|mut frame| {
+            let elem = match accessor.get_value(&mut frame, idx)? {
+                Some(elem) => elem.into_jlrs_result()?,
+                None => return Err(path.invalid_value("element is undefined")),
+            };
+
+            func(frame.unrooted(), elem, path)
+        });
+        path.pop();
+        res?;
+    }
+
+    Ok(())
+}
+
+// Call `func` with each element of `value`, which must be an `AbstractSet` or `Vector`.
+fn for_each_set_element<'target, Tgt, F>(
+    target: Tgt,
+    value: Value,
+    path: &mut ConversionPath,
+    func: F,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+    F: FnMut(Unrooted, Value, &mut ConversionPath) -> JlrsResult<()>,
+{
+    if value.is::<Array>() {
+        return for_each_element(target, value, path, func);
+    }
+
+    target.local_scope::<_, _, 1>(|mut frame| {
+        let abstract_set = inline_static_ref!(ABSTRACT_SET, Value, "Base.AbstractSet", frame);
+        if !value.isa(abstract_set) {
+            return Err(path.unexpected_type("AbstractSet", value));
+        }
+
+        let collect = inline_static_ref!(COLLECT, Function, "Base.collect", frame);
+        // Safety: collecting a set is safe.
+        let elements = unsafe { collect.call1(&mut frame, value).into_jlrs_result()? };
+        for_each_element(&frame, elements, path, func)
+    })
+}
+
+// Call `func` with each key-value pair of `value`, which must be an `AbstractDict` or
+// `NamedTuple`. The key is pushed to `path` before `func` is called.
+fn for_each_pair<'target, Tgt, F>(
+    target: Tgt,
+    value: Value,
+    path: &mut ConversionPath,
+    mut func: F,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+    F: FnMut(Unrooted, Value, Value, &mut ConversionPath) -> JlrsResult<()>,
+{
+    if value.is::<NamedTuple>() {
+        for (idx, name) in value.field_names().iter().copied().enumerate() {
+            path.push_key(name.as_str().unwrap_or("<Non-UTF8 symbol>"));
+            let res = target.local_scope::<_, _, 1>(|mut frame| {
+                let v = value
+                    .get_nth_field(&mut frame, idx)
+                    .map_err(|_| path.invalid_value("field is undefined"))?;
+                func(frame.unrooted(), name.as_value(), v, path)
+            });
+            path.pop();
+            res?;
+        }
+
+        return Ok(());
+    }
+
+    target.local_scope::<_, _, 1>(|mut frame| {
+        let abstract_dict = inline_static_ref!(ABSTRACT_DICT, Value, "Base.AbstractDict", frame);
+        if !value.isa(abstract_dict) {
+            return Err(path.unexpected_type("AbstractDict", value));
+        }
+
+        let collect = inline_static_ref!(COLLECT, Function, "Base.collect", frame);
+        // Safety: collecting a dict is safe, the result is a Vector of Pairs.
+        let pairs = unsafe { collect.call1(&mut frame, value).into_jlrs_result()? };
+        let arr = unsafe { pairs.cast_unchecked::<Array>() };
+        let len = unsafe { arr.dimensions().size() };
+        let mut accessor = unsafe { arr.indeterminate_data() };
+
+        for idx in 0..len {
+            frame.local_scope::<_, _, 3>(|mut frame| {
+                let pair = accessor
+                    .get_value(&mut frame, idx)?
+                    .unwrap()
+                    .into_jlrs_result()?;
+                let k = pair.get_nth_field(&mut frame, 0)?;
+                let v = pair.get_nth_field(&mut frame, 1)?;
+
+                path.push_key(k.display_string_or("<Cannot display key>"));
+                let res = func(frame.unrooted(), k, v, path);
+                path.pop();
+                res
+            })?;
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,101 @@
+//! Convert Julia exceptions to Rust errors.
+//!
+//! When a Julia function throws an exception it's returned as a `Value`, finding out what went
+//! wrong requires checking its type and extracting its fields. The [`FromJuliaException`] trait
+//! lets you declare a Rust enum whose variants correspond to Julia exception types, and
+//! [`MapException::map_exception`] converts the exception in a `JuliaResult` to that enum if its
+//! type matches one of those variants.
+//!
+//! `FromJuliaException` can be derived for enums. Every variant must be annotated with the Julia
+//! type it corresponds to with the `julia_type` attribute. The variants are checked in
+//! declaration order, so a variant for a subtype must be declared before a variant for its
+//! supertype. The fields of a variant are extracted from the exception with [`FromJulia`], named
+//! fields are looked up by name and unnamed fields by position:
+//!
+//! ```ignore
+//! #[derive(FromJuliaException, Debug)]
+//! enum MathError {
+//!     #[jlrs(julia_type = "Base.DomainError")]
+//!     Domain { msg: String },
+//!     #[jlrs(julia_type = "Base.DivideError")]
+//!     DivideByZero,
+//! }
+//!
+//! julia.scope(|mut frame| unsafe {
+//!     let res = Value::eval_string(&mut frame, "div(1, 0)").map_exception::<MathError>()?;
+//!     assert!(matches!(res, Err(MappedException::Matched(MathError::DivideByZero))));
+//!     Ok(())
+//! })?;
+//! ```
+//!
+//! [`FromJulia`]: crate::convert::from_julia::FromJulia
+
+use crate::{
+    data::managed::{value::Value, Managed},
+    error::{JlrsResult, JuliaResult},
+    memory::target::Target,
+};
+
+/// Trait implemented by Rust errors that can be created from Julia exceptions.
+///
+/// See the [module-level docs] for more information.
+///
+/// [module-level docs]: self
+pub trait FromJuliaException: Sized {
+    /// Convert `exception` to `Self`. Returns `Ok(None)` if `exception` doesn't correspond to
+    /// `Self`, and an error if it does but its fields can't be extracted.
+    ///
+    /// The `target` is only used to root temporary data.
+    fn from_julia_exception<'target, Tgt>(
+        target: Tgt,
+        exception: Value,
+    ) -> JlrsResult<Option<Self>>
+    where
+        Tgt: Target<'target>;
+}
+
+/// An exception that has been mapped with [`MapException::map_exception`].
+#[derive(Debug)]
+pub enum MappedException<'scope, 'data, E> {
+    /// The exception has been converted to `E`.
+    Matched(E),
+    /// The exception doesn't correspond to `E`.
+    Unmatched(Value<'scope, 'data>),
+}
+
+/// Extension trait that lets you map the exception in a `JuliaResult` to a Rust error.
+pub trait MapException<'scope, 'data, T>: private::MapExceptionPriv {
+    /// If an exception has been thrown, try to convert it to `E`. If the type of the exception
+    /// doesn't correspond to `E`, the original exception is returned as
+    /// `MappedException::Unmatched`.
+    ///
+    /// An error is returned if the exception corresponds to `E` but it can't be converted.
+    fn map_exception<E>(self) -> JlrsResult<Result<T, MappedException<'scope, 'data, E>>>
+    where
+        E: FromJuliaException;
+}
+
+impl<'scope, 'data, T> MapException<'scope, 'data, T> for JuliaResult<'scope, 'data, T> {
+    fn map_exception<E>(self) -> JlrsResult<Result<T, MappedException<'scope, 'data, E>>>
+    where
+        E: FromJuliaException,
+    {
+        match self {
+            Ok(v) => Ok(Ok(v)),
+            Err(exception) => {
+                let unrooted = exception.unrooted_target();
+                match E::from_julia_exception(unrooted, exception)? {
+                    Some(e) => Ok(Err(MappedException::Matched(e))),
+                    None => Ok(Err(MappedException::Unmatched(exception))),
+                }
+            }
+        }
+    }
+}
+
+mod private {
+    use crate::error::JuliaResult;
+
+    pub trait MapExceptionPriv {}
+    impl<T> MapExceptionPriv for JuliaResult<'_, '_, T> {}
+}
@@ -2,21 +2,70 @@
 //!
 //! A `JuliaResult` contains an exception in its `Err` variant, if you're only interested in
 //! the error message you can convert it to a `JlrsException` with the [`IntoJlrsResult`] trait
-//! defined in this module.
+//! defined in this module. If you need more information about the exception, like its fields and
+//! backtrace, it can be converted to a [`JuliaException`] instead.
+//!
+//! [`JuliaException`]: crate::error::JuliaException
 
 use crate::{
-    data::managed::Managed,
-    error::{JlrsError, JlrsResult, JuliaResult, CANNOT_DISPLAY_VALUE},
+    call::Call,
+    convert::from_julia::FromJulia,
+    data::managed::{
+        module::{HelperModule, Module},
+        value::Value,
+        Managed,
+    },
+    error::{
+        BacktraceFrame, JlrsError, JlrsResult, JuliaException, JuliaResult, CANNOT_DISPLAY_VALUE,
+    },
+    memory::target::Target,
 };
 
+const EXCEPTIONS_MODULE: &str = "module JlrsExceptions
+    function show_field(x)
+        try
+            sprint(show, x; context=:limit=>true)
+        catch
+            \"<Cannot display value>\"
+        end
+    end
+
+    function describe(exc, bt)
+        T = typeof(exc)
+        fields = Tuple{String,String}[]
+        for name in fieldnames(T)
+            value = isdefined(exc, name) ? show_field(getfield(exc, name)) : \"#undef\"
+            push!(fields, (String(name), value))
+        end
+
+        frames = Tuple{String,String,Int,Bool}[]
+        for frame in stacktrace(bt)
+            push!(frames, (String(frame.func), String(frame.file), frame.line, frame.inlined))
+        end
+
+        (string(T), fields, frames)
+    end
+end";
+
+static EXCEPTIONS: HelperModule = HelperModule::new("JlrsExceptions", EXCEPTIONS_MODULE);
+
 /// Extension trait that lets you convert a `JuliaResult` to a `JlrsResult`.
 ///
-/// If an exception is thrown, this trait's only method converts the exception to an error message
-/// by calling `Base.showerror`.
+/// If an exception is thrown, [`IntoJlrsResult::into_jlrs_result`] converts the exception to an
+/// error message by calling `Base.showerror`.
 pub trait IntoJlrsResult<T>: private::IntoJlrsResultPriv {
     /// Convert `self` to `JlrsResult` by calling `Base.showerror` if an exception has been
     /// thrown.
     fn into_jlrs_result(self) -> JlrsResult<T>;
+
+    /// Convert `self` to `JlrsResult`. If an exception has been thrown it's converted to a
+    /// `JlrsError::JuliaException`, which contains the type, fields and backtrace of the
+    /// exception in addition to its error message.
+    ///
+    /// The backtrace is the backtrace of the exception that was caught most recently. This
+    /// method must be called before any other exception is thrown, otherwise the backtrace of
+    /// that exception is returned.
+    fn into_jlrs_result_with_backtrace(self) -> JlrsResult<T>;
 }
 
 impl<T> IntoJlrsResult<T> for JuliaResult<'_, '_, T> {
@@ -27,6 +76,53 @@ impl<T> IntoJlrsResult<T> for JuliaResult<'_, '_, T> {
             Err(e) => JlrsError::exception_error(e.error_string_or(CANNOT_DISPLAY_VALUE))?,
         }
     }
+
+    fn into_jlrs_result_with_backtrace(self) -> JlrsResult<T> {
+        match self {
+            Ok(v) => Ok(v),
+            Err(e) => Err(julia_exception(e)?)?,
+        }
+    }
+}
+
+// Converts `exception` to a `JuliaException`. The backtrace must be captured before anything
+// else is called, showing the exception or loading the helper module can catch exceptions
+// internally.
+fn julia_exception(exception: Value) -> JlrsResult<JuliaException> {
+    let unrooted = exception.unrooted_target();
+    unrooted.local_scope::<_, _, 3>(|mut frame| unsafe {
+        let exception = exception.root(&mut frame);
+        let backtrace = Module::base(&frame)
+            .function(&frame, "catch_backtrace")?
+            .as_managed()
+            .call0(&mut frame)
+            .into_jlrs_result()?;
+
+        let message = exception.error_string_or(CANNOT_DISPLAY_VALUE);
+
+        let module = EXCEPTIONS.get_or_init(&frame)?;
+
+        let description = module
+            .function(&frame, "describe")?
+            .as_managed()
+            .call2(&mut frame, exception, backtrace)
+            .into_jlrs_result()?;
+
+        let (type_name, fields, frames) = <(
+            String,
+            Vec<(String, String)>,
+            Vec<(String, String, i64, bool)>,
+        )>::from_julia(&frame, description)?;
+
+        let backtrace = frames
+            .into_iter()
+            .map(|(function, file, line, inlined)| {
+                BacktraceFrame::new(function, file, line.max(0) as usize, inlined)
+            })
+            .collect();
+
+        Ok(JuliaException::new(type_name, message, fields, backtrace))
+    })
 }
 
 mod private {
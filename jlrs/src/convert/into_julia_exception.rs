@@ -0,0 +1,51 @@
+//! Convert Rust errors to Julia exceptions.
+//!
+//! An exported function that returns `JlrsResult<T>` throws a `JlrsCore.JlrsError` that only
+//! contains an error message when it returns an error, so Julia code can't handle specific
+//! errors with `catch e::ArgumentError`. An exported function can also return `Result<T, E>`
+//! where `E` implements [`IntoJuliaException`]. If it returns an error, that error is converted
+//! to a Julia exception of the type declared by `E` and this exception is thrown.
+//!
+//! `IntoJuliaException` can be derived for enums. Every variant must be annotated with the Julia
+//! exception type it's converted to with the `julia_type` attribute. This can be a built-in
+//! exception type like `Base.ArgumentError` or `Base.BoundsError`, or a custom exception type
+//! defined in Julia. The fields of a variant are converted to Julia data with [`ToJulia`] and
+//! passed to the constructor of that type in declaration order:
+//!
+//! ```ignore
+//! #[derive(IntoJuliaException, Debug)]
+//! enum LookupError {
+//!     #[jlrs(julia_type = "Base.ArgumentError")]
+//!     InvalidName(String),
+//!     #[jlrs(julia_type = "Base.DomainError")]
+//!     Negative { val: i64, msg: String },
+//!     #[jlrs(julia_type = "Main.MyModule.NotFoundError")]
+//!     NotFound { id: u64 },
+//! }
+//!
+//! fn lookup(id: i64) -> Result<u64, LookupError> {
+//!     // ...
+//! }
+//! ```
+//!
+//! If the error can't be converted to the exception, e.g. because the exception type doesn't
+//! exist, a `JlrsCore.JlrsError` that describes this error is thrown instead.
+//!
+//! [`ToJulia`]: crate::convert::to_julia::ToJulia
+
+use crate::{data::managed::value::ValueData, error::JlrsResult, memory::target::Target};
+
+/// Trait implemented by Rust errors that can be converted to Julia exceptions.
+///
+/// See the [module-level docs] for more information.
+///
+/// [module-level docs]: self
+pub trait IntoJuliaException {
+    /// Convert `self` to a Julia exception.
+    fn into_julia_exception<'target, Tgt>(
+        self,
+        target: Tgt,
+    ) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>;
+}
@@ -2,11 +2,17 @@
 
 pub mod ccall_types;
 pub mod compatible;
+pub mod from_julia;
+pub mod from_julia_exception;
 pub mod into_jlrs_result;
 pub mod into_julia;
+pub mod into_julia_exception;
 #[cfg(feature = "async-rt")]
 pub mod into_result;
 #[cfg(feature = "jlrs-ndarray")]
 pub mod ndarray;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod to_julia;
 pub mod to_symbol;
 pub mod unbox;
@@ -0,0 +1,449 @@
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::{bool::Bool, char::Char, nothing::Nothing, tuple::Tuple},
+        managed::{
+            array::{dimensions::Dims, Array},
+            function::Function,
+            string::JuliaString,
+            symbol::Symbol,
+            value::Value,
+            Managed,
+        },
+    },
+    error::{JlrsError, JlrsResult, CANNOT_DISPLAY_TYPE},
+    inline_static_ref,
+    memory::target::{unrooted::Unrooted, Target},
+};
+
+/// Deserialize `value` as `T`.
+#[inline]
+pub fn from_value<T: DeserializeOwned>(value: Value) -> JlrsResult<T> {
+    T::deserialize(Deserializer::new(value))
+}
+
+/// A `serde::Deserializer` that walks Julia data.
+///
+/// Temporary data that is created while the data is deserialized, e.g. when a field that is
+/// stored inline is accessed, is rooted in local scopes.
+pub struct Deserializer<'scope, 'data> {
+    value: Value<'scope, 'data>,
+}
+
+impl<'scope, 'data> Deserializer<'scope, 'data> {
+    /// Create a new `Deserializer` for `value`.
+    #[inline]
+    pub fn new(value: Value<'scope, 'data>) -> Self {
+        Deserializer { value }
+    }
+
+    #[inline]
+    fn unrooted(&self) -> Unrooted<'scope> {
+        // Safety: the unrooted target is only used to create local scopes.
+        unsafe { Unrooted::new() }
+    }
+
+    fn unsupported(&self) -> Box<JlrsError> {
+        <Box<JlrsError> as de::Error>::custom(format!(
+            "cannot deserialize data of type {}",
+            self.value.datatype().display_string_or(CANNOT_DISPLAY_TYPE)
+        ))
+    }
+
+    fn is_abstract_dict(&self) -> bool {
+        let unrooted = self.unrooted();
+        let abstract_dict = inline_static_ref!(ABSTRACT_DICT, Value, "Base.AbstractDict", unrooted);
+        self.value.isa(abstract_dict)
+    }
+
+    fn is_abstract_set(&self) -> bool {
+        let unrooted = self.unrooted();
+        let abstract_set = inline_static_ref!(ABSTRACT_SET, Value, "Base.AbstractSet", unrooted);
+        self.value.isa(abstract_set)
+    }
+
+    // Collects the contents of `self.value` in a `Vector`, and visits that vector. The vector is
+    // rooted while it's visited.
+    fn visit_collected<'de, V: Visitor<'de>>(self, visitor: V, map: bool) -> JlrsResult<V::Value> {
+        self.unrooted().local_scope::<_, _, 1>(|mut frame| {
+            let collect = inline_static_ref!(COLLECT, Function, "Base.collect", frame);
+            // Safety: collecting a dict or set is safe.
+            let collected = unsafe { collect.call1(&mut frame, self.value).into_jlrs_result()? };
+            // Safety: the result of collect is a Vector.
+            let arr = unsafe { collected.cast_unchecked::<Array>() };
+
+            if map {
+                visitor.visit_map(PairsAccess::new(arr))
+            } else {
+                visitor.visit_seq(ArrayAccess::new(arr))
+            }
+        })
+    }
+}
+
+impl<'de, 'scope, 'data> de::Deserializer<'de> for Deserializer<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> JlrsResult<V::Value> {
+        let value = self.value;
+
+        // Safety: the types are checked before the data is unboxed or cast.
+        unsafe {
+            if value.is::<Bool>() {
+                visitor.visit_bool(value.unbox_unchecked::<Bool>().as_bool())
+            } else if value.is::<i8>() {
+                visitor.visit_i8(value.unbox_unchecked::<i8>())
+            } else if value.is::<i16>() {
+                visitor.visit_i16(value.unbox_unchecked::<i16>())
+            } else if value.is::<i32>() {
+                visitor.visit_i32(value.unbox_unchecked::<i32>())
+            } else if value.is::<i64>() {
+                visitor.visit_i64(value.unbox_unchecked::<i64>())
+            } else if value.is::<u8>() {
+                visitor.visit_u8(value.unbox_unchecked::<u8>())
+            } else if value.is::<u16>() {
+                visitor.visit_u16(value.unbox_unchecked::<u16>())
+            } else if value.is::<u32>() {
+                visitor.visit_u32(value.unbox_unchecked::<u32>())
+            } else if value.is::<u64>() {
+                visitor.visit_u64(value.unbox_unchecked::<u64>())
+            } else if value.is::<f32>() {
+                visitor.visit_f32(value.unbox_unchecked::<f32>())
+            } else if value.is::<f64>() {
+                visitor.visit_f64(value.unbox_unchecked::<f64>())
+            } else if value.is::<Char>() {
+                match value.unbox_unchecked::<Char>().try_as_char() {
+                    Some(c) => visitor.visit_char(c),
+                    None => Err(de::Error::custom("invalid Unicode scalar value")),
+                }
+            } else if value.is::<JuliaString>() {
+                visitor.visit_str(value.cast_unchecked::<JuliaString>().as_str()?)
+            } else if value.is::<Symbol>() {
+                visitor.visit_str(value.cast_unchecked::<Symbol>().as_str()?)
+            } else if value.is::<Nothing>() {
+                visitor.visit_unit()
+            } else if value.is::<Array>() {
+                visitor.visit_seq(ArrayAccess::new(value.cast_unchecked::<Array>()))
+            } else if value.is::<Tuple>() {
+                visitor.visit_seq(FieldAccess::new(value))
+            } else if self.is_abstract_dict() {
+                self.visit_collected(visitor, true)
+            } else if self.is_abstract_set() {
+                self.visit_collected(visitor, false)
+            } else if value.n_fields() > 0 {
+                visitor.visit_map(FieldAccess::new(value))
+            } else {
+                Err(self.unsupported())
+            }
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> JlrsResult<V::Value> {
+        if self.value.is::<Nothing>() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> JlrsResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> JlrsResult<V::Value> {
+        let value = self.value;
+
+        // Safety: the types are checked before the data is cast.
+        unsafe {
+            if value.is::<Symbol>() {
+                let variant = value.cast_unchecked::<Symbol>().as_str()?;
+                visitor.visit_enum(variant.into_deserializer())
+            } else if value.is::<JuliaString>() {
+                let variant = value.cast_unchecked::<JuliaString>().as_str()?;
+                visitor.visit_enum(variant.into_deserializer())
+            } else if value.n_fields() == 1 && !value.is::<Tuple>() {
+                visitor.visit_enum(VariantAccess { value })
+            } else {
+                Err(self.unsupported())
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+// Provides access to the elements of an array.
+struct ArrayAccess<'scope, 'data> {
+    array: Array<'scope, 'data>,
+    len: usize,
+    idx: usize,
+}
+
+impl<'scope, 'data> ArrayAccess<'scope, 'data> {
+    #[inline]
+    fn new(array: Array<'scope, 'data>) -> Self {
+        // Safety: the dimensions aren't mutated while they're in use.
+        let len = unsafe { array.dimensions().size() };
+        ArrayAccess { array, len, idx: 0 }
+    }
+
+    // Calls `func` with the next element, which is rooted while `func` is called.
+    fn next_element<T, F>(&mut self, func: F) -> JlrsResult<Option<T>>
+    where
+        F: FnOnce(Value) -> JlrsResult<T>,
+    {
+        if self.idx == self.len {
+            return Ok(None);
+        }
+
+        let idx = self.idx;
+        self.idx += 1;
+
+        // Safety: the unrooted target is only used to create a local scope, the array isn't
+        // mutated while it's being accessed.
+        let unrooted = unsafe { Unrooted::new() };
+        unrooted.local_scope::<_, _, 1>(|mut frame| {
+            let mut accessor = unsafe { self.array.indeterminate_data() };
+            match accessor.get_value(&mut frame, idx)? {
+                Some(elem) => func(elem.into_jlrs_result()?).map(Some),
+                None => Err(de::Error::custom(format!(
+                    "element {} is undefined",
+                    idx + 1
+                ))),
+            }
+        })
+    }
+}
+
+impl<'de, 'scope, 'data> SeqAccess<'de> for ArrayAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> JlrsResult<Option<T::Value>> {
+        self.next_element(|elem| seed.deserialize(Deserializer::new(elem)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.idx)
+    }
+}
+
+// Provides access to the keys and values of a `Vector{<:Pair}`.
+struct PairsAccess<'scope, 'data> {
+    array: ArrayAccess<'scope, 'data>,
+    idx: usize,
+}
+
+impl<'scope, 'data> PairsAccess<'scope, 'data> {
+    #[inline]
+    fn new(array: Array<'scope, 'data>) -> Self {
+        PairsAccess {
+            array: ArrayAccess::new(array),
+            idx: 0,
+        }
+    }
+
+    // Calls `func` with the `field_idx`th field of the current pair.
+    fn pair_field<'de, T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+        field_idx: usize,
+    ) -> JlrsResult<Option<T::Value>> {
+        // The value is accessed after the key, reset the index to access the same pair.
+        self.array.idx = self.idx;
+        let res = self.array.next_element(|pair| {
+            let unrooted = unsafe { Unrooted::new() };
+            unrooted.local_scope::<_, _, 1>(|mut frame| {
+                let field = pair.get_nth_field(&mut frame, field_idx)?;
+                seed.deserialize(Deserializer::new(field))
+            })
+        });
+
+        if field_idx == 1 {
+            self.idx += 1;
+        }
+
+        res
+    }
+}
+
+impl<'de, 'scope, 'data> MapAccess<'de> for PairsAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> JlrsResult<Option<K::Value>> {
+        self.pair_field(seed, 0)
+    }
+
+    #[inline]
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> JlrsResult<V::Value> {
+        match self.pair_field(seed, 1)? {
+            Some(value) => Ok(value),
+            None => Err(de::Error::custom("no value available")),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.array.len - self.idx)
+    }
+}
+
+// Provides access to the fields of a value, either as a sequence or as a map with the field
+// names as keys.
+struct FieldAccess<'scope, 'data> {
+    value: Value<'scope, 'data>,
+    n_fields: usize,
+    idx: usize,
+}
+
+impl<'scope, 'data> FieldAccess<'scope, 'data> {
+    #[inline]
+    fn new(value: Value<'scope, 'data>) -> Self {
+        FieldAccess {
+            value,
+            n_fields: value.n_fields(),
+            idx: 0,
+        }
+    }
+
+    fn field<'de, T: DeserializeSeed<'de>>(&self, seed: T, idx: usize) -> JlrsResult<T::Value> {
+        // Safety: the unrooted target is only used to create a local scope.
+        let unrooted = unsafe { Unrooted::new() };
+        unrooted.local_scope::<_, _, 1>(|mut frame| {
+            let field = self.value.get_nth_field(&mut frame, idx)?;
+            seed.deserialize(Deserializer::new(field))
+        })
+    }
+}
+
+impl<'de, 'scope, 'data> SeqAccess<'de> for FieldAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> JlrsResult<Option<T::Value>> {
+        if self.idx == self.n_fields {
+            return Ok(None);
+        }
+
+        let idx = self.idx;
+        self.idx += 1;
+        self.field(seed, idx).map(Some)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.n_fields - self.idx)
+    }
+}
+
+impl<'de, 'scope, 'data> MapAccess<'de> for FieldAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> JlrsResult<Option<K::Value>> {
+        if self.idx == self.n_fields {
+            return Ok(None);
+        }
+
+        let name = self.value.field_names()[self.idx];
+        seed.deserialize(Deserializer::new(name.as_value()))
+            .map(Some)
+    }
+
+    #[inline]
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> JlrsResult<V::Value> {
+        let idx = self.idx;
+        self.idx += 1;
+        self.field(seed, idx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.n_fields - self.idx)
+    }
+}
+
+// Provides access to an enum variant that has been serialized as a single-entry struct.
+struct VariantAccess<'scope, 'data> {
+    value: Value<'scope, 'data>,
+}
+
+impl<'de, 'scope, 'data> de::EnumAccess<'de> for VariantAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+    type Variant = Self;
+
+    #[inline]
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> JlrsResult<(V::Value, Self)> {
+        let name = self.value.field_names()[0];
+        let variant = seed.deserialize(Deserializer::new(name.as_value()))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'scope, 'data> de::VariantAccess<'de> for VariantAccess<'scope, 'data> {
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn unit_variant(self) -> JlrsResult<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> JlrsResult<T::Value> {
+        FieldAccess::new(self.value).field(seed, 0)
+    }
+
+    #[inline]
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> JlrsResult<V::Value> {
+        FieldAccess::new(self.value).field(AnySeed(visitor), 0)
+    }
+
+    #[inline]
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> JlrsResult<V::Value> {
+        FieldAccess::new(self.value).field(AnySeed(visitor), 0)
+    }
+}
+
+// Forwards a visitor to `deserialize_any`.
+struct AnySeed<V>(V);
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for AnySeed<V> {
+    type Value = V::Value;
+
+    #[inline]
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        deserializer.deserialize_any(self.0)
+    }
+}
@@ -0,0 +1,78 @@
+//! Serialize Rust data to Julia and deserialize Julia data with serde.
+//!
+//! This module is only available if the `serde` feature is enabled. It provides a
+//! [`Serializer`] that builds Julia data from any type that implements `serde::Serialize`, and a
+//! [`Deserializer`] that walks Julia data to create any type that implements
+//! `serde::Deserialize`. The functions [`to_value`] and [`from_value`] are the easiest way to
+//! use them.
+//!
+//! The serializer maps serde's data model to Julia as follows:
+//!
+//! | serde                                 | Julia                                          |
+//! |---------------------------------------|------------------------------------------------|
+//! | `bool`, integers, floats, `char`      | `Bool`, `Int8`, ..., `Float64`, `Char`         |
+//! | string                                | `String`                                       |
+//! | bytes                                 | `Vector{UInt8}`                                |
+//! | `None`, unit, unit struct             | `nothing`                                      |
+//! | `Some(v)`, newtype struct             | `v`                                            |
+//! | unit variant                          | `Symbol`                                       |
+//! | sequence, tuple struct                | `Vector`                                       |
+//! | tuple                                 | `Tuple`                                        |
+//! | map                                   | `Dict`                                         |
+//! | struct                                | `NamedTuple` or `Dict{Symbol, Any}`            |
+//! | other variants                        | single-entry struct, e.g. `(Variant = v,)`     |
+//!
+//! The element type of a `Vector` and the key and value types of a `Dict` are the narrowest
+//! types that can contain all elements, keys and values, e.g. serializing `vec![1.0, 2.0]`
+//! results in a `Vector{Float64}`. Structs are serialized as `NamedTuple`s by default, this can
+//! be changed with [`Serializer::with_struct_repr`].
+//!
+//! The deserializer accepts everything the serializer produces. Julia structs that aren't
+//! `NamedTuple`s are deserialized as maps with their field names as keys, so a Rust struct can
+//! be deserialized from a Julia struct with the same field names.
+
+mod de;
+mod ser;
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+pub use self::{
+    de::{from_value, Deserializer},
+    ser::{to_value, Serializer, StructRepr},
+};
+use crate::error::JlrsError;
+
+/// Error that is returned by a serde implementation, or when data can't be serialized or
+/// deserialized.
+#[derive(Debug, Error)]
+#[error("{msg}")]
+pub struct SerdeError {
+    msg: String,
+}
+
+impl SerdeError {
+    /// Returns a reference to the error message.
+    pub fn get_message(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl ::serde::ser::Error for Box<JlrsError> {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Box::new(JlrsError::other(SerdeError {
+            msg: msg.to_string(),
+        }))
+    }
+}
+
+impl ::serde::de::Error for Box<JlrsError> {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Box::new(JlrsError::other(SerdeError {
+            msg: msg.to_string(),
+        }))
+    }
+}
@@ -0,0 +1,600 @@
+use serde::ser::{self, Serialize};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::tuple::Tuple,
+        managed::{
+            array::Array,
+            datatype::DataType,
+            function::Function,
+            string::JuliaString,
+            symbol::Symbol,
+            union_all::UnionAll,
+            value::{Value, ValueData},
+            Managed,
+        },
+    },
+    error::{JlrsError, JlrsResult},
+    inline_static_ref,
+    memory::target::{frame::GcFrame, ExtendedTarget, Target},
+};
+
+/// How structs are represented in Julia.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StructRepr {
+    /// Serialize structs as `NamedTuple`s.
+    #[default]
+    NamedTuple,
+    /// Serialize structs as `Dict{Symbol, Any}`s.
+    Dict,
+}
+
+/// Serialize `value` to Julia data with the default options.
+///
+/// Temporary data is rooted in a new scope, the result is rooted in `target`.
+pub fn to_value<'target, T, Tgt>(
+    target: ExtendedTarget<'target, '_, '_, Tgt>,
+    value: &T,
+) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+where
+    T: Serialize + ?Sized,
+    Tgt: Target<'target>,
+{
+    let (target, frame) = target.split();
+    frame.scope(|mut frame| {
+        let value = value.serialize(Serializer::new(&mut frame))?;
+        Ok(value.root(target))
+    })
+}
+
+/// A `serde::Serializer` that creates Julia data.
+///
+/// All data that is created while a value is serialized, including the serialized value itself,
+/// is rooted in the frame. [`to_value`] serializes the value in a new scope so this temporary
+/// data is no longer rooted after the value has been serialized.
+pub struct Serializer<'frame, 'scope> {
+    frame: &'frame mut GcFrame<'scope>,
+    struct_repr: StructRepr,
+}
+
+impl<'frame, 'scope> Serializer<'frame, 'scope> {
+    /// Create a new `Serializer` that roots its data in `frame`.
+    #[inline]
+    pub fn new(frame: &'frame mut GcFrame<'scope>) -> Self {
+        Serializer {
+            frame,
+            struct_repr: StructRepr::default(),
+        }
+    }
+
+    /// Set how structs are represented in Julia.
+    #[inline]
+    pub fn with_struct_repr(mut self, struct_repr: StructRepr) -> Self {
+        self.struct_repr = struct_repr;
+        self
+    }
+
+    #[inline]
+    fn reborrow<'nested>(&'nested mut self) -> Serializer<'nested, 'scope> {
+        Serializer {
+            frame: self.frame,
+            struct_repr: self.struct_repr,
+        }
+    }
+
+    // Create a `Vector` with the narrowest element type that can contain all elements.
+    fn new_vector(
+        &mut self,
+        elements: &[Value<'scope, 'static>],
+    ) -> JlrsResult<Value<'scope, 'static>> {
+        let frame = &mut *self.frame;
+        let map = inline_static_ref!(MAP, Function, "Base.map", frame);
+        let identity = inline_static_ref!(IDENTITY, Function, "Base.identity", frame);
+
+        let any = DataType::any_type(frame).as_value();
+        let mut vector = Array::new_for(&mut *frame, elements.len(), any).into_jlrs_result()?;
+
+        // Safety: the elements are stored in a new Vector{Any}, mapping identity over it narrows
+        // its element type.
+        unsafe {
+            let mut data = vector.value_data_mut()?;
+            for (index, element) in elements.iter().copied().enumerate() {
+                data.set(index, Some(element))?;
+            }
+
+            map.call2(frame, identity.as_value(), vector.as_value())
+                .into_jlrs_result()
+        }
+    }
+
+    // Create a `Dict` with the narrowest key and value types.
+    fn new_dict(
+        &mut self,
+        keys: &[Value<'scope, 'static>],
+        values: &[Value<'scope, 'static>],
+    ) -> JlrsResult<Value<'scope, 'static>> {
+        let keys = self.new_vector(keys)?;
+        let values = self.new_vector(values)?;
+
+        let frame = &mut *self.frame;
+        let dict = inline_static_ref!(DICT, Function, "Base.Dict", frame);
+        let zip = inline_static_ref!(ZIP, Function, "Base.zip", frame);
+
+        // Safety: zipping two vectors of equal length and creating a Dict from the result is
+        // safe.
+        unsafe {
+            let pairs = zip.call2(&mut *frame, keys, values).into_jlrs_result()?;
+            dict.call1(frame, pairs).into_jlrs_result()
+        }
+    }
+
+    // Create a struct-like value according to the struct representation.
+    fn new_struct(
+        &mut self,
+        names: &[Symbol<'scope>],
+        values: &[Value<'scope, 'static>],
+    ) -> JlrsResult<Value<'scope, 'static>> {
+        let frame = &mut *self.frame;
+        let names: Vec<_> = names.iter().map(|name| name.as_value()).collect();
+
+        match self.struct_repr {
+            StructRepr::NamedTuple => {
+                let named_tuple =
+                    inline_static_ref!(NAMED_TUPLE, UnionAll, "Core.NamedTuple", frame);
+
+                // Safety: NamedTuple{names}(values) creates a new NamedTuple, the names are
+                // unique because they're the fields of a Rust struct.
+                unsafe {
+                    let names = Tuple::new(&mut *frame, names).into_jlrs_result()?;
+                    let values = Tuple::new(&mut *frame, values).into_jlrs_result()?;
+                    let ty = named_tuple
+                        .as_value()
+                        .apply_type(&mut *frame, [names])
+                        .into_jlrs_result()?;
+                    ty.call1(frame, values).into_jlrs_result()
+                }
+            }
+            StructRepr::Dict => {
+                let dict = inline_static_ref!(DICT, UnionAll, "Base.Dict", frame);
+                let setindex = inline_static_ref!(SETINDEX, Function, "Base.setindex!", frame);
+
+                // Safety: a Dict{Symbol, Any} is created and populated with symbols and values.
+                unsafe {
+                    let sym = DataType::symbol_type(frame).as_value();
+                    let any = DataType::any_type(frame).as_value();
+                    let ty = dict
+                        .as_value()
+                        .apply_type(&mut *frame, [sym, any])
+                        .into_jlrs_result()?;
+                    let dict = ty.call0(&mut *frame).into_jlrs_result()?;
+
+                    for (name, value) in names.iter().copied().zip(values.iter().copied()) {
+                        setindex
+                            .call3(&mut *frame, dict, value, name)
+                            .into_jlrs_result()?;
+                    }
+
+                    Ok(dict)
+                }
+            }
+        }
+    }
+
+    // Serializes `value` as a single-entry struct with the name `variant`.
+    fn new_variant(
+        &mut self,
+        variant: &'static str,
+        value: Value<'scope, 'static>,
+    ) -> JlrsResult<Value<'scope, 'static>> {
+        let name = Symbol::new(&*self.frame, variant);
+        self.new_struct(&[name], &[value])
+    }
+}
+
+impl<'frame, 'scope> ser::Serializer for Serializer<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+    type SerializeSeq = SerializeVec<'frame, 'scope>;
+    type SerializeTuple = SerializeVec<'frame, 'scope>;
+    type SerializeTupleStruct = SerializeVec<'frame, 'scope>;
+    type SerializeTupleVariant = SerializeVec<'frame, 'scope>;
+    type SerializeMap = SerializeMap<'frame, 'scope>;
+    type SerializeStruct = SerializeStruct<'frame, 'scope>;
+    type SerializeStructVariant = SerializeStruct<'frame, 'scope>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> JlrsResult<Self::Ok> {
+        Ok(Value::new(self.frame, v))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> JlrsResult<Self::Ok> {
+        Ok(JuliaString::new(self.frame, v).as_value())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> JlrsResult<Self::Ok> {
+        let arr = Array::from_vec(self.frame, v.to_vec(), v.len())?.into_jlrs_result()?;
+        Ok(arr.as_value())
+    }
+
+    #[inline]
+    fn serialize_none(self) -> JlrsResult<Self::Ok> {
+        Ok(Value::nothing(self.frame))
+    }
+
+    #[inline]
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> JlrsResult<Self::Ok> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> JlrsResult<Self::Ok> {
+        Ok(Value::nothing(self.frame))
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> JlrsResult<Self::Ok> {
+        Ok(Value::nothing(self.frame))
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> JlrsResult<Self::Ok> {
+        Ok(Symbol::new(self.frame, variant).as_value())
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> JlrsResult<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> JlrsResult<Self::Ok> {
+        let value = value.serialize(self.reborrow())?;
+        self.new_variant(variant, value)
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> JlrsResult<Self::SerializeSeq> {
+        Ok(SerializeVec::new(self, len.unwrap_or(0), VecKind::Vector))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> JlrsResult<Self::SerializeTuple> {
+        Ok(SerializeVec::new(self, len, VecKind::Tuple))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> JlrsResult<Self::SerializeTupleStruct> {
+        Ok(SerializeVec::new(self, len, VecKind::Vector))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> JlrsResult<Self::SerializeTupleVariant> {
+        Ok(SerializeVec::new(self, len, VecKind::Variant(variant)))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> JlrsResult<Self::SerializeMap> {
+        let len = len.unwrap_or(0);
+        Ok(SerializeMap {
+            serializer: self,
+            keys: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> JlrsResult<Self::SerializeStruct> {
+        Ok(SerializeStruct::new(self, len, None))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> JlrsResult<Self::SerializeStructVariant> {
+        Ok(SerializeStruct::new(self, len, Some(variant)))
+    }
+}
+
+enum VecKind {
+    Vector,
+    Tuple,
+    Variant(&'static str),
+}
+
+/// Serializes sequences, tuples, tuple structs and tuple variants.
+pub struct SerializeVec<'frame, 'scope> {
+    serializer: Serializer<'frame, 'scope>,
+    elements: Vec<Value<'scope, 'static>>,
+    kind: VecKind,
+}
+
+impl<'frame, 'scope> SerializeVec<'frame, 'scope> {
+    #[inline]
+    fn new(serializer: Serializer<'frame, 'scope>, len: usize, kind: VecKind) -> Self {
+        SerializeVec {
+            serializer,
+            elements: Vec::with_capacity(len),
+            kind,
+        }
+    }
+
+    #[inline]
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        let value = value.serialize(self.serializer.reborrow())?;
+        self.elements.push(value);
+        Ok(())
+    }
+
+    fn finish(mut self) -> JlrsResult<Value<'scope, 'static>> {
+        match self.kind {
+            VecKind::Vector => self.serializer.new_vector(&self.elements),
+            VecKind::Tuple => {
+                Tuple::new(&mut *self.serializer.frame, self.elements).into_jlrs_result()
+            }
+            VecKind::Variant(variant) => {
+                let value = self.serializer.new_vector(&self.elements)?;
+                self.serializer.new_variant(variant, value)
+            }
+        }
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeSeq for SerializeVec<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeTuple for SerializeVec<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeTupleStruct for SerializeVec<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeTupleVariant for SerializeVec<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+/// Serializes maps.
+pub struct SerializeMap<'frame, 'scope> {
+    serializer: Serializer<'frame, 'scope>,
+    keys: Vec<Value<'scope, 'static>>,
+    values: Vec<Value<'scope, 'static>>,
+}
+
+impl<'frame, 'scope> ser::SerializeMap for SerializeMap<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> JlrsResult<()> {
+        let key = key.serialize(self.serializer.reborrow())?;
+        self.keys.push(key);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> JlrsResult<()> {
+        let value = value.serialize(self.serializer.reborrow())?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(mut self) -> JlrsResult<Self::Ok> {
+        self.serializer.new_dict(&self.keys, &self.values)
+    }
+}
+
+/// Serializes structs and struct variants.
+pub struct SerializeStruct<'frame, 'scope> {
+    serializer: Serializer<'frame, 'scope>,
+    names: Vec<Symbol<'scope>>,
+    values: Vec<Value<'scope, 'static>>,
+    variant: Option<&'static str>,
+}
+
+impl<'frame, 'scope> SerializeStruct<'frame, 'scope> {
+    #[inline]
+    fn new(
+        serializer: Serializer<'frame, 'scope>,
+        len: usize,
+        variant: Option<&'static str>,
+    ) -> Self {
+        SerializeStruct {
+            serializer,
+            names: Vec::with_capacity(len),
+            values: Vec::with_capacity(len),
+            variant,
+        }
+    }
+
+    #[inline]
+    fn push<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> JlrsResult<()> {
+        let value = value.serialize(self.serializer.reborrow())?;
+        self.names.push(Symbol::new(&*self.serializer.frame, key));
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn finish(mut self) -> JlrsResult<Value<'scope, 'static>> {
+        let value = self.serializer.new_struct(&self.names, &self.values)?;
+        match self.variant {
+            Some(variant) => self.serializer.new_variant(variant, value),
+            None => Ok(value),
+        }
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeStruct for SerializeStruct<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> JlrsResult<()> {
+        self.push(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl<'frame, 'scope> ser::SerializeStructVariant for SerializeStruct<'frame, 'scope> {
+    type Ok = Value<'scope, 'static>;
+    type Error = Box<JlrsError>;
+
+    #[inline]
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> JlrsResult<()> {
+        self.push(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> JlrsResult<Self::Ok> {
+        self.finish()
+    }
+}
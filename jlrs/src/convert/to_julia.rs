@@ -0,0 +1,505 @@
+//! Convert owned Rust data, including collections, to Julia.
+//!
+//! [`IntoJulia`] is limited to isbits-types, which makes it impossible to use it to convert
+//! strings and collections to Julia. The [`ToJulia`] trait defined in this module doesn't have
+//! this limitation, the conversion can allocate as much Julia data as necessary. It's implemented
+//! for all types that implement `IntoJulia`, strings, `Option`s, tuples, and most collections
+//! from the standard library:
+//!
+//! | Rust type                             | Julia type             |
+//! |---------------------------------------|------------------------|
+//! | `T: IntoJulia`                        | `IntoJulia::julia_type`|
+//! | `String`, `&str`                      | `String`               |
+//! | `Option<T>`                           | `Union{Nothing, T}`    |
+//! | `Vec<T>`, `VecDeque<T>`, `Box<[T]>`   | `Vector{T}`            |
+//! | `HashMap<K, V>`, `BTreeMap<K, V>`     | `Dict{K, V}`           |
+//! | `HashSet<T>`, `BTreeSet<T>`           | `Set{T}`               |
+//! | `(T1, T2, ...)`                       | `Tuple{T1, T2, ...}`   |
+//! | `Value<'_, 'static>`                  | `Any`                  |
+//!
+//! These implementations can be nested arbitrarily, e.g. `Vec<HashMap<String, Vec<f64>>>` is
+//! converted to a `Vector{Dict{String, Vector{Float64}}}`.
+//!
+//! `ToJulia` can be derived for structs whose fields all implement `ToJulia`. The Julia type must
+//! be provided with the `julia_type` attribute, e.g. `#[jlrs(julia_type = "Main.Foo")]`. The
+//! derived implementation converts the fields in declaration order and calls the constructor of
+//! that type with the converted fields.
+//!
+//! [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    hash::BuildHasher,
+};
+
+use jl_sys::jl_apply_array_type;
+
+use crate::{
+    call::Call,
+    convert::{into_jlrs_result::IntoJlrsResult, into_julia::IntoJulia},
+    data::managed::{
+        array::Array,
+        datatype::DataType,
+        function::Function,
+        private::ManagedPriv,
+        string::JuliaString,
+        union::Union,
+        union_all::UnionAll,
+        value::{Value, ValueData},
+        Managed,
+    },
+    error::JlrsResult,
+    inline_static_ref,
+    memory::target::Target,
+    private::Private,
+};
+
+/// Trait implemented by types that can be converted to Julia data with [`ToJulia::to_julia`].
+///
+/// Unlike [`IntoJulia`], this trait isn't limited to isbits-types. See the [module-level docs]
+/// for an overview of the types that implement this trait.
+///
+/// [`IntoJulia`]: crate::convert::into_julia::IntoJulia
+/// [module-level docs]: self
+pub trait ToJulia {
+    /// Returns the Julia type of the data `self` is converted to.
+    ///
+    /// This type is used as the element type when a collection of `Self` is converted.
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>;
+
+    /// Convert `self` to Julia data.
+    ///
+    /// If an exception is thrown while the data is converted, it's converted to an error message
+    /// and returned.
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>;
+}
+
+impl<T: IntoJulia> ToJulia for T {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        // Safety: the type returned by IntoJulia::julia_type is globally rooted.
+        unsafe {
+            <T as IntoJulia>::julia_type(&target)
+                .as_managed()
+                .as_value()
+                .root(target)
+        }
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        Ok(self.into_julia(target))
+    }
+}
+
+impl ToJulia for String {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::string_type(&target).as_value().root(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        self.as_str().to_julia(target)
+    }
+}
+
+impl ToJulia for &str {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::string_type(&target).as_value().root(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        // Safety: a new string is converted to a Value with the same lifetimes.
+        unsafe {
+            let s = JuliaString::new(&target, self).as_managed();
+            Ok(s.as_value().root(target))
+        }
+    }
+}
+
+impl<'scope> ToJulia for Value<'scope, 'static> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::any_type(&target).as_value().root(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        // Safety: the value is rooted while it's converted, the lifetime is tied to the target.
+        unsafe { Ok(target.data_from_ptr(self.unwrap_non_null(Private), Private)) }
+    }
+}
+
+impl<T: ToJulia> ToJulia for Option<T> {
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        target
+            .with_local_scope::<_, _, 1>(|target, mut frame| {
+                let ty = T::julia_type(&mut frame);
+                let nothing = DataType::nothing_type(&frame).as_value();
+                // Safety: both arguments are types, the union can't throw.
+                unsafe { Ok(Union::new_unchecked(target, [nothing, ty])) }
+            })
+            .unwrap()
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        match self {
+            Some(v) => v.to_julia(target),
+            None => Ok(Value::nothing(&target).root(target)),
+        }
+    }
+}
+
+impl<T: ToJulia> ToJulia for Vec<T> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        vector_type::<T, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_vector(target, self.len(), self)
+    }
+}
+
+impl<T: ToJulia> ToJulia for VecDeque<T> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        vector_type::<T, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_vector(target, self.len(), self)
+    }
+}
+
+impl<T: ToJulia> ToJulia for Box<[T]> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        vector_type::<T, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        let v = self.into_vec();
+        new_vector(target, v.len(), v)
+    }
+}
+
+impl<K: ToJulia, V: ToJulia, S: BuildHasher> ToJulia for HashMap<K, V, S> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        dict_type::<K, V, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_dict::<K, V, _, _>(target, self)
+    }
+}
+
+impl<K: ToJulia, V: ToJulia> ToJulia for BTreeMap<K, V> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        dict_type::<K, V, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_dict::<K, V, _, _>(target, self)
+    }
+}
+
+impl<T: ToJulia, S: BuildHasher> ToJulia for HashSet<T, S> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        set_type::<T, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_set::<T, _, _>(target, self)
+    }
+}
+
+impl<T: ToJulia> ToJulia for BTreeSet<T> {
+    #[inline]
+    fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        set_type::<T, _>(target)
+    }
+
+    #[inline]
+    fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        new_set::<T, _, _>(target, self)
+    }
+}
+
+macro_rules! impl_to_julia_tuple {
+    ($n:expr, $($types:ident => $idx:tt),+) => {
+        impl<$($types: ToJulia),+> ToJulia for ($($types,)+) {
+            fn julia_type<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+            where
+                Tgt: Target<'target>,
+            {
+                target
+                    .with_local_scope::<_, _, $n>(|target, mut frame| {
+                        let types = [$($types::julia_type(&mut frame)),+];
+                        // Safety: all parameters are types, applying them to Tuple can't throw.
+                        unsafe {
+                            Ok(DataType::anytuple_type(&frame)
+                                .as_value()
+                                .apply_type_unchecked(target, types))
+                        }
+                    })
+                    .unwrap()
+            }
+
+            fn to_julia<'target, Tgt>(self, target: Tgt) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+            where
+                Tgt: Target<'target>,
+            {
+                target.with_local_scope::<_, _, { $n + 1 }>(|target, mut frame| {
+                    let values = [$(self.$idx.to_julia(&mut frame)?),+];
+                    // The type returned by `julia_type` can be abstract, the tuple's type is
+                    // determined by the types of the converted values instead.
+                    let tuple = inline_static_ref!(TUPLE, Function, "Core.tuple", frame);
+                    let tuple = unsafe { tuple.call(&mut frame, values) }.into_jlrs_result()?;
+                    Ok(tuple.root(target))
+                })
+            }
+        }
+    };
+}
+
+impl_to_julia_tuple!(1, T1 => 0);
+impl_to_julia_tuple!(2, T1 => 0, T2 => 1);
+impl_to_julia_tuple!(3, T1 => 0, T2 => 1, T3 => 2);
+impl_to_julia_tuple!(4, T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+impl_to_julia_tuple!(5, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4);
+impl_to_julia_tuple!(6, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5);
+impl_to_julia_tuple!(7, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6);
+impl_to_julia_tuple!(8, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6, T8 => 7);
+
+fn vector_type<'target, T, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+where
+    T: ToJulia,
+    Tgt: Target<'target>,
+{
+    target
+        .with_local_scope::<_, _, 1>(|target, mut frame| {
+            let elty = T::julia_type(&mut frame);
+            // Safety: the element type is a valid type, applying it to Array can't throw.
+            unsafe {
+                let ty = jl_apply_array_type(elty.unwrap(Private), 1);
+                Ok(
+                    Value::wrap_non_null(std::ptr::NonNull::new_unchecked(ty), Private)
+                        .root(target),
+                )
+            }
+        })
+        .unwrap()
+}
+
+fn dict_type<'target, K, V, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+where
+    K: ToJulia,
+    V: ToJulia,
+    Tgt: Target<'target>,
+{
+    target
+        .with_local_scope::<_, _, 2>(|target, mut frame| {
+            let kty = K::julia_type(&mut frame);
+            let vty = V::julia_type(&mut frame);
+            let dict = inline_static_ref!(DICT, UnionAll, "Base.Dict", frame);
+            // Safety: both parameters are types, applying them to Dict can't throw.
+            unsafe { Ok(dict.as_value().apply_type_unchecked(target, [kty, vty])) }
+        })
+        .unwrap()
+}
+
+fn set_type<'target, T, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+where
+    T: ToJulia,
+    Tgt: Target<'target>,
+{
+    target
+        .with_local_scope::<_, _, 1>(|target, mut frame| {
+            let elty = T::julia_type(&mut frame);
+            let set = inline_static_ref!(SET, UnionAll, "Base.Set", frame);
+            // Safety: the parameter is a type, applying it to Set can't throw.
+            unsafe { Ok(set.as_value().apply_type_unchecked(target, [elty])) }
+        })
+        .unwrap()
+}
+
+fn new_vector<'target, T, I, Tgt>(
+    target: Tgt,
+    len: usize,
+    iter: I,
+) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+where
+    T: ToJulia,
+    I: IntoIterator<Item = T>,
+    Tgt: Target<'target>,
+{
+    target.with_local_scope::<_, _, 2>(|target, mut frame| {
+        let elty = T::julia_type(&mut frame);
+        let mut arr = Array::new_for(&mut frame, len, elty).into_jlrs_result()?;
+
+        {
+            // Safety: the array has just been allocated, nothing else can access it.
+            let mut accessor = unsafe { arr.indeterminate_data_mut() };
+            for (idx, elem) in iter.into_iter().enumerate() {
+                frame.local_scope::<_, _, 2>(|mut frame| {
+                    let elem = elem.to_julia(&mut frame)?;
+                    accessor
+                        .set_value(&mut frame, idx, Some(elem))?
+                        .into_jlrs_result()
+                })?;
+            }
+        }
+
+        Ok(arr.as_value().root(target))
+    })
+}
+
+fn new_dict<'target, K, V, I, Tgt>(
+    target: Tgt,
+    iter: I,
+) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+where
+    K: ToJulia,
+    V: ToJulia,
+    I: IntoIterator<Item = (K, V)>,
+    Tgt: Target<'target>,
+{
+    target.with_local_scope::<_, _, 2>(|target, mut frame| {
+        let ty = dict_type::<K, V, _>(&mut frame);
+        // Safety: calling the constructor of a concrete Dict type without arguments is safe.
+        let dict = unsafe { ty.call0(&mut frame).into_jlrs_result()? };
+        let setindex = inline_static_ref!(SETINDEX, Function, "Base.setindex!", frame);
+
+        for (k, v) in iter {
+            frame.local_scope::<_, _, 3>(|mut frame| {
+                let k = k.to_julia(&mut frame)?;
+                let v = v.to_julia(&mut frame)?;
+                // Safety: setindex! is called with a dict and a key and value of the correct
+                // type.
+                unsafe {
+                    setindex.call3(&mut frame, dict, v, k).into_jlrs_result()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(dict.root(target))
+    })
+}
+
+fn new_set<'target, T, I, Tgt>(target: Tgt, iter: I) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+where
+    T: ToJulia,
+    I: IntoIterator<Item = T>,
+    Tgt: Target<'target>,
+{
+    target.with_local_scope::<_, _, 2>(|target, mut frame| {
+        let ty = set_type::<T, _>(&mut frame);
+        // Safety: calling the constructor of a concrete Set type without arguments is safe.
+        let set = unsafe { ty.call0(&mut frame).into_jlrs_result()? };
+        let push = inline_static_ref!(PUSH, Function, "Base.push!", frame);
+
+        for elem in iter {
+            frame.local_scope::<_, _, 2>(|mut frame| {
+                let elem = elem.to_julia(&mut frame)?;
+                // Safety: push! is called with a set and an element of the correct type.
+                unsafe {
+                    push.call2(&mut frame, set, elem).into_jlrs_result()?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(set.root(target))
+    })
+}
@@ -4,7 +4,16 @@
 //! of the [`Call`] trait. You don't need to cast a [`Value`] to a [`Function`] in order to call
 //! it because [`Value`] also implements [`Call`].
 //!
+//! If the `internal-types` feature is enabled, the methods of a function can be inspected with
+//! [`Function::methods`], [`Function::applicable`] and [`Function::most_specific_method`].
+//!
+//! A [`TypedFunction`] is annotated with the types of its arguments and its return type, which
+//! are checked when it's created.
+//!
 //! [`Call`]: crate::call::Call
+//! [`TypedFunction`]: crate::data::managed::function::typed::TypedFunction
+
+pub mod typed;
 
 use std::{marker::PhantomData, ptr::NonNull};
 
@@ -25,6 +34,19 @@ use crate::{
     prelude::ValueData,
     private::Private,
 };
+#[cfg(feature = "internal-types")]
+use crate::{
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::{
+        layout::nothing::Nothing,
+        managed::{
+            array::Array,
+            internal::method::Method,
+            module::{HelperModule, Module},
+        },
+    },
+    error::JlrsError,
+};
 
 /// A Julia function.
 #[derive(Clone, Copy)]
@@ -43,6 +65,227 @@ impl<'scope, 'data> Function<'scope, 'data> {
     }
 }
 
+#[cfg(feature = "internal-types")]
+const METHODS_MODULE: &str = "module JlrsMethods
+    methods(f) = Any[m for m in Base.methods(f)]
+
+    argument_names(m::Method) = join(String[String(n) for n in Base.method_argnames(m)[2:end]], '\\0')
+
+    applicable(f, types...) = hasmethod(f, Tuple{types...})
+
+    function most_specific(f, types::Type)
+        hasmethod(f, types) || return nothing
+        try
+            which(f, types)
+        catch
+            nothing
+        end
+    end
+end";
+
+#[cfg(feature = "internal-types")]
+static METHODS: HelperModule = HelperModule::new("JlrsMethods", METHODS_MODULE);
+
+#[cfg(feature = "internal-types")]
+impl<'scope, 'data> Function<'scope, 'data> {
+    /// Returns information about all methods of this function.
+    ///
+    /// The methods are rooted in `target`, which must be a rooting target like a mutable
+    /// reference to a frame. A single slot is used for all methods.
+    pub fn methods<'target, Tgt>(self, target: Tgt) -> JlrsResult<Vec<MethodInfo<'target>>>
+    where
+        Tgt: Target<'target>
+            + TargetType<'target, Data<'static, Value<'target, 'static>> = Value<'target, 'static>>,
+    {
+        target.with_local_scope::<_, _, 1>(|target, mut frame| unsafe {
+            // The methods don't borrow any data from this function.
+            let func = Value::wrap_non_null(self.unwrap_non_null(Private), Private);
+            let module = METHODS.get_or_init(&frame)?;
+            let methods = module
+                .function(&frame, "methods")?
+                .as_managed()
+                .call1(&mut frame, func)
+                .into_jlrs_result()?
+                .root(target)
+                .cast::<Array>()?;
+
+            let methods = methods.value_data()?;
+            let mut infos = Vec::with_capacity(methods.as_slice().len());
+            for method in methods.as_slice().iter().copied().flatten() {
+                let method = method.as_value().cast::<Method>()?;
+                infos.push(MethodInfo::new(&frame, module, method)?);
+            }
+
+            Ok(infos)
+        })
+    }
+
+    /// Returns `true` if this function has a method that can be called with arguments of the
+    /// types in `arg_types`.
+    pub fn applicable<'target, Tgt>(
+        self,
+        target: &Tgt,
+        arg_types: &[Value<'_, 'static>],
+    ) -> JlrsResult<bool>
+    where
+        Tgt: Target<'target>,
+    {
+        target.local_scope::<_, _, 2>(|mut frame| unsafe {
+            let module = METHODS.get_or_init(&frame)?;
+            let mut args = Vec::with_capacity(arg_types.len() + 1);
+            args.push(self.as_value());
+            args.extend_from_slice(arg_types);
+
+            module
+                .function(&frame, "applicable")?
+                .as_managed()
+                .call(&mut frame, args.as_slice())
+                .into_jlrs_result()?
+                .unbox::<bool>()
+                .map(|b| b.as_bool())
+        })
+    }
+
+    /// Returns the most specific method of this function that can be called with arguments of
+    /// the types in the tuple type `T`, e.g. `Tuple2<f64, i64>`. If there is no such method, or
+    /// if the call is ambiguous, `None` is returned.
+    ///
+    /// The method is rooted in `target`, which must be a rooting target like a mutable reference
+    /// to a frame.
+    pub fn most_specific_method<'target, T, Tgt>(
+        self,
+        target: Tgt,
+    ) -> JlrsResult<Option<MethodInfo<'target>>>
+    where
+        T: ConstructType,
+        Tgt: Target<'target>
+            + TargetType<'target, Data<'static, Value<'target, 'static>> = Value<'target, 'static>>,
+    {
+        target.with_local_scope::<_, _, 2>(|target, mut frame| unsafe {
+            // The method doesn't borrow any data from this function.
+            let func = Value::wrap_non_null(self.unwrap_non_null(Private), Private);
+            let module = METHODS.get_or_init(&frame)?;
+            let types = T::construct_type(&mut frame);
+            let method = module
+                .function(&frame, "most_specific")?
+                .as_managed()
+                .call2(&mut frame, func, types)
+                .into_jlrs_result()?;
+
+            if method.is::<Nothing>() {
+                return Ok(None);
+            }
+
+            let method = method.root(target).cast::<Method>()?;
+            Ok(Some(MethodInfo::new(&frame, module, method)?))
+        })
+    }
+}
+
+/// Information about a method of a [`Function`].
+///
+/// The method is rooted in the target that was used to look it up, its signature and module are
+/// reachable from the method so they don't need to be rooted separately.
+#[cfg(feature = "internal-types")]
+#[derive(Clone, Debug)]
+pub struct MethodInfo<'scope> {
+    method: Method<'scope>,
+    signature: Value<'scope, 'static>,
+    module: Module<'scope>,
+    argument_names: Vec<String>,
+    file: String,
+    line: i32,
+}
+
+#[cfg(feature = "internal-types")]
+impl<'scope> MethodInfo<'scope> {
+    unsafe fn new<'target, Tgt>(
+        target: &Tgt,
+        helpers: Module,
+        method: Method<'scope>,
+    ) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        let unrooted = Unrooted::new();
+        let signature = method
+            .signature(unrooted)
+            .ok_or_else(|| JlrsError::exception("method has no signature"))?
+            .as_managed();
+        let module = method
+            .module(unrooted)
+            .ok_or_else(|| JlrsError::exception("method has no module"))?
+            .as_managed();
+        let file = match method.file() {
+            Some(file) => file.as_string()?,
+            None => String::new(),
+        };
+
+        let argument_names = target.local_scope::<_, _, 1>(|mut frame| {
+            let names = helpers
+                .function(&frame, "argument_names")?
+                .as_managed()
+                .call1(&mut frame, method.as_value())
+                .into_jlrs_result()?
+                .unbox::<String>()?
+                .map_err(|_| JlrsError::exception("argument names are not valid UTF-8"))?;
+
+            if names.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Ok(names.split('\0').map(String::from).collect())
+            }
+        })?;
+
+        Ok(MethodInfo {
+            method,
+            signature,
+            module,
+            argument_names,
+            file,
+            line: method.line(),
+        })
+    }
+
+    /// Returns the method.
+    #[inline]
+    pub fn method(&self) -> Method<'scope> {
+        self.method
+    }
+
+    /// Returns the signature of the method, the tuple type of the function and its arguments.
+    /// This is a `DataType`, or a `UnionAll` if the method has type parameters.
+    #[inline]
+    pub fn signature(&self) -> Value<'scope, 'static> {
+        self.signature
+    }
+
+    /// Returns the module the method has been defined in.
+    #[inline]
+    pub fn module(&self) -> Module<'scope> {
+        self.module
+    }
+
+    /// Returns the names of the arguments of the method. Unnamed arguments have a name that
+    /// starts with `#`.
+    #[inline]
+    pub fn argument_names(&self) -> &[String] {
+        &self.argument_names
+    }
+
+    /// Returns the file the method has been defined in.
+    #[inline]
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line the method has been defined on.
+    #[inline]
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+}
+
 // Safety: The trait is implemented correctly by using the implementation
 // of ValidLayout for FunctionRef
 unsafe impl Typecheck for Function<'_, '_> {
@@ -0,0 +1,344 @@
+//! A function with a checked signature.
+//!
+//! Calling a function with the methods of [`Call`] involves converting the arguments to Julia
+//! data, calling the function, checking for exceptions and unboxing the result. If the function
+//! has no method that matches the arguments, this is only detected when the function is called.
+//!
+//! A [`TypedFunction<Args, Ret>`] wraps a function and is annotated with the types of its
+//! arguments and its return type. When it's created, jlrs checks that the function has a method
+//! that can be called with arguments of the types in `Args`, and that the return types inferred
+//! by Julia for that call are compatible with `Ret`. The arguments are a tuple of types that
+//! implement [`ToJulia`], they're converted to Julia data when the function is called and the
+//! result is unboxed as `Ret`:
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::data::managed::function::typed::TypedFunction;
+//! # use jlrs::util::test::JULIA;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! julia.scope(|mut frame| unsafe {
+//!     let add = Module::base(&frame).function(&frame, "+")?.as_managed();
+//!     let add = TypedFunction::<(f64, f64), f64>::new(&frame, add.as_value())?;
+//!     assert_eq!(add.call(&frame, (1.0, 2.0))?, 3.0);
+//!     Ok(())
+//! }).unwrap();
+//! # });
+//! # }
+//! ```
+//!
+//! `Value` arguments are treated as arguments of type `Any`, so the function must have a method
+//! that accepts `Any` for those arguments.
+//!
+//! [`Call`]: crate::call::Call
+//! [`ToJulia`]: crate::convert::to_julia::ToJulia
+
+use std::marker::PhantomData;
+
+use crate::{
+    call::Call,
+    convert::{into_jlrs_result::IntoJlrsResult, to_julia::ToJulia, unbox::Unbox},
+    data::{
+        layout::nothing::Nothing,
+        managed::{
+            datatype::DataType,
+            module::HelperModule,
+            symbol::Symbol,
+            value::{Value, ValueData},
+            Managed,
+        },
+        types::{construct_type::ConstructType, typecheck::Typecheck},
+    },
+    error::{JlrsResult, TypeError, CANNOT_DISPLAY_TYPE, CANNOT_DISPLAY_VALUE},
+    memory::target::Target,
+    private::Private,
+};
+#[cfg(feature = "async")]
+use crate::{call::CallAsync, memory::target::frame::AsyncGcFrame};
+
+const TYPED_FUNCTION_MODULE: &str = "module JlrsTypedFunction
+    function check(f, argtypes::Type, rettype::Type)
+        hasmethod(f, argtypes) || return :nomethod
+        for rt in Base.return_types(f, argtypes)
+            rt === Union{} && continue
+            typeintersect(rt, rettype) === Union{} && return rt
+        end
+        nothing
+    end
+end";
+
+static TYPED_FUNCTION: HelperModule = HelperModule::new("JlrsTypedFunction", TYPED_FUNCTION_MODULE);
+
+/// Trait implemented by tuples of arguments that a [`TypedFunction`] can be called with.
+///
+/// This trait is implemented for `()` and tuples of up to eight elements that implement
+/// [`ToJulia`].
+///
+/// [`ToJulia`]: crate::convert::to_julia::ToJulia
+pub trait TypedArgs: private::TypedArgsPriv {
+    /// Returns the tuple type of the arguments, e.g. `Tuple{Float64, Int64}` for `(f64, i64)`.
+    fn argument_types<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>;
+}
+
+impl TypedArgs for () {
+    #[inline]
+    fn argument_types<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+    where
+        Tgt: Target<'target>,
+    {
+        DataType::emptytuple_type(&target).as_value().root(target)
+    }
+}
+
+macro_rules! impl_typed_args {
+    ($n:expr, $($types:ident => $idx:tt),+) => {
+        impl<$($types: ToJulia),+> TypedArgs for ($($types,)+) {
+            #[inline]
+            fn argument_types<'target, Tgt>(target: Tgt) -> ValueData<'target, 'static, Tgt>
+            where
+                Tgt: Target<'target>,
+            {
+                <Self as ToJulia>::julia_type(target)
+            }
+        }
+
+        impl<$($types: ToJulia),+> private::TypedArgsPriv for ($($types,)+) {
+            unsafe fn call_unbox<'target, Tgt, R>(
+                self,
+                target: &Tgt,
+                func: Value<'_, 'static>,
+                _: Private,
+            ) -> JlrsResult<R::Output>
+            where
+                Tgt: Target<'target>,
+                R: Unbox + Typecheck,
+            {
+                target.local_scope::<_, _, { $n + 1 }>(|mut frame| {
+                    let args = [$(self.$idx.to_julia(&mut frame)?),+];
+                    func.call(&mut frame, args).into_jlrs_result()?.unbox::<R>()
+                })
+            }
+
+            #[cfg(feature = "async")]
+            fn root_arguments<'target>(
+                self,
+                frame: &mut AsyncGcFrame<'target>,
+                _: Private,
+            ) -> JlrsResult<Vec<Value<'target, 'static>>> {
+                Ok(vec![$(self.$idx.to_julia(&mut *frame)?),+])
+            }
+        }
+    };
+}
+
+impl_typed_args!(1, T1 => 0);
+impl_typed_args!(2, T1 => 0, T2 => 1);
+impl_typed_args!(3, T1 => 0, T2 => 1, T3 => 2);
+impl_typed_args!(4, T1 => 0, T2 => 1, T3 => 2, T4 => 3);
+impl_typed_args!(5, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4);
+impl_typed_args!(6, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5);
+impl_typed_args!(7, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6);
+impl_typed_args!(8, T1 => 0, T2 => 1, T3 => 2, T4 => 3, T5 => 4, T6 => 5, T7 => 6, T8 => 7);
+
+/// A function annotated with the types of its arguments and its return type.
+///
+/// See the [module-level docs] for more information.
+///
+/// [module-level docs]: self
+pub struct TypedFunction<'scope, Args, Ret> {
+    func: Value<'scope, 'static>,
+    _types: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<Args, Ret> Clone for TypedFunction<'_, Args, Ret> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Args, Ret> Copy for TypedFunction<'_, Args, Ret> {}
+
+impl<'scope, Args, Ret> TypedFunction<'scope, Args, Ret>
+where
+    Args: TypedArgs,
+    Ret: ConstructType + Unbox + Typecheck,
+{
+    /// Create a new `TypedFunction` that wraps `func`.
+    ///
+    /// Returns `TypeError::NoMatchingMethod` if `func` has no method that can be called with
+    /// arguments of the types in `Args`, and `TypeError::IncompatibleReturnType` if Julia infers
+    /// a return type that is incompatible with `Ret`. If the return type can't be inferred it's
+    /// only checked when the function is called.
+    pub fn new<'target, Tgt>(target: &Tgt, func: Value<'scope, 'static>) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+    {
+        target.local_scope::<_, _, 4>(|mut frame| unsafe {
+            let module = TYPED_FUNCTION.get_or_init(&frame)?;
+
+            let arg_types = Args::argument_types(&mut frame);
+            let ret_type = Ret::construct_type(&mut frame);
+            let res = module
+                .function(&frame, "check")?
+                .as_managed()
+                .call3(&mut frame, func, arg_types, ret_type)
+                .into_jlrs_result()?;
+
+            if res.is::<Nothing>() {
+                Ok(TypedFunction {
+                    func,
+                    _types: PhantomData,
+                })
+            } else if res.is::<Symbol>() {
+                Err(TypeError::NoMatchingMethod {
+                    function: func.display_string_or(CANNOT_DISPLAY_VALUE),
+                    signature: arg_types.display_string_or(CANNOT_DISPLAY_TYPE),
+                })?
+            } else {
+                Err(TypeError::IncompatibleReturnType {
+                    function: func.display_string_or(CANNOT_DISPLAY_VALUE),
+                    inferred: res.display_string_or(CANNOT_DISPLAY_TYPE),
+                    expected: ret_type.display_string_or(CANNOT_DISPLAY_TYPE),
+                })?
+            }
+        })
+    }
+
+    /// Returns the wrapped function.
+    #[inline]
+    pub fn function(self) -> Value<'scope, 'static> {
+        self.func
+    }
+
+    /// Call the function with `args`. The arguments are converted to Julia data, the result is
+    /// unboxed as `Ret`.
+    ///
+    /// If an exception is thrown it's returned as a `JlrsError::Exception`, if the result can't
+    /// be unboxed as `Ret` an `AccessError::InvalidLayout` is returned.
+    ///
+    /// # Safety
+    ///
+    /// This method lets you call arbitrary Julia functions which can't be checked for
+    /// correctness. More information can be found in the [`safety`] module.
+    ///
+    /// [`safety`]: crate::safety
+    #[inline]
+    pub unsafe fn call<'target, Tgt>(self, target: &Tgt, args: Args) -> JlrsResult<Ret::Output>
+    where
+        Tgt: Target<'target>,
+    {
+        args.call_unbox::<_, Ret>(target, self.func, Private)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'scope, Args, Ret> TypedFunction<'scope, Args, Ret>
+where
+    Args: TypedArgs,
+    Ret: ConstructType + Unbox + Typecheck,
+{
+    /// Call the function with `args` in a new task, and wait for it to complete. The arguments
+    /// are converted to Julia data in a new scope, the result is unboxed as `Ret`.
+    ///
+    /// See [`CallAsync::call_async`] for more information.
+    ///
+    /// # Safety
+    ///
+    /// This method lets you call arbitrary Julia functions which can't be checked for
+    /// correctness. More information can be found in the [`safety`] module.
+    ///
+    /// [`safety`]: crate::safety
+    pub async unsafe fn call_async<'target>(
+        self,
+        frame: &mut AsyncGcFrame<'target>,
+        args: Args,
+    ) -> JlrsResult<Ret::Output>
+    where
+        Ret::Output: 'target,
+    {
+        let func = self.func;
+        frame
+            .async_scope(|mut frame| async move {
+                let args = args.root_arguments(&mut frame, Private)?;
+                func.call_async(&mut frame, args.as_slice())
+                    .await
+                    .into_jlrs_result()?
+                    .unbox::<Ret>()
+            })
+            .await
+    }
+}
+
+impl<'scope, Args, Ret> From<TypedFunction<'scope, Args, Ret>> for Value<'scope, 'static> {
+    #[inline]
+    fn from(func: TypedFunction<'scope, Args, Ret>) -> Self {
+        func.func
+    }
+}
+
+mod private {
+    #[cfg(feature = "async")]
+    use crate::memory::target::frame::AsyncGcFrame;
+    use crate::{
+        call::Call,
+        convert::{into_jlrs_result::IntoJlrsResult, unbox::Unbox},
+        data::{managed::value::Value, types::typecheck::Typecheck},
+        error::JlrsResult,
+        memory::target::Target,
+        private::Private,
+    };
+
+    pub trait TypedArgsPriv: Sized {
+        // Safety: calling arbitrary Julia functions is unsafe.
+        unsafe fn call_unbox<'target, Tgt, R>(
+            self,
+            target: &Tgt,
+            func: Value<'_, 'static>,
+            _: Private,
+        ) -> JlrsResult<R::Output>
+        where
+            Tgt: Target<'target>,
+            R: Unbox + Typecheck;
+
+        #[cfg(feature = "async")]
+        fn root_arguments<'target>(
+            self,
+            frame: &mut AsyncGcFrame<'target>,
+            _: Private,
+        ) -> JlrsResult<Vec<Value<'target, 'static>>>;
+    }
+
+    impl TypedArgsPriv for () {
+        #[inline]
+        unsafe fn call_unbox<'target, Tgt, R>(
+            self,
+            target: &Tgt,
+            func: Value<'_, 'static>,
+            _: Private,
+        ) -> JlrsResult<R::Output>
+        where
+            Tgt: Target<'target>,
+            R: Unbox + Typecheck,
+        {
+            target.local_scope::<_, _, 1>(|mut frame| {
+                func.call0(&mut frame).into_jlrs_result()?.unbox::<R>()
+            })
+        }
+
+        #[cfg(feature = "async")]
+        #[inline]
+        fn root_arguments<'target>(
+            self,
+            _: &mut AsyncGcFrame<'target>,
+            _: Private,
+        ) -> JlrsResult<Vec<Value<'target, 'static>>> {
+            Ok(Vec::new())
+        }
+    }
+}
@@ -1,21 +1,70 @@
 //! Managed type for `Expr`.
+//!
+//! Expressions can be created by parsing code with [`Expr::parse`], or constructed node by node
+//! with [`Expr::new`] and its specialized variants. Constructing expressions avoids the pitfalls
+//! of generating code with string formatting, values are inserted as literals rather than as
+//! code. Expressions can be evaluated in any module with [`Expr::eval`].
 
 use std::{marker::PhantomData, ptr::NonNull};
 
 use jl_sys::{jl_expr_t, jl_expr_type};
 
 use crate::{
+    call::Call,
+    convert::{into_jlrs_result::IntoJlrsResult, to_symbol::ToSymbol},
     data::managed::{
         array::{ArrayData, ArrayRef},
+        datatype::DataType,
+        module::{HelperModule, Module},
         private::ManagedPriv,
+        string::JuliaString,
         symbol::Symbol,
-        Ref,
+        value::{Value, ValueData, ValueResult},
+        Managed, Ref,
     },
+    error::{JlrsResult, ParseError},
     impl_julia_typecheck,
     memory::target::{Target, TargetResult},
     private::Private,
 };
 
+const PARSE_MODULE: &str = "module JlrsParse
+    function error_location(err, line)
+        if err isa Meta.ParseError && isdefined(err, :detail)
+            err = err.detail
+        end
+
+        if isdefined(Base, :JuliaSyntax) && err isa Base.JuliaSyntax.ParseError
+            try
+                diagnostic = first(err.diagnostics)
+                byte = Base.JuliaSyntax.first_byte(diagnostic)
+                line, column = Base.JuliaSyntax.source_location(err.source, byte)
+                return (diagnostic.message, line, column)
+            catch
+            end
+        end
+
+        message = err isa AbstractString ? String(err) : sprint(showerror, err)
+        (message, line, 0)
+    end
+
+    function parse(src::String, filename::String)
+        ex = Meta.parseall(src; filename=filename)
+        line = 1
+        for arg in ex.args
+            if arg isa LineNumberNode
+                line = arg.line
+            elseif arg isa Expr && (arg.head === :error || arg.head === :incomplete)
+                return error_location(first(arg.args), line)
+            end
+        end
+
+        ex
+    end
+end";
+
+static PARSE: HelperModule = HelperModule::new("JlrsParse", PARSE_MODULE);
+
 /// A compound expression in Julia ASTs.
 #[derive(Copy, Clone)]
 #[repr(transparent)]
@@ -53,6 +102,215 @@ impl<'scope> Expr<'scope> {
     }
 }
 
+impl Expr<'_> {
+    /// Parse `src` without evaluating it. The code is attributed to `filename`, which is used in
+    /// the line number nodes of the returned expression.
+    ///
+    /// The returned expression has the head `:toplevel`. If the code contains a syntax error a
+    /// [`ParseError`] that contains the location of that error is returned.
+    pub fn parse<'target, S, F, Tgt>(
+        target: Tgt,
+        src: S,
+        filename: F,
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        S: AsRef<str>,
+        F: AsRef<str>,
+        Tgt: Target<'target>,
+    {
+        let filename = filename.as_ref();
+        target.with_local_scope::<_, _, 3>(|target, mut frame| unsafe {
+            let module = PARSE.get_or_init(&frame)?;
+
+            let src = JuliaString::new(&mut frame, src);
+            let filename_jl = JuliaString::new(&mut frame, filename);
+            let res = module
+                .function(&frame, "parse")?
+                .as_managed()
+                .call2(&mut frame, src.as_value(), filename_jl.as_value())
+                .into_jlrs_result()?;
+
+            if let Ok(expr) = res.cast::<Expr>() {
+                return Ok(expr.root(target));
+            }
+
+            let message = match res.get_nth_field(&frame, 0)?.as_value().unbox::<String>()? {
+                Ok(message) => message,
+                Err(bytes) => String::from_utf8_lossy(&bytes).into(),
+            };
+            let line = res.get_nth_field(&frame, 1)?.as_value().unbox::<isize>()?;
+            let column = res.get_nth_field(&frame, 2)?.as_value().unbox::<isize>()?;
+
+            Err(ParseError::new(
+                message,
+                filename.into(),
+                line.max(0) as usize,
+                column.max(0) as usize,
+            ))?
+        })
+    }
+
+    /// Create a new expression with head `head` and arguments `args`.
+    ///
+    /// The arguments can be other expressions, symbols, and literals. Literals can be created
+    /// from Rust data with [`Value::new`], nodes like `QuoteNode` and `GlobalRef` with
+    /// [`Expr::quote_node`] and [`Expr::global_ref`].
+    pub fn new<'target, H, Tgt>(
+        target: Tgt,
+        head: H,
+        args: &[Value<'_, 'static>],
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        H: ToSymbol,
+        Tgt: Target<'target>,
+    {
+        unsafe {
+            let head = head.to_symbol(&target).as_value();
+            let mut values = Vec::with_capacity(args.len() + 1);
+            values.push(head);
+            values.extend_from_slice(args);
+
+            // Safety: the expression is rooted before Julia can allocate new data.
+            let expr = DataType::expr_type(&target)
+                .as_value()
+                .call(&target, values.as_slice())
+                .map_err(|e| e.as_value())
+                .into_jlrs_result()?
+                .as_value()
+                .cast::<Expr>()?;
+
+            Ok(expr.root(target))
+        }
+    }
+
+    /// Create a function call expression, `func(args...)`.
+    #[inline]
+    pub fn new_call<'target, Tgt>(
+        target: Tgt,
+        func: Value<'_, 'static>,
+        args: &[Value<'_, 'static>],
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        let mut values = Vec::with_capacity(args.len() + 1);
+        values.push(func);
+        values.extend_from_slice(args);
+        Self::new(target, "call", &values)
+    }
+
+    /// Create an assignment expression, `lhs = rhs`.
+    #[inline]
+    pub fn new_assignment<'target, Tgt>(
+        target: Tgt,
+        lhs: Value<'_, 'static>,
+        rhs: Value<'_, 'static>,
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        Self::new(target, "=", &[lhs, rhs])
+    }
+
+    /// Create a block expression, `begin exprs... end`.
+    #[inline]
+    pub fn new_block<'target, Tgt>(
+        target: Tgt,
+        exprs: &[Value<'_, 'static>],
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        Self::new(target, "block", exprs)
+    }
+
+    /// Create a function definition, `function name(params...) body end`.
+    ///
+    /// The parameters are usually symbols, `body` is usually a block expression.
+    pub fn new_function<'target, N, Tgt>(
+        target: Tgt,
+        name: N,
+        params: &[Value<'_, 'static>],
+        body: Value<'_, 'static>,
+    ) -> JlrsResult<ExprData<'target, Tgt>>
+    where
+        N: ToSymbol,
+        Tgt: Target<'target>,
+    {
+        target.with_local_scope::<_, _, 1>(|target, mut frame| {
+            let name = name.to_symbol(&frame).as_value();
+            let signature = Self::new_call(&mut frame, name, params)?;
+            Self::new(target, "function", &[signature.as_value(), body])
+        })
+    }
+
+    /// Create a `QuoteNode` that contains `value`. A quoted value is not evaluated when the
+    /// expression that contains it is evaluated, e.g. a quoted symbol isn't treated as a
+    /// variable.
+    pub fn quote_node<'target, Tgt>(
+        target: Tgt,
+        value: Value<'_, 'static>,
+    ) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        unsafe {
+            // Safety: the node is rooted before Julia can allocate new data.
+            let node = DataType::quotenode_type(&target)
+                .as_value()
+                .call1(&target, value)
+                .map_err(|e| e.as_value())
+                .into_jlrs_result()?
+                .as_value();
+
+            Ok(node.root(target))
+        }
+    }
+
+    /// Create a `GlobalRef` to the global `name` in `module`.
+    pub fn global_ref<'target, N, Tgt>(
+        target: Tgt,
+        module: Module,
+        name: N,
+    ) -> JlrsResult<ValueData<'target, 'static, Tgt>>
+    where
+        N: ToSymbol,
+        Tgt: Target<'target>,
+    {
+        unsafe {
+            let name = name.to_symbol(&target).as_value();
+
+            // Safety: the node is rooted before Julia can allocate new data.
+            let node = DataType::globalref_type(&target)
+                .as_value()
+                .call2(&target, module.as_value(), name)
+                .map_err(|e| e.as_value())
+                .into_jlrs_result()?
+                .as_value();
+
+            Ok(node.root(target))
+        }
+    }
+
+    /// Evaluate this expression in `module` with `Core.eval`.
+    ///
+    /// # Safety
+    ///
+    /// The expression can't be checked for correctness, nothing prevents you from causing a
+    /// segmentation fault with an expression like `unsafe_load(Ptr{Float64}(C_NULL))`.
+    pub unsafe fn eval<'target, Tgt>(
+        self,
+        target: Tgt,
+        module: Module,
+    ) -> JlrsResult<ValueResult<'target, 'static, Tgt>>
+    where
+        Tgt: Target<'target>,
+    {
+        let eval = Module::typed_global_cached::<Value, _, _>(&target, "Core.eval")?;
+        Ok(eval.call2(target, module.as_value(), self.as_value()))
+    }
+}
+
 impl_julia_typecheck!(Expr<'scope>, jl_expr_type, 'scope);
 impl_debug!(Expr<'_>);
 
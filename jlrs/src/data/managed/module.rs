@@ -29,10 +29,13 @@ use super::{
 use crate::{
     call::Call,
     catch::catch_exceptions,
-    convert::to_symbol::ToSymbol,
+    convert::{into_jlrs_result::IntoJlrsResult, to_symbol::ToSymbol},
     data::{
         layout::nothing::Nothing,
-        managed::{function::Function, private::ManagedPriv, symbol::Symbol, value::Value},
+        managed::{
+            function::Function, private::ManagedPriv, string::JuliaString, symbol::Symbol,
+            value::Value,
+        },
         types::{construct_type::ConstructType, typecheck::Typecheck},
     },
     error::{AccessError, JlrsResult, TypeError},
@@ -712,3 +715,57 @@ impl JlrsCore {
         )
     }
 }
+
+// A module that is defined in `JlrsCore` by jlrs the first time it's used. The module is defined
+// at most once, other threads that use it while it's being defined wait until it's available.
+pub(crate) struct HelperModule {
+    module: GcSafeOnceLock<HelperModuleInner>,
+    name: &'static str,
+    src: &'static str,
+}
+
+struct HelperModuleInner(NonNull<jl_module_t>);
+unsafe impl Send for HelperModuleInner {}
+unsafe impl Sync for HelperModuleInner {}
+
+impl HelperModule {
+    // `src` must define a module named `name`.
+    pub(crate) const fn new(name: &'static str, src: &'static str) -> Self {
+        HelperModule {
+            module: GcSafeOnceLock::new(),
+            name,
+            src,
+        }
+    }
+
+    // Returns the module, it's defined if it doesn't exist yet. The module is globally rooted by
+    // `JlrsCore`.
+    pub(crate) fn get_or_init<'target, Tgt>(&self, target: &Tgt) -> JlrsResult<Module<'target>>
+    where
+        Tgt: Target<'target>,
+    {
+        let inner = self.module.get_or_try_init(|| unsafe {
+            let jlrs_core = JlrsCore::module(target);
+            if let Ok(module) = jlrs_core.submodule(target, self.name) {
+                return Ok(HelperModuleInner(
+                    module.as_managed().unwrap_non_null(Private),
+                ));
+            }
+
+            target.local_scope::<_, _, 2>(|mut frame| {
+                let src = JuliaString::new(&mut frame, self.src);
+                Module::base(&frame)
+                    .function(&frame, "include_string")?
+                    .as_managed()
+                    .call2(&mut frame, jlrs_core.as_value(), src.as_value())
+                    .into_jlrs_result()?;
+                Ok(())
+            })?;
+
+            let module = jlrs_core.submodule(target, self.name)?.as_managed();
+            JlrsResult::Ok(HelperModuleInner(module.unwrap_non_null(Private)))
+        })?;
+
+        unsafe { Ok(Module::wrap_non_null(inner.0, Private)) }
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Previous versions of jlrs could not throw exceptions from an exported function. This
 //! restriction has been lifted and `RustResult` has been deprecated. A function that may throw
-//! should return `JlrsResult<T>` or `Result<T, ValueRet>`.
+//! should return `JlrsResult<T>`, `Result<T, ValueRet>`, or `Result<T, E>` where `E` implements
+//! [`IntoJuliaException`].
 //!
 //! `RustResult{T}` is a type provided by the JlrsCore package that contains either data or an
 //! exception. It can be converted to `T`, if it contains an exception that exception is thrown.
@@ -22,7 +23,7 @@ use super::{
 #[cfg(feature = "ccall")]
 use crate::{ccall::CCall, memory::stack_frame::StackFrame};
 use crate::{
-    convert::ccall_types::CCallReturn,
+    convert::{ccall_types::CCallReturn, into_julia_exception::IntoJuliaException},
     data::{
         layout::bool::Bool,
         managed::{
@@ -152,6 +153,32 @@ impl<'target, 'data, U: ConstructType> RustResult<'target, 'data, U> {
             .unwrap()
     }
 
+    /// Constructs a `RustResult` that contains `error`, which is converted to the Julia exception
+    /// declared by its [`IntoJuliaException`] implementation. If this conversion fails, the
+    /// conversion error is converted to a `JlrsCore.JlrsError` instead.
+    pub fn exception<Tgt, E>(target: Tgt, error: E) -> RustResultData<'target, 'data, U, Tgt>
+    where
+        Tgt: Target<'target>,
+        E: IntoJuliaException,
+    {
+        target
+            .with_local_scope::<_, _, 2>(|target, mut frame| unsafe {
+                let error = match error.into_julia_exception(&mut frame) {
+                    Ok(error) => error,
+                    Err(e) => return Ok(Self::jlrs_error(target, *e)),
+                };
+
+                let unrooted = target.unrooted();
+                let ty = Self::construct_type(&mut frame).cast_unchecked::<DataType>();
+                Ok(ty
+                    .instantiate_unchecked(&frame, [error, Value::true_v(&unrooted)])
+                    .as_value()
+                    .cast_unchecked::<RustResult<U>>()
+                    .root(target))
+            })
+            .unwrap()
+    }
+
     #[doc(hidden)]
     #[cfg(feature = "ccall")]
     pub unsafe fn borrow_error_internal() -> RustResultRef<'static, 'static, U> {
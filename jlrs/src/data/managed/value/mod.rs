@@ -1291,6 +1291,40 @@ impl Value<'_, '_> {
             path: path.as_ref().to_string_lossy().into(),
         })?
     }
+
+    /// Evaluate `code` in `module` with `Base.include_string`. The code is attributed to
+    /// `filename`, so errors and stack traces refer to the lines of `code` in that file. The
+    /// result of the last expression is returned.
+    ///
+    /// # Safety
+    ///
+    /// The code can't be checked for correctness, nothing prevents you from causing a
+    /// segmentation fault with code like `unsafe_load(Ptr{Float64}(C_NULL))`.
+    pub unsafe fn eval_with_location<'target, C, F, Tgt>(
+        target: Tgt,
+        module: Module,
+        code: C,
+        filename: F,
+    ) -> JlrsResult<ValueResult<'target, 'static, Tgt>>
+    where
+        C: AsRef<str>,
+        F: AsRef<str>,
+        Tgt: Target<'target>,
+    {
+        target.with_local_scope::<_, _, 2>(|target, mut frame| {
+            let code = JuliaString::new(&mut frame, code);
+            let filename = JuliaString::new(&mut frame, filename);
+            let include_string =
+                Module::typed_global_cached::<Value, _, _>(&frame, "Base.include_string")?;
+
+            Ok(include_string.call3(
+                target,
+                module.as_value(),
+                code.as_value(),
+                filename.as_value(),
+            ))
+        })
+    }
 }
 
 /// # Equality
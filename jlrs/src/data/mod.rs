@@ -38,5 +38,6 @@
 
 pub mod layout;
 pub mod managed;
+pub mod rooted;
 pub mod static_data;
 pub mod types;
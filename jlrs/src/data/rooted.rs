@@ -0,0 +1,275 @@
+//! Long-lived roots for Julia data.
+//!
+//! Managed data can normally only be used in the scope it has been rooted in. It can outlive
+//! that scope by rooting it in an `Output` of a parent scope, by storing it in a global with
+//! `Module::set_global`, or by leaking it if it's globally rooted. None of these options are
+//! convenient if you want to hold on to some Julia data in a Rust struct and use it in many
+//! different scopes, for example a compiled model object that is created once per session.
+//!
+//! A [`Rooted`] handle roots its data in a root set that is managed by jlrs. The data remains
+//! rooted until all clones of the handle have been dropped. A `Rooted` handle is `Send` and
+//! `Sync`, the data can only be accessed by providing a target so it can only be used from a
+//! thread that can call into Julia, but the handle can be dropped from any thread.
+//!
+//! The root set is a `Vector` of fixed-size chunks defined in the `JlrsRoots` module, which is
+//! created in `JlrsCore` when the first handle is created. Dropping a handle clears its slot in
+//! the chunk it was stored in, which is reused by the next handle that is created.
+//!
+//! When a runtime shuts down, the root set is freed together with the rest of Julia's data.
+//! Handles that are dropped afterwards don't touch the root set. When jlrs is used from Julia via
+//! `ccall`, jlrs can't detect that Julia has exited, so handles must not be dropped after that
+//! point, e.g. by storing them in a thread-local or by dropping them in an `atexit` hook.
+//!
+//! ```
+//! # use jlrs::prelude::*;
+//! # use jlrs::data::rooted::Rooted;
+//! # use jlrs::util::test::JULIA;
+//! # fn main() {
+//! # JULIA.with(|j| {
+//! # let mut julia = j.borrow_mut();
+//! # let mut frame = StackFrame::new();
+//! # let mut julia = julia.instance(&mut frame);
+//! let rooted = julia
+//!     .scope(|mut frame| {
+//!         let value = Value::new(&mut frame, 1usize);
+//!         Rooted::new(&frame, value)
+//!     })
+//!     .unwrap();
+//!
+//! julia
+//!     .scope(|frame| {
+//!         let value = rooted.as_managed(&frame);
+//!         assert_eq!(value.unbox::<usize>()?, 1);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! # });
+//! # }
+//! ```
+
+use std::{
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+    ptr::{null_mut, NonNull},
+    sync::{
+        atomic::{AtomicBool, AtomicPtr, Ordering},
+        Arc,
+    },
+};
+
+use jl_sys::jl_value_t;
+use parking_lot::{const_mutex, Mutex};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::managed::{
+        array::Array, module::HelperModule, private::ManagedPriv, value::Value, Managed,
+    },
+    error::JlrsResult,
+    gc_safe::{mutex::const_gc_safe_mutex, GcSafeMutex},
+    memory::target::Target,
+    private::Private,
+};
+
+const CHUNK_SIZE: usize = 256;
+
+const ROOTS_MODULE: &str = "module JlrsRoots
+    const chunks = Vector{Any}[]
+
+    function new_chunk(n)
+        chunk = Vector{Any}(undef, n)
+        push!(chunks, chunk)
+        chunk
+    end
+
+    function set_root!(chunk, i, value)
+        @inbounds chunk[i + 1] = value
+        nothing
+    end
+end";
+
+static ROOTS: HelperModule = HelperModule::new("JlrsRoots", ROOTS_MODULE);
+
+// Creating a new root can allocate a new chunk, this lock ensures the chunks are only updated by
+// one thread at a time. The free slots are protected by a separate lock that is never held while
+// calling into Julia, handles can be dropped from threads that can't call into Julia.
+static ROOT_SET: GcSafeMutex<()> = const_gc_safe_mutex(());
+static FREE_SLOTS: Mutex<Vec<Slot>> = const_mutex(Vec::new());
+
+// Set when Julia exits, the chunks have been freed and must no longer be accessed. Only updated
+// and checked while `FREE_SLOTS` is locked.
+static EXITED: AtomicBool = AtomicBool::new(false);
+
+// Called by the runtimes before Julia exits.
+#[cfg_attr(not(any(feature = "sync-rt", feature = "async-rt")), allow(dead_code))]
+pub(crate) fn julia_exited() {
+    let mut free_slots = FREE_SLOTS.lock();
+    EXITED.store(true, Ordering::Relaxed);
+    free_slots.clear();
+}
+
+// A slot in the root set, `chunk` is rooted by `JlrsRoots.chunks` and never resized.
+#[derive(Clone, Copy)]
+struct Slot {
+    chunk: NonNull<jl_value_t>,
+    index: usize,
+}
+
+unsafe impl Send for Slot {}
+
+struct RootedSlot {
+    slot: Slot,
+    value: NonNull<jl_value_t>,
+}
+
+unsafe impl Send for RootedSlot {}
+unsafe impl Sync for RootedSlot {}
+
+impl Drop for RootedSlot {
+    fn drop(&mut self) {
+        let mut free_slots = FREE_SLOTS.lock();
+        if EXITED.load(Ordering::Relaxed) {
+            return;
+        }
+
+        // Safety: Julia hasn't exited, and the chunk is never resized so its data pointer remains
+        // valid. Clearing a slot doesn't require a write barrier.
+        unsafe {
+            let chunk = Array::wrap_non_null(self.slot.chunk.cast(), Private);
+            let slots = chunk.data_ptr() as *const AtomicPtr<jl_value_t>;
+            (*slots.add(self.slot.index)).store(null_mut(), Ordering::Release);
+        }
+
+        free_slots.push(self.slot);
+    }
+}
+
+/// A handle to managed data that remains rooted until all clones of this handle have been
+/// dropped.
+///
+/// `T` is the managed type with static lifetimes, e.g. `Value<'static, 'static>`. See the
+/// [module-level docs] for more information.
+///
+/// [module-level docs]: self
+pub struct Rooted<T> {
+    slot: Arc<RootedSlot>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Rooted<T>
+where
+    T: Managed<'static, 'static>,
+{
+    /// Root `data` in the root set managed by jlrs.
+    ///
+    /// An error is returned if the root set can't be created or extended.
+    pub fn new<'target, 'scope, Tgt, M>(target: &Tgt, data: M) -> JlrsResult<Self>
+    where
+        Tgt: Target<'target>,
+        M: Managed<'scope, 'static, TypeConstructor<'static, 'static> = T>,
+    {
+        let value = data.as_value();
+        let _guard = ROOT_SET.lock();
+
+        target.local_scope::<_, _, 4>(|mut frame| unsafe {
+            let module = ROOTS.get_or_init(&frame)?;
+            let free_slot = FREE_SLOTS.lock().pop();
+            let slot = match free_slot {
+                Some(slot) => slot,
+                None => {
+                    let n = Value::new(&mut frame, CHUNK_SIZE);
+                    let chunk = module
+                        .function(&frame, "new_chunk")?
+                        .as_managed()
+                        .call1(&mut frame, n)
+                        .into_jlrs_result()?
+                        .unwrap_non_null(Private);
+
+                    FREE_SLOTS
+                        .lock()
+                        .extend((1..CHUNK_SIZE).rev().map(|index| Slot { chunk, index }));
+
+                    Slot { chunk, index: 0 }
+                }
+            };
+
+            let chunk = Value::wrap_non_null(slot.chunk, Private);
+            let index = Value::new(&mut frame, slot.index);
+            let res = module
+                .function(&frame, "set_root!")?
+                .as_managed()
+                .call3(&mut frame, chunk, index, value);
+
+            if let Err(e) = res.into_jlrs_result() {
+                FREE_SLOTS.lock().push(slot);
+                return Err(e);
+            }
+
+            Ok(Rooted {
+                slot: Arc::new(RootedSlot {
+                    slot,
+                    value: value.unwrap_non_null(Private),
+                }),
+                _marker: PhantomData,
+            })
+        })
+    }
+
+    /// Access the rooted data.
+    ///
+    /// The target is only used to ensure the data is accessed from a thread that can call into
+    /// Julia. The data can be used as long as both this handle and the target are live.
+    #[inline]
+    pub fn as_managed<'borrow, 'target, Tgt>(
+        &'borrow self,
+        _: &Tgt,
+    ) -> T::TypeConstructor<'borrow, 'static>
+    where
+        'target: 'borrow,
+        Tgt: Target<'target>,
+    {
+        // Safety: the data is rooted as long as this handle exists.
+        unsafe {
+            let value = Value::wrap_non_null(self.slot.value, Private);
+            <T::TypeConstructor<'borrow, 'static>>::from_value_unchecked(value, Private)
+        }
+    }
+
+    /// Root the data in `target`.
+    #[inline]
+    pub fn root<'target, Tgt>(
+        &self,
+        target: Tgt,
+    ) -> Tgt::Data<'static, T::TypeConstructor<'target, 'static>>
+    where
+        Tgt: Target<'target>,
+    {
+        // Safety: the data is rooted as long as this handle exists.
+        unsafe { target.data_from_ptr(self.slot.value.cast(), Private) }
+    }
+
+    /// Returns `true` if both handles are clones of the same handle.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.slot, &other.slot)
+    }
+}
+
+impl<T> Clone for Rooted<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Rooted {
+            slot: self.slot.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for Rooted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rooted")
+            .field("value", &self.slot.value)
+            .finish()
+    }
+}
@@ -1,6 +1,6 @@
 //! Everything related to errors.
 
-use std::error::Error as StdErr;
+use std::{error::Error as StdErr, time::Duration};
 
 use thiserror::Error;
 
@@ -36,6 +36,14 @@ pub enum RuntimeError {
     ChannelClosed,
     #[error("channel full")]
     ChannelFull,
+    #[error("runtime is shutting down")]
+    ShuttingDown,
+    #[error("task panicked")]
+    TaskPanicked,
+    #[error("invalid value for option {option}: {reason}")]
+    InvalidOption { option: String, reason: String },
+    #[error("cannot preload {package}: {reason}")]
+    PreloadFailed { package: String, reason: String },
 }
 
 /// IO errors.
@@ -78,6 +86,24 @@ pub enum TypeError {
     LayoutNone { ty: String },
     #[error("The layout of this type is incompatible with {base_type}")]
     IncompatibleBaseType { base_type: String },
+    #[error("{path}: expected {expected}, found {found}")]
+    UnexpectedTypeAt {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    #[error("{path}: field does not exist in {type_name}")]
+    MissingFieldAt { path: String, type_name: String },
+    #[error("{path}: {reason}")]
+    InvalidValueAt { path: String, reason: String },
+    #[error("no method of {function} matches {signature}")]
+    NoMatchingMethod { function: String, signature: String },
+    #[error("inferred return type {inferred} of {function} is incompatible with {expected}")]
+    IncompatibleReturnType {
+        function: String,
+        inferred: String,
+        expected: String,
+    },
 }
 
 /// Array layout errors.
@@ -151,6 +177,15 @@ pub enum InstantiationError {
     ArraySizeMismatch { dim_size: usize, vec_size: usize },
 }
 
+/// Task cancellation errors.
+#[derive(Debug, Error)]
+pub enum CancellationError {
+    #[error("task was cancelled")]
+    Cancelled,
+    #[error("task timed out after {timeout:?}")]
+    TimedOut { timeout: Duration },
+}
+
 /// Julia exception converted to a string.
 #[derive(Debug, Error)]
 #[error("{msg}")]
@@ -165,6 +200,149 @@ impl Exception {
     }
 }
 
+/// Julia exception converted to its type, fields and backtrace.
+///
+/// Unlike [`Exception`], which only contains the error message, this error keeps enough
+/// information about the exception to handle it without parsing that message. It's created by
+/// [`IntoJlrsResult::into_jlrs_result_with_backtrace`].
+///
+/// [`IntoJlrsResult::into_jlrs_result_with_backtrace`]: crate::convert::into_jlrs_result::IntoJlrsResult::into_jlrs_result_with_backtrace
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct JuliaException {
+    type_name: String,
+    message: String,
+    fields: Vec<(String, String)>,
+    backtrace: Vec<BacktraceFrame>,
+}
+
+impl JuliaException {
+    pub(crate) fn new(
+        type_name: String,
+        message: String,
+        fields: Vec<(String, String)>,
+        backtrace: Vec<BacktraceFrame>,
+    ) -> Self {
+        JuliaException {
+            type_name,
+            message,
+            fields,
+            backtrace,
+        }
+    }
+
+    /// Returns the name of the type of the exception, e.g. `BoundsError`.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the error message, i.e. the string that is shown when calling `Base.showerror`.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the names and values of the fields of the exception. The values are converted to
+    /// strings by calling `Base.show`, undefined fields have the value `#undef`.
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// Returns the value of the field named `name`, if the exception has such a field.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the frames of the backtrace, the innermost frame comes first.
+    pub fn backtrace(&self) -> &[BacktraceFrame] {
+        &self.backtrace
+    }
+}
+
+/// A single frame in the backtrace of a [`JuliaException`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    function: String,
+    file: String,
+    line: usize,
+    inlined: bool,
+}
+
+impl BacktraceFrame {
+    pub(crate) fn new(function: String, file: String, line: usize, inlined: bool) -> Self {
+        BacktraceFrame {
+            function,
+            file,
+            line,
+            inlined,
+        }
+    }
+
+    /// Returns the name of the function.
+    pub fn function(&self) -> &str {
+        &self.function
+    }
+
+    /// Returns the file the function has been defined in.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the line in the file, or 0 if it's unknown.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns `true` if the code of this frame has been inlined into the next frame.
+    pub fn is_inlined(&self) -> bool {
+        self.inlined
+    }
+}
+
+/// Syntax error found while parsing Julia code.
+#[derive(Debug, Error)]
+#[error("{filename}:{line}:{column}: {message}")]
+pub struct ParseError {
+    message: String,
+    filename: String,
+    line: usize,
+    column: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(message: String, filename: String, line: usize, column: usize) -> Self {
+        ParseError {
+            message,
+            filename,
+            line,
+            column,
+        }
+    }
+
+    /// Returns a reference to the error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns a reference to the name of the file that was parsed.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns the line the error was found on, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column the error was found at, starting at 1. The column is 0 if the parser
+    /// doesn't report it, which is the case before Julia 1.10.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
 /// All different errors.
 #[derive(Debug, Error)]
 pub enum JlrsError {
@@ -184,6 +362,12 @@ pub enum JlrsError {
     InstantiationError(InstantiationError),
     #[error("Array layout error: {0}")]
     ArrayLayoutError(ArrayLayoutError),
+    #[error("Cancellation error: {0}")]
+    CancellationError(CancellationError),
+    #[error("Parse error: {0}")]
+    ParseError(ParseError),
+    #[error("Exception: {0}")]
+    JuliaException(JuliaException),
 }
 
 impl JlrsError {
@@ -236,3 +420,6 @@ impl_from!(IOError);
 impl_from!(AccessError);
 impl_from!(InstantiationError);
 impl_from!(ArrayLayoutError);
+impl_from!(CancellationError);
+impl_from!(ParseError);
+impl_from!(JuliaException);
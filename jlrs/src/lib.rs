@@ -194,6 +194,11 @@
 //!
 //!   Provide extra field accessor methods for managed types.
 //!
+//! - `log` and `tracing`
+//!
+//!   Forward log messages emitted in Julia with macros like `@info` to the log crate or to
+//!   tracing. See the [`logging`] module for more information.
+//!
 //! - `i686`
 //!
 //!   Link with a 32-bit build of Julia on Linux, only used for cross-compilation.
@@ -776,7 +781,7 @@
 
 #![forbid(rustdoc::broken_intra_doc_links)]
 
-use std::sync::atomic::AtomicBool;
+use std::{path::PathBuf, sync::atomic::AtomicBool};
 
 use atomic::Ordering;
 #[cfg(feature = "sync-rt")]
@@ -836,15 +841,19 @@ pub mod data;
 pub mod error;
 pub mod gc_safe;
 pub mod info;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub mod logging;
 pub mod memory;
 #[cfg(feature = "prelude")]
 pub mod prelude;
 pub(crate) mod private;
 #[cfg(feature = "pyplot")]
 pub mod pyplot;
+pub mod redirect;
 #[cfg(any(feature = "sync-rt", feature = "async-rt"))]
 pub mod runtime;
 pub mod safety;
+pub mod sandbox;
 #[doc(hidden)]
 #[cfg(feature = "sync-rt")]
 pub mod util;
@@ -873,6 +882,15 @@ pub enum InstallJlrsCore {
         /// Revision to be installed
         revision: String,
     },
+    /// Use the package at the given local path, this doesn't require network access.
+    ///
+    /// The package is loaded from this path even if another version of JlrsCore is installed.
+    /// The path is only added to `LOAD_PATH` while the package is loaded, the active environment
+    /// is not modified.
+    Path {
+        /// Path to the package
+        path: PathBuf,
+    },
 }
 
 impl InstallJlrsCore {
@@ -927,6 +945,24 @@ impl InstallJlrsCore {
                     ),
                 )
             },
+            InstallJlrsCore::Path { path } => {
+                let path = julia_string_literal(&path.to_string_lossy());
+                Value::eval_string(
+                    unrooted,
+                    format!(
+                        "if !haskey(Base.loaded_modules, Base.PkgId(Base.UUID(\"29be08bc-e5fd-4da2-bbc1-72011c6ea2c9\"), \"JlrsCore\"))
+                             pushfirst!(LOAD_PATH, {path})
+                             try
+                                 using JlrsCore
+                             finally
+                                 deleteat!(LOAD_PATH, findfirst(==({path}), LOAD_PATH))
+                             end
+                         else
+                             const JlrsCore = Base.loaded_modules[Base.PkgId(Base.UUID(\"29be08bc-e5fd-4da2-bbc1-72011c6ea2c9\"), \"JlrsCore\")]
+                         end"
+                    ),
+                )
+            },
             InstallJlrsCore::No => {
                 Value::eval_string(
                     unrooted,
@@ -949,6 +985,20 @@ impl InstallJlrsCore {
     }
 }
 
+// Converts `s` to a Julia string literal.
+fn julia_string_literal(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('"');
+    for c in s.chars() {
+        if matches!(c, '"' | '\\' | '$') {
+            literal.push('\\');
+        }
+        literal.push(c);
+    }
+    literal.push('"');
+    literal
+}
+
 // The chosen install method is stored in a OnceCell when the sync runtime is used to
 // avoid having to store it in `PendingJulia`.
 #[cfg(feature = "sync-rt")]
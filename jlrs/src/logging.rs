@@ -0,0 +1,418 @@
+//! Forward Julia's log messages to Rust.
+//!
+//! Log messages emitted in Julia with macros like `@info` and `@warn` are handled by Julia's
+//! global logger, which writes them to `stderr` by default. [`install_logger`] replaces the
+//! global logger with a logger that forwards all messages to the log crate or to tracing,
+//! depending on the [`LogBackend`]. [`uninstall_logger`] restores the previous global logger.
+//!
+//! When a message is forwarded to the log crate, the target of the record is the name of the
+//! Julia module that emitted the message. The key-value pairs of the message are converted to
+//! strings and added to the record. Events forwarded to tracing have the target `julia`, the
+//! module, file, line and key-value pairs are recorded as fields.
+//!
+//! Julia skips messages below the minimum enabled level of the global logger before they're
+//! formatted, so it's strongly recommended to keep this level in sync with the level filter
+//! used in Rust. The initial level is provided when the logger is installed and can be updated
+//! with [`set_min_level`]. [`LogLevel`] can be converted from the level filters of both crates,
+//! e.g. `log::max_level().into()`.
+//!
+//! Only the global logger is replaced, loggers that have been set for a specific task with
+//! `with_logger` are not affected.
+
+use std::{
+    borrow::Cow,
+    ffi::c_void,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::managed::{module::HelperModule, value::Value},
+    error::JlrsResult,
+    memory::target::Target,
+};
+
+const LOGGING_MODULE: &str = "module JlrsLogging
+    import Base.CoreLogging: AbstractLogger, LogLevel, handle_message, shouldlog,
+        min_enabled_level, catch_exceptions, global_logger
+
+    mutable struct RustLogger <: AbstractLogger
+        fptr::Ptr{Cvoid}
+        backend::Int32
+        min_level::LogLevel
+        previous::AbstractLogger
+    end
+
+    min_enabled_level(logger::RustLogger) = logger.min_level
+    shouldlog(::RustLogger, args...) = true
+    catch_exceptions(::RustLogger) = true
+
+    function handle_message(logger::RustLogger, level, message, _module, group, id, file, line;
+        kwargs...)
+        strings = String[
+            string(message),
+            _module === nothing ? \"\" : string(_module),
+            file === nothing ? \"\" : string(file),
+        ]
+
+        for (key, value) in kwargs
+            push!(strings, string(key), string(value))
+        end
+
+        ptrs = map(pointer, strings)
+        lens = Csize_t[sizeof(s) for s in strings]
+        GC.@preserve strings ccall(logger.fptr, Cvoid,
+            (Int32, Int32, Int, Ptr{Ptr{UInt8}}, Ptr{Csize_t}, Csize_t),
+            logger.backend, convert(LogLevel, level).level, something(line, 0), ptrs, lens,
+            length(strings))
+        nothing
+    end
+
+    function install(fptr::Ptr{Cvoid}, backend::Int32, level::Int32)
+        current = global_logger()
+        previous = current isa RustLogger ? current.previous : current
+        global_logger(RustLogger(fptr, backend, LogLevel(level), previous))
+        nothing
+    end
+
+    function set_min_level(level::Int32)
+        logger = global_logger()
+        logger isa RustLogger || error(\"the Rust logger has not been installed\")
+        logger.min_level = LogLevel(level)
+        # The minimum enabled level is cached when the global logger is set.
+        global_logger(logger)
+        nothing
+    end
+
+    function uninstall()
+        logger = global_logger()
+        logger isa RustLogger && global_logger(logger.previous)
+        nothing
+    end
+end";
+
+static LOGGING: HelperModule = HelperModule::new("JlrsLogging", LOGGING_MODULE);
+
+// The values of Julia's standard log levels.
+const JL_BELOW_MIN_LEVEL: i32 = -1000001;
+const JL_DEBUG: i32 = -1000;
+const JL_INFO: i32 = 0;
+const JL_WARN: i32 = 1000;
+const JL_ERROR: i32 = 2000;
+const JL_ABOVE_MAX_LEVEL: i32 = 1000001;
+
+/// The crate log messages from Julia are forwarded to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogBackend {
+    /// Forward messages to the logger set with `log::set_logger`.
+    #[cfg(feature = "log")]
+    Log,
+    /// Forward messages as events to the current tracing subscriber.
+    #[cfg(feature = "tracing")]
+    Tracing,
+}
+
+impl LogBackend {
+    #[inline]
+    fn as_i32(self) -> i32 {
+        match self {
+            #[cfg(feature = "log")]
+            LogBackend::Log => 0,
+            #[cfg(feature = "tracing")]
+            LogBackend::Tracing => 1,
+        }
+    }
+}
+
+/// The minimum enabled level of Julia's global logger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// All messages are enabled, including messages below `Debug` which are mapped to the trace
+    /// level in Rust.
+    All,
+    /// `Logging.Debug`
+    Debug,
+    /// `Logging.Info`
+    Info,
+    /// `Logging.Warn`
+    Warn,
+    /// `Logging.Error`
+    Error,
+    /// All messages are disabled.
+    Off,
+}
+
+impl LogLevel {
+    #[inline]
+    fn as_i32(self) -> i32 {
+        match self {
+            LogLevel::All => JL_BELOW_MIN_LEVEL,
+            LogLevel::Debug => JL_DEBUG,
+            LogLevel::Info => JL_INFO,
+            LogLevel::Warn => JL_WARN,
+            LogLevel::Error => JL_ERROR,
+            LogLevel::Off => JL_ABOVE_MAX_LEVEL,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::LevelFilter> for LogLevel {
+    fn from(filter: log::LevelFilter) -> Self {
+        match filter {
+            log::LevelFilter::Off => LogLevel::Off,
+            log::LevelFilter::Error => LogLevel::Error,
+            log::LevelFilter::Warn => LogLevel::Warn,
+            log::LevelFilter::Info => LogLevel::Info,
+            log::LevelFilter::Debug => LogLevel::Debug,
+            log::LevelFilter::Trace => LogLevel::All,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<tracing::level_filters::LevelFilter> for LogLevel {
+    fn from(filter: tracing::level_filters::LevelFilter) -> Self {
+        match filter.into_level() {
+            None => LogLevel::Off,
+            Some(tracing::Level::ERROR) => LogLevel::Error,
+            Some(tracing::Level::WARN) => LogLevel::Warn,
+            Some(tracing::Level::INFO) => LogLevel::Info,
+            Some(tracing::Level::DEBUG) => LogLevel::Debug,
+            Some(_) => LogLevel::All,
+        }
+    }
+}
+
+/// Replace Julia's global logger with a logger that forwards all messages to `backend`.
+///
+/// Messages below `min_level` are discarded by Julia. If a Rust logger has already been
+/// installed it's replaced.
+pub fn install_logger<'target, Tgt>(
+    target: &Tgt,
+    backend: LogBackend,
+    min_level: LogLevel,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    let fptr = handle_message
+        as unsafe extern "C" fn(i32, i32, isize, *const *const u8, *const usize, usize);
+    let fptr = fptr as *mut c_void;
+    let args = [backend.as_i32(), min_level.as_i32()];
+    call_logging_module(target, "install", Some(fptr), &args)
+}
+
+/// Set the minimum enabled level of the installed Rust logger.
+///
+/// Returns an error if the global logger isn't a Rust logger.
+pub fn set_min_level<'target, Tgt>(target: &Tgt, min_level: LogLevel) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    call_logging_module(target, "set_min_level", None, &[min_level.as_i32()])
+}
+
+/// Restore the global logger that was active before the Rust logger was installed.
+///
+/// Nothing happens if the global logger isn't a Rust logger.
+pub fn uninstall_logger<'target, Tgt>(target: &Tgt) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    call_logging_module(target, "uninstall", None, &[])
+}
+
+fn call_logging_module<'target, Tgt>(
+    target: &Tgt,
+    func: &str,
+    fptr: Option<*mut c_void>,
+    args: &[i32],
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    target.local_scope::<_, _, 3>(|mut frame| unsafe {
+        let module = LOGGING.get_or_init(&frame)?;
+
+        let func = module.function(&frame, func)?.as_managed();
+        let res = match (fptr, args) {
+            (Some(fptr), &[backend, level]) => {
+                let fptr = Value::new(&mut frame, fptr);
+                let backend = Value::new(&mut frame, backend);
+                let level = Value::new(&mut frame, level);
+                func.call3(&frame, fptr, backend, level)
+            }
+            (None, &[level]) => {
+                let level = Value::new(&mut frame, level);
+                func.call1(&frame, level)
+            }
+            _ => func.call0(&frame),
+        };
+
+        res.map_err(|e| e.as_value()).into_jlrs_result()?;
+        Ok(())
+    })
+}
+
+// Called from Julia for every message that is handled by the Rust logger. The first three
+// strings are the message, module and file, the remaining strings are key-value pairs.
+unsafe extern "C" fn handle_message(
+    backend: i32,
+    level: i32,
+    line: isize,
+    ptrs: *const *const u8,
+    lens: *const usize,
+    n_strings: usize,
+) {
+    catch_unwind(AssertUnwindSafe(|| {
+        let ptrs = std::slice::from_raw_parts(ptrs, n_strings);
+        let lens = std::slice::from_raw_parts(lens, n_strings);
+        let strings: Vec<Cow<str>> = ptrs
+            .iter()
+            .zip(lens.iter())
+            .map(|(&ptr, &len)| String::from_utf8_lossy(std::slice::from_raw_parts(ptr, len)))
+            .collect();
+
+        if strings.len() < 3 {
+            return;
+        }
+
+        let message = JuliaMessage {
+            level,
+            message: &strings[0],
+            module: &strings[1],
+            file: &strings[2],
+            line: line.max(0) as u32,
+            key_values: strings[3..]
+                .chunks_exact(2)
+                .map(|kv| (kv[0].as_ref(), kv[1].as_ref()))
+                .collect(),
+        };
+
+        match backend {
+            #[cfg(feature = "log")]
+            0 => message.log(),
+            #[cfg(feature = "tracing")]
+            1 => message.trace(),
+            _ => (),
+        }
+    }))
+    .ok();
+}
+
+struct JuliaMessage<'a> {
+    level: i32,
+    message: &'a str,
+    module: &'a str,
+    file: &'a str,
+    line: u32,
+    key_values: Vec<(&'a str, &'a str)>,
+}
+
+impl JuliaMessage<'_> {
+    #[cfg(feature = "log")]
+    fn log(&self) {
+        let level = match self.level {
+            l if l < JL_DEBUG => log::Level::Trace,
+            l if l < JL_INFO => log::Level::Debug,
+            l if l < JL_WARN => log::Level::Info,
+            l if l < JL_ERROR => log::Level::Warn,
+            _ => log::Level::Error,
+        };
+
+        if level > log::max_level() {
+            return;
+        }
+
+        let target = if self.module.is_empty() {
+            "julia"
+        } else {
+            self.module
+        };
+
+        let metadata = log::Metadata::builder().level(level).target(target).build();
+
+        let logger = log::logger();
+        if !logger.enabled(&metadata) {
+            return;
+        }
+
+        logger.log(
+            &log::Record::builder()
+                .metadata(metadata)
+                .module_path(Some(target))
+                .file(Some(self.file))
+                .line(Some(self.line))
+                .key_values(&self.key_values.as_slice())
+                .args(format_args!("{}", self.message))
+                .build(),
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    fn trace(&self) {
+        use std::fmt::Write;
+
+        let mut fields = String::new();
+        for (key, value) in self.key_values.iter() {
+            if !fields.is_empty() {
+                fields.push(' ');
+            }
+            write!(fields, "{key}={value}").ok();
+        }
+
+        macro_rules! event {
+            ($level:expr) => {
+                tracing::event!(
+                    target: "julia",
+                    $level,
+                    module = self.module,
+                    file = self.file,
+                    line = self.line,
+                    fields = fields.as_str(),
+                    "{}",
+                    self.message
+                )
+            };
+        }
+
+        match self.level {
+            l if l < JL_DEBUG => event!(tracing::Level::TRACE),
+            l if l < JL_INFO => event!(tracing::Level::DEBUG),
+            l if l < JL_WARN => event!(tracing::Level::INFO),
+            l if l < JL_ERROR => event!(tracing::Level::WARN),
+            _ => event!(tracing::Level::ERROR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogLevel;
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn log_level_filter_conversion() {
+        assert_eq!(LogLevel::from(log::LevelFilter::Off), LogLevel::Off);
+        assert_eq!(LogLevel::from(log::LevelFilter::Warn), LogLevel::Warn);
+        assert_eq!(LogLevel::from(log::LevelFilter::Trace), LogLevel::All);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_level_filter_conversion() {
+        use tracing::level_filters::LevelFilter;
+
+        assert_eq!(LogLevel::from(LevelFilter::OFF), LogLevel::Off);
+        assert_eq!(LogLevel::from(LevelFilter::INFO), LogLevel::Info);
+        assert_eq!(LogLevel::from(LevelFilter::TRACE), LogLevel::All);
+    }
+
+    #[test]
+    fn levels_are_ordered() {
+        assert!(LogLevel::All < LogLevel::Debug);
+        assert!(LogLevel::Error < LogLevel::Off);
+    }
+}
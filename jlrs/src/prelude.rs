@@ -7,8 +7,8 @@ pub use jlrs_macros::julia_module;
 pub use jlrs_macros::julia_version;
 #[cfg(feature = "jlrs-derive")]
 pub use jlrs_macros::{
-    CCallArg, CCallReturn, ConstructType, HasLayout, IntoJulia, IsBits, Typecheck, Unbox,
-    ValidField, ValidLayout,
+    CCallArg, CCallReturn, ConstructType, FromJulia, FromJuliaException, HasLayout, IntoJulia,
+    IntoJuliaException, IsBits, ToJulia, Typecheck, Unbox, ValidField, ValidLayout,
 };
 
 #[cfg(feature = "ccall")]
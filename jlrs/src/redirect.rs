@@ -0,0 +1,310 @@
+//! Redirect Julia's `stdout` and `stderr`.
+//!
+//! By default, everything Julia prints is written directly to the standard output and error
+//! streams of the process. Output written to Julia's `stdout` and `stderr`, e.g. by `println` or
+//! `@show`, can be redirected to an arbitrary [`Write`] implementation with
+//! [`redirect_output`]. The original streams can be restored with [`restore_output`].
+//!
+//! [`LineCallback`] implements `Write` and calls a closure for every line of output, which can
+//! be used to forward Julia's output to a logger.
+//!
+//! Redirection works by replacing the global `Base.stdout` or `Base.stderr` with an instance of
+//! an `IO` subtype that forwards everything written to it to Rust. Julia objects that have
+//! captured the original stream before it was redirected, e.g. the global logger, continue to
+//! write to the original stream.
+//!
+//! The same functionality is available as methods of `Julia`, `AsyncJulia`, and `CCall`.
+
+use std::{
+    ffi::c_void,
+    io::{self, Write},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::managed::{module::HelperModule, value::Value},
+    error::JlrsResult,
+    memory::target::Target,
+};
+
+const REDIRECT_MODULE: &str = "module JlrsRedirect
+    mutable struct RustIO <: IO
+        stream::Int32
+        fptr::Ptr{Cvoid}
+    end
+
+    function Base.unsafe_write(io::RustIO, p::Ptr{UInt8}, n::UInt)
+        ccall(io.fptr, Csize_t, (Int32, Ptr{UInt8}, Csize_t), io.stream, p, n)
+    end
+
+    Base.write(io::RustIO, b::UInt8) = unsafe_write(io, Ref(b), 1)
+    Base.isopen(::RustIO) = true
+
+    function Base.flush(io::RustIO)
+        ccall(io.fptr, Csize_t, (Int32, Ptr{UInt8}, Csize_t), io.stream, C_NULL, 0)
+        nothing
+    end
+
+    const originals = Dict{Int32,IO}()
+
+    streamname(stream::Int32) = stream == 1 ? :stdout : :stderr
+
+    function redirect(stream::Int32, fptr::Ptr{Cvoid})
+        name = streamname(stream)
+        current = getfield(Base, name)
+        current isa RustIO || (originals[stream] = current)
+        Core.eval(Base, Expr(:(=), name, RustIO(stream, fptr)))
+        nothing
+    end
+
+    function restore(stream::Int32)
+        haskey(originals, stream) || return nothing
+        Core.eval(Base, Expr(:(=), streamname(stream), pop!(originals, stream)))
+        nothing
+    end
+end";
+
+static REDIRECT: HelperModule = HelperModule::new("JlrsRedirect", REDIRECT_MODULE);
+
+// The writers Julia's output is redirected to, indexed by `StdStream::index`.
+static TARGETS: [Mutex<Option<Box<dyn Write + Send>>>; 2] = [Mutex::new(None), Mutex::new(None)];
+
+/// One of Julia's standard output streams.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StdStream {
+    /// `Base.stdout`
+    Stdout,
+    /// `Base.stderr`
+    Stderr,
+}
+
+impl StdStream {
+    // The file descriptor of the stream, used to identify it in Julia.
+    #[inline]
+    fn fd(self) -> i32 {
+        match self {
+            StdStream::Stdout => 1,
+            StdStream::Stderr => 2,
+        }
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        match self {
+            StdStream::Stdout => 0,
+            StdStream::Stderr => 1,
+        }
+    }
+
+    #[inline]
+    fn from_fd(fd: i32) -> Self {
+        if fd == 1 {
+            StdStream::Stdout
+        } else {
+            StdStream::Stderr
+        }
+    }
+}
+
+/// Redirect Julia's `stdout` or `stderr` to `writer`.
+///
+/// If the stream has already been redirected, the previous writer is flushed and dropped. If an
+/// error is returned, the stream remains unchanged.
+pub fn redirect_output<'target, Tgt, W>(
+    target: &Tgt,
+    stream: StdStream,
+    writer: W,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+    W: Write + Send + 'static,
+{
+    // Output that is written before the new writer has been installed is written to the previous
+    // writer, or to the standard stream of the process if there is none.
+    let fptr = write_output as unsafe extern "C" fn(i32, *const u8, usize) -> usize;
+    call_redirect_module(target, "redirect", stream, Some(fptr as *mut c_void))?;
+
+    let previous = lock_target(stream).replace(Box::new(writer));
+    if let Some(mut previous) = previous {
+        previous.flush().ok();
+    }
+
+    Ok(())
+}
+
+/// Restore Julia's original `stdout` or `stderr`.
+///
+/// The writer the stream was redirected to is flushed and dropped. Nothing happens if the stream
+/// hasn't been redirected.
+pub fn restore_output<'target, Tgt>(target: &Tgt, stream: StdStream) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    call_redirect_module(target, "restore", stream, None)?;
+
+    let previous = lock_target(stream).take();
+    if let Some(mut previous) = previous {
+        previous.flush().ok();
+    }
+
+    Ok(())
+}
+
+/// A writer that calls a closure for every line that is written to it.
+///
+/// The line is passed to the closure without the trailing newline, invalid UTF-8 is replaced
+/// with `U+FFFD`. An incomplete final line is passed to the closure when the writer is dropped.
+pub struct LineCallback<F>
+where
+    F: FnMut(&str),
+{
+    buffer: Vec<u8>,
+    func: F,
+}
+
+impl<F> LineCallback<F>
+where
+    F: FnMut(&str),
+{
+    /// Create a new `LineCallback` that calls `func` for every line.
+    #[inline]
+    pub fn new(func: F) -> Self {
+        LineCallback {
+            buffer: Vec::new(),
+            func,
+        }
+    }
+
+    // Calls `func` with `buffer[start..end]`, a trailing carriage return is removed.
+    fn emit(&mut self, start: usize, end: usize) {
+        let mut line = &self.buffer[start..end];
+        if line.last() == Some(&b'\r') {
+            line = &line[..line.len() - 1];
+        }
+
+        (self.func)(&String::from_utf8_lossy(line));
+    }
+}
+
+impl<F> Write for LineCallback<F>
+where
+    F: FnMut(&str),
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        let mut start = 0;
+        while let Some(pos) = self.buffer[start..].iter().position(|&b| b == b'\n') {
+            let end = start + pos;
+            self.emit(start, end);
+            start = end + 1;
+        }
+
+        self.buffer.drain(..start);
+        Ok(buf.len())
+    }
+
+    // Incomplete lines are not emitted when the writer is flushed because Julia flushes its
+    // output streams frequently.
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<F> Drop for LineCallback<F>
+where
+    F: FnMut(&str),
+{
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            self.emit(0, self.buffer.len());
+        }
+    }
+}
+
+fn call_redirect_module<'target, Tgt>(
+    target: &Tgt,
+    func: &str,
+    stream: StdStream,
+    fptr: Option<*mut c_void>,
+) -> JlrsResult<()>
+where
+    Tgt: Target<'target>,
+{
+    target.local_scope::<_, _, 2>(|mut frame| unsafe {
+        let module = REDIRECT.get_or_init(&frame)?;
+
+        let func = module.function(&frame, func)?.as_managed();
+        let stream = Value::new(&mut frame, stream.fd());
+        let res = match fptr {
+            Some(fptr) => {
+                let fptr = Value::new(&mut frame, fptr);
+                func.call2(&frame, stream, fptr)
+            }
+            None => func.call1(&frame, stream),
+        };
+
+        res.map_err(|e| e.as_value()).into_jlrs_result()?;
+        Ok(())
+    })
+}
+
+#[inline]
+fn lock_target(stream: StdStream) -> MutexGuard<'static, Option<Box<dyn Write + Send>>> {
+    // A panic while writing must not disable redirection.
+    TARGETS[stream.index()]
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+// Called from Julia to write `len` bytes to the writer associated with `fd`. If `data` is null
+// the writer is flushed. If the stream has been restored while Julia still holds on to the
+// redirected stream, the data is written to the standard stream of the process.
+unsafe extern "C" fn write_output(fd: i32, data: *const u8, len: usize) -> usize {
+    let stream = StdStream::from_fd(fd);
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        let mut target = lock_target(stream);
+        let writer: &mut dyn Write = match target.as_mut() {
+            Some(writer) => writer.as_mut(),
+            None => {
+                std::mem::drop(target);
+                return write_fallback(stream, data, len);
+            }
+        };
+
+        if data.is_null() {
+            writer.flush().ok();
+            return 0;
+        }
+
+        let bytes = std::slice::from_raw_parts(data, len);
+        match writer.write_all(bytes) {
+            Ok(_) => len,
+            Err(_) => 0,
+        }
+    }));
+
+    res.unwrap_or(0)
+}
+
+unsafe fn write_fallback(stream: StdStream, data: *const u8, len: usize) -> usize {
+    let mut writer: Box<dyn Write> = match stream {
+        StdStream::Stdout => Box::new(io::stdout()),
+        StdStream::Stderr => Box::new(io::stderr()),
+    };
+
+    if data.is_null() {
+        writer.flush().ok();
+        return 0;
+    }
+
+    let bytes = std::slice::from_raw_parts(data, len);
+    match writer.write_all(bytes) {
+        Ok(_) => len,
+        Err(_) => 0,
+    }
+}
@@ -1,8 +1,13 @@
-use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, sync::Arc, time::Duration};
 
 use jl_sys::{jl_adopt_thread, jl_gc_safepoint, jlrs_gc_safe_enter, jlrs_gc_safe_leave};
 
-use super::{queue::Receiver, AsyncRuntime, Message, MessageInner};
+use super::{
+    queue::Receiver,
+    shutdown::{abort_running, set_current_shutdown, RunningTask, ShutdownState},
+    stats::StatsCollector,
+    AsyncRuntime, Message, MessageInner,
+};
 use crate::{
     async_util::task::sleep,
     error::JlrsResult,
@@ -14,8 +19,10 @@ pub(crate) unsafe fn init_worker<R: AsyncRuntime, const N: usize>(
     worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    stats: Arc<StatsCollector>,
+    shutdown: Arc<ShutdownState>,
 ) -> std::thread::JoinHandle<JlrsResult<()>> {
-    R::spawn_thread(move || run_async::<R, N>(worker_id, recv_timeout, receiver))
+    R::spawn_thread(move || run_async::<R, N>(worker_id, recv_timeout, receiver, stats, shutdown))
 }
 
 #[inline]
@@ -23,21 +30,38 @@ fn run_async<R: AsyncRuntime, const N: usize>(
     worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    stats: Arc<StatsCollector>,
+    shutdown: Arc<ShutdownState>,
 ) -> JlrsResult<()> {
     let mut base_frame = StackFrame::<N>::new_n();
     R::block_on(
-        unsafe { run_inner::<R, N>(recv_timeout, receiver, &mut base_frame) },
+        unsafe {
+            run_inner::<R, N>(
+                worker_id,
+                recv_timeout,
+                receiver,
+                stats,
+                shutdown,
+                &mut base_frame,
+            )
+        },
         Some(worker_id),
     )
 }
 
 async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
+    worker_id: usize,
     recv_timeout: Duration,
     receiver: Receiver<Message>,
+    stats: Arc<StatsCollector>,
+    shutdown: Arc<ShutdownState>,
     base_frame: &mut StackFrame<N>,
 ) -> JlrsResult<()> {
     let _ = jl_adopt_thread();
 
+    // The main runtime thread is thread 0.
+    let thread = worker_id + 1;
+
     let base_frame: &'static mut StackFrame<N> = std::mem::transmute(base_frame);
     let mut pinned = base_frame.pin();
     let base_frame = pinned.stack_frame();
@@ -60,7 +84,13 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
         Rc::new(RefCell::new(running_tasks.into_boxed_slice()))
     };
 
+    set_current_shutdown(shutdown.clone());
+
     loop {
+        if shutdown.deadline_passed() {
+            break;
+        }
+
         let n_free = free_stacks.borrow().len();
         if n_free == 0 {
             sleep(&Unrooted::new(), recv_timeout);
@@ -83,62 +113,80 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
 
         match msg {
             None => jl_gc_safepoint(),
-            Some(Ok(msg)) => match msg.inner {
-                MessageInner::Task(task) => {
-                    let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                    let stack = base_frame.nth_stack(idx);
-
-                    let task = {
-                        let free_stacks = free_stacks.clone();
-                        let running_tasks = running_tasks.clone();
-
-                        R::spawn_local(async move {
-                            task.call(stack).await;
-                            free_stacks.borrow_mut().push_back(idx);
-                            running_tasks.borrow_mut()[idx] = None;
-                        })
-                    };
-
-                    running_tasks.borrow_mut()[idx] = Some(task);
-                }
-                MessageInner::BlockingTask(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
+            Some(Ok(msg)) => {
+                shutdown.add_drained();
+                let received_at = stats.received(thread, msg.queued_at);
+                match msg.inner {
+                    MessageInner::Task(task) => {
+                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                        let stack = base_frame.nth_stack(idx);
+                        let persistent = task.is_persistent();
+                        stats.started(thread, persistent);
+
+                        let task = {
+                            let free_stacks = free_stacks.clone();
+                            let running_tasks = running_tasks.clone();
+                            let stats = stats.clone();
+
+                            RunningTask::<R>::spawn(async move {
+                                task.call(stack).await;
+                                free_stacks.borrow_mut().push_back(idx);
+                                running_tasks.borrow_mut()[idx] = None;
+                                stats.finished(thread, persistent, received_at);
+                            })
+                        };
+
+                        running_tasks.borrow_mut()[idx] = Some(task);
+                    }
+                    MessageInner::BlockingTask(task) => {
+                        let stack = base_frame.sync_stack();
+                        task.call(stack);
+                        stats.completed(received_at);
+                    }
+                    MessageInner::PostBlockingTask(task) => {
+                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                        let stack = base_frame.nth_stack(idx);
+                        stats.started(thread, false);
+
+                        let task = {
+                            let free_stacks = free_stacks.clone();
+                            let running_tasks = running_tasks.clone();
+                            let stats = stats.clone();
+
+                            RunningTask::<R>::spawn(async move {
+                                task.post(stack).await;
+                                free_stacks.borrow_mut().push_back(idx);
+                                running_tasks.borrow_mut()[idx] = None;
+                                stats.finished(thread, false, received_at);
+                            })
+                        };
+
+                        running_tasks.borrow_mut()[idx] = Some(task);
+                    }
+                    MessageInner::Include(task) => {
+                        let stack = base_frame.sync_stack();
+                        task.call(stack);
+                        stats.completed(received_at);
+                    }
+                    MessageInner::ErrorColor(task) => {
+                        let stack = base_frame.sync_stack();
+                        task.call(stack);
+                        stats.completed(received_at);
+                    }
                 }
-                MessageInner::PostBlockingTask(task) => {
-                    let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                    let stack = base_frame.nth_stack(idx);
-
-                    let task = {
-                        let free_stacks = free_stacks.clone();
-                        let running_tasks = running_tasks.clone();
-
-                        R::spawn_local(async move {
-                            task.post(stack).await;
-                            free_stacks.borrow_mut().push_back(idx);
-                            running_tasks.borrow_mut()[idx] = None;
-                        })
-                    };
-
-                    running_tasks.borrow_mut()[idx] = Some(task);
-                }
-                MessageInner::Include(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
-                }
-                MessageInner::ErrorColor(task) => {
-                    let stack = base_frame.sync_stack();
-                    task.call(stack);
-                }
-            },
+            }
             _ => break,
         }
     }
 
     // Wait for all tasks to complete without blocking the thread.
-    for i in 0..N {
+    'wait: for i in 0..N {
         loop {
             if running_tasks.borrow()[i].is_some() {
+                if shutdown.deadline_passed() {
+                    break 'wait;
+                }
+
                 R::yield_now().await;
                 sleep(&Unrooted::new(), recv_timeout);
                 jl_gc_safepoint();
@@ -148,5 +196,10 @@ async unsafe fn run_inner<R: AsyncRuntime, const N: usize>(
         }
     }
 
+    // The stacks used by abandoned tasks are freed after this function returns, and Julia is
+    // shut down after all worker threads have stopped, so they must be dropped now.
+    let n_abandoned = abort_running(&running_tasks);
+    shutdown.add_abandoned(n_abandoned);
+
     Ok(())
 }
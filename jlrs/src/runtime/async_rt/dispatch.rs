@@ -1,16 +1,45 @@
 //! Dispatch a task to the async runtime.
 
-use std::{fmt::Debug, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData, time::Instant};
 
 use crate::{
     async_util::affinity::{Affinity, ToAny, ToMain, ToWorker},
-    runtime::async_rt::{queue::Sender, Message},
+    error::{JlrsResult, RuntimeError},
+    runtime::async_rt::{
+        queue::{PushError, Schedule, Sender},
+        Message,
+    },
 };
 
+/// Error returned when a task can't be dispatched immediately.
+pub enum TryDispatchError<D> {
+    /// The backing queue is full, the dispatcher is returned to allow retrying.
+    Full(D),
+    /// The runtime is shutting down, the task has been dropped.
+    ShuttingDown,
+}
+
+impl<D> Debug for TryDispatchError<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TryDispatchError::Full(_) => f.write_str("Full(..)"),
+            TryDispatchError::ShuttingDown => f.write_str("ShuttingDown"),
+        }
+    }
+}
+
 /// Dispatch a task to the async runtime.
+///
+/// By default a task is dispatched with the lowest priority and without a deadline. If the async
+/// runtime has been configured to use multiple priority classes with
+/// [`AsyncRuntimeBuilder::priority_classes`], a higher priority can be set with
+/// [`Dispatch::with_priority`].
+///
+/// [`AsyncRuntimeBuilder::priority_classes`]: crate::runtime::builder::AsyncRuntimeBuilder::priority_classes
 pub struct Dispatch<'a, D> {
     msg: Message,
     sender: &'a Sender<Message>,
+    schedule: Schedule,
     _dispatch: PhantomData<D>,
 }
 
@@ -26,35 +55,70 @@ impl<'a, D: Affinity> Dispatch<'a, D> {
         Dispatch {
             msg,
             sender,
+            schedule: Schedule::default(),
             _dispatch: PhantomData,
         }
     }
+
+    /// Set the priority class of the task.
+    ///
+    /// Classes are numbered from 0, the default and lowest priority, to `n - 1`, where `n` is
+    /// the number of priority classes of the async runtime. A priority larger than `n - 1` is
+    /// treated as `n - 1`. When a runtime thread takes a new task from the queue it takes the one
+    /// with the highest priority, if aging is enabled the priority of a task increases while
+    /// it's waiting in the queue.
+    #[inline]
+    pub fn with_priority(mut self, priority: usize) -> Self {
+        self.schedule.priority = priority;
+        self
+    }
+
+    /// Set the deadline of the task.
+    ///
+    /// Once its deadline has passed, a task that is still waiting in the queue is taken before
+    /// all tasks whose deadline hasn't passed regardless of their priority. Tasks whose deadlines
+    /// have passed are taken in order of their deadline. Tasks aren't dropped if their deadline
+    /// has passed.
+    #[inline]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.schedule.deadline = Some(deadline);
+        self
+    }
 }
 
 impl<'a, D: ToAny> Dispatch<'a, D> {
     /// Dispatch the task to any thread.
     ///
     /// The dispatched task can be handled by either the main thread or any of the worker threads.
-    /// This method doesn't resolve until the task has been successfully dispatched.
+    /// This method doesn't resolve until the task has been successfully dispatched. An error is
+    /// returned if the runtime is shutting down.
     #[inline]
-    pub async fn dispatch_any(self) {
-        self.sender.send(self.msg).await
+    pub async fn dispatch_any(mut self) -> JlrsResult<()> {
+        self.msg.queued_at = Instant::now();
+        self.sender
+            .send(self.msg, self.schedule)
+            .await
+            .map_err(|_| RuntimeError::ShuttingDown)?;
+        Ok(())
     }
 
     /// Try to dispatch the task to any thread.
     ///
     /// The dispatched task can be handled by either the main thread or any of the worker threads.
-    /// If the backing queue is full, the dispatcher is returned to allow retrying.
+    /// If the backing queue is full, the dispatcher is returned to allow retrying. If the runtime
+    /// is shutting down, [`TryDispatchError::ShuttingDown`] is returned.
     #[inline]
-    pub fn try_dispatch_any(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send(self.msg) {
-            Err(Dispatch {
+    pub fn try_dispatch_any(mut self) -> Result<(), TryDispatchError<Self>> {
+        self.msg.queued_at = Instant::now();
+        match self.sender.try_send(self.msg, self.schedule) {
+            Ok(()) => Ok(()),
+            Err(PushError::Full(msg)) => Err(TryDispatchError::Full(Dispatch {
                 msg,
                 sender: self.sender,
+                schedule: self.schedule,
                 _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
+            })),
+            Err(PushError::Closed(_)) => Err(TryDispatchError::ShuttingDown),
         }
     }
 }
@@ -63,26 +127,35 @@ impl<'a, D: ToMain> Dispatch<'a, D> {
     /// Dispatch the task to the main thread.
     ///
     /// The dispatched task is guaranteed to be handled by the main thread. This method doesn't
-    /// resolve until the task has been successfully dispatched.
+    /// resolve until the task has been successfully dispatched. An error is returned if the
+    /// runtime is shutting down.
     #[inline]
-    pub async fn dispatch_main(self) {
-        self.sender.send_main(self.msg).await
+    pub async fn dispatch_main(mut self) -> JlrsResult<()> {
+        self.msg.queued_at = Instant::now();
+        self.sender
+            .send_main(self.msg, self.schedule)
+            .await
+            .map_err(|_| RuntimeError::ShuttingDown)?;
+        Ok(())
     }
 
     /// Try to dispatch the task to the main thread.
     ///
     /// The dispatched task is guaranteed to be handled by the main thread. If the backing queue
-    /// is full, the dispatcher is returned to allow retrying.
+    /// is full, the dispatcher is returned to allow retrying. If the runtime is shutting down,
+    /// [`TryDispatchError::ShuttingDown`] is returned.
     #[inline]
-    pub fn try_dispatch_main(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send_main(self.msg) {
-            Err(Dispatch {
+    pub fn try_dispatch_main(mut self) -> Result<(), TryDispatchError<Self>> {
+        self.msg.queued_at = Instant::now();
+        match self.sender.try_send_main(self.msg, self.schedule) {
+            Ok(()) => Ok(()),
+            Err(PushError::Full(msg)) => Err(TryDispatchError::Full(Dispatch {
                 msg,
                 sender: self.sender,
+                schedule: self.schedule,
                 _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
+            })),
+            Err(PushError::Closed(_)) => Err(TryDispatchError::ShuttingDown),
         }
     }
 }
@@ -92,27 +165,35 @@ impl<'a, D: ToWorker> Dispatch<'a, D> {
     ///
     /// The dispatched task is guaranteed to be handled by a worker thread if they're used,
     /// otherwise it's handled by the main thread. This method doesn't resolve until the task has
-    /// been successfully dispatched.
+    /// been successfully dispatched. An error is returned if the runtime is shutting down.
     #[inline]
-    pub async fn dispatch_worker(self) {
-        self.sender.send_worker(self.msg).await
+    pub async fn dispatch_worker(mut self) -> JlrsResult<()> {
+        self.msg.queued_at = Instant::now();
+        self.sender
+            .send_worker(self.msg, self.schedule)
+            .await
+            .map_err(|_| RuntimeError::ShuttingDown)?;
+        Ok(())
     }
 
     /// Try to dispatch the task to a worker thread.
     ///
     /// The dispatched task is guaranteed to be handled by a worker thread if they're used,
     /// otherwise it's handled by the main thread.  If the backing queue is full, the dispatcher
-    /// is returned to allow retrying.
+    /// is returned to allow retrying. If the runtime is shutting down,
+    /// [`TryDispatchError::ShuttingDown`] is returned.
     #[inline]
-    pub fn try_dispatch_worker(self) -> Result<(), Self> {
-        if let Some(msg) = self.sender.try_send_worker(self.msg) {
-            Err(Dispatch {
+    pub fn try_dispatch_worker(mut self) -> Result<(), TryDispatchError<Self>> {
+        self.msg.queued_at = Instant::now();
+        match self.sender.try_send_worker(self.msg, self.schedule) {
+            Ok(()) => Ok(()),
+            Err(PushError::Full(msg)) => Err(TryDispatchError::Full(Dispatch {
                 msg,
                 sender: self.sender,
+                schedule: self.schedule,
                 _dispatch: PhantomData,
-            })
-        } else {
-            Ok(())
+            })),
+            Err(PushError::Closed(_)) => Err(TryDispatchError::ShuttingDown),
         }
     }
 }
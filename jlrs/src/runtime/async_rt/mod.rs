@@ -26,6 +26,9 @@ pub mod adopted;
 pub mod async_std_rt;
 pub mod dispatch;
 pub mod queue;
+pub mod shutdown;
+pub mod stats;
+pub mod supervision;
 #[cfg(feature = "tokio-rt")]
 pub mod tokio_rt;
 
@@ -35,10 +38,11 @@ use std::{
     ffi::{c_void, CStr},
     fmt,
     marker::PhantomData,
+    num::NonZeroUsize,
     path::Path,
     rc::Rc,
     sync::{atomic::Ordering, Arc},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use async_trait::async_trait;
@@ -56,22 +60,36 @@ use self::adopted::init_worker;
 use self::{
     dispatch::Dispatch,
     queue::{channel, Receiver, Sender},
+    shutdown::{
+        abort_running, set_current_shutdown, RunningTask, ShutdownMode, ShutdownState,
+        ShutdownSummary,
+    },
+    stats::{RuntimeStats, StatsCollector},
+    supervision::{Liveness, PersistentHealth, SupervisionPolicy},
 };
 use crate::{
     async_util::{
         affinity::{Affinity, DispatchAny, DispatchMain},
+        bridge::{FromJuliaBridge, ToJuliaBridge},
+        cancellation::CancellationHandle,
         channel::{Channel, ChannelSender, OneshotSender, TrySendError},
         envelope::{
             BlockingTask, BlockingTaskEnvelope, CallPersistentTask, IncludeTask,
             IncludeTaskEnvelope, InnerPersistentMessage, PendingTask, PendingTaskEnvelope,
             Persistent, PersistentComms, RegisterPersistent, RegisterTask, SetErrorColorTask,
-            SetErrorColorTaskEnvelope, Task,
+            SetErrorColorTaskEnvelope, StreamTask, Task,
         },
         future::wake_task,
+        stream::JuliaStream,
         task::{sleep, AsyncTask, PersistentTask},
     },
-    convert::into_result::IntoResult,
-    data::managed::{module::Module, value::Value},
+    convert::{
+        from_julia::FromJulia, into_julia::IntoJulia, into_result::IntoResult, unbox::Unbox,
+    },
+    data::{
+        managed::{module::Module, value::Value},
+        types::typecheck::Typecheck,
+    },
     error::{IOError, JlrsError, JlrsResult, RuntimeError},
     init_jlrs,
     memory::{
@@ -79,7 +97,8 @@ use crate::{
         stack_frame::StackFrame,
         target::{frame::GcFrame, unrooted::Unrooted},
     },
-    runtime::{builder::AsyncRuntimeBuilder, INIT},
+    redirect::{redirect_output, restore_output, StdStream},
+    runtime::{builder::AsyncRuntimeBuilder, options::ValidatedOptions, INIT},
 };
 
 /// Functionality that is necessary to use an async runtime with jlrs.
@@ -145,6 +164,8 @@ pub trait AsyncRuntime: Send + Sync + 'static {
 /// down when the last handle is dropped and all active tasks have completed.
 pub struct AsyncJulia<R> {
     sender: Sender<Message>,
+    stats: Arc<StatsCollector>,
+    shutdown: Arc<ShutdownState>,
     _runtime: PhantomData<R>,
 }
 
@@ -154,10 +175,48 @@ impl<R> AsyncJulia<R>
 where
     R: AsyncRuntime,
 {
+    /// Returns a snapshot of the statistics of the runtime.
+    ///
+    /// See the [`stats`] module for more information.
+    ///
+    /// [`stats`]: crate::runtime::async_rt::stats
+    pub fn stats(&self) -> RuntimeStats {
+        let (main_queue, any_queue, worker_queue) = self.sender.stats();
+        self.stats.snapshot(main_queue, any_queue, worker_queue)
+    }
+
+    /// Shut down the runtime.
+    ///
+    /// The task queues are closed immediately, tasks that are dispatched afterwards are dropped.
+    /// Depending on `mode`, the tasks that are still waiting in the queues are either handled or
+    /// dropped. Persistent tasks stop accepting new calls and their `exit` method is called. The
+    /// returned future resolves when all tasks have completed or have been abandoned because
+    /// the deadline has passed, and Julia's `atexit` hooks have run.
+    ///
+    /// Returns an error if the runtime is already shutting down. See the [`shutdown`] module
+    /// for more information.
+    ///
+    /// [`shutdown`]: crate::runtime::async_rt::shutdown
+    pub async fn shutdown(&self, mode: ShutdownMode) -> JlrsResult<ShutdownSummary> {
+        let receiver = self.shutdown.request(mode)?;
+        self.sender.close();
+
+        if let ShutdownMode::Discard { .. } = mode {
+            self.shutdown.add_discarded(self.sender.clear());
+        }
+
+        match receiver.await {
+            Ok(summary) => Ok(summary),
+            Err(_) => Err(RuntimeError::ChannelClosed)?,
+        }
+    }
+
     /// Resize the task queue.
     ///
-    /// No tasks are dropped if the queue is shrunk. This method return a future that doesn´t
-    /// resolve until the queue can be resized without dropping any tasks.
+    /// The largest priority class is resized to `capacity`, the capacities of the other classes
+    /// are scaled proportionally to the capacities they were created with. No tasks are dropped
+    /// if the queue is shrunk. This method return a future that doesn´t resolve until the queue can be
+    /// resized without dropping any tasks.
     #[inline]
     pub fn resize_queue<'own>(
         &'own self,
@@ -181,8 +240,10 @@ where
 
     /// Resize the task queue of the main runtime thread.
     ///
-    /// No tasks are dropped if the queue is shrunk. This method return a future that doesn´t
-    /// resolve until the queue can be resized without dropping any tasks.
+    /// The largest priority class is resized to `capacity`, the capacities of the other classes
+    /// are scaled proportionally to the capacities they were created with. No tasks are dropped
+    /// if the queue is shrunk. This method return a future that doesn´t resolve until the queue can be
+    /// resized without dropping any tasks.
     #[inline]
     pub fn resize_main_queue<'own>(&'own self, capacity: usize) -> impl 'own + Future<Output = ()> {
         self.sender.resize_main_queue(capacity)
@@ -199,8 +260,10 @@ where
 
     /// Resize the task queue of the worker threads.
     ///
-    /// No tasks are dropped if the queue is shrunk. This method return a future that doesn´t
-    /// resolve until the queue can be resized without dropping any tasks.
+    /// The largest priority class is resized to `capacity`, the capacities of the other classes
+    /// are scaled proportionally to the capacities they were created with. No tasks are dropped
+    /// if the queue is shrunk. This method return a future that doesn´t resolve until the queue can be
+    /// resized without dropping any tasks.
     #[inline]
     pub fn resize_worker_queue<'own>(
         &'own self,
@@ -227,7 +290,7 @@ where
     /// This method waits if there's no room in the channel. It takes two arguments, the task and
     /// the sending half of a channel which is used to send the result back after the task has
     /// completed.
-    pub fn task<A, O>(&self, task: A, res_sender: O) -> Dispatch<A::Affinity>
+    pub fn task<A, O>(&self, task: A, res_sender: O) -> Dispatch<'_, A::Affinity>
     where
         A: AsyncTask,
         O: OneshotSender<JlrsResult<A::Output>>,
@@ -238,12 +301,37 @@ where
         Dispatch::new(&self.sender, msg)
     }
 
+    /// Send a new async task that can be cancelled to the runtime.
+    ///
+    /// This method is similar to [`AsyncJulia::task`], but also returns a [`CancellationHandle`].
+    /// If the task is cancelled, the Julia task it's waiting on is interrupted by throwing an
+    /// `InterruptException` into it, the task is stopped at its current await point, and
+    /// [`CancellationError::Cancelled`] is sent back as an error. All frames of the task are
+    /// popped before the result is sent.
+    ///
+    /// [`CancellationError::Cancelled`]: crate::error::CancellationError::Cancelled
+    pub fn cancellable_task<A, O>(
+        &self,
+        task: A,
+        res_sender: O,
+    ) -> (Dispatch<'_, A::Affinity>, CancellationHandle)
+    where
+        A: AsyncTask,
+        O: OneshotSender<JlrsResult<A::Output>>,
+    {
+        let handle = CancellationHandle::new();
+        let pending_task = PendingTask::<_, _, Task>::new_cancellable(task, res_sender, &handle);
+        let boxed = Box::new(pending_task);
+        let msg = MessageInner::Task(boxed).wrap();
+        (Dispatch::new(&self.sender, msg), handle)
+    }
+
     /// Register an async task.
     ///
     /// This method waits if there's no room in the channel. It takes one argument, the sending
     /// half of a channel which is used to send the result back after the registration has
     /// completed.
-    pub fn register_task<A, O>(&self, res_sender: O) -> Dispatch<A::Affinity>
+    pub fn register_task<A, O>(&self, res_sender: O) -> Dispatch<'_, A::Affinity>
     where
         A: AsyncTask,
         O: OneshotSender<JlrsResult<()>>,
@@ -261,7 +349,7 @@ where
     /// `Send`. The second is the sending half of a channel which is used to send the
     /// result back after the task has completed. This task is executed as soon as possible and
     /// can't call async methods, so it blocks the runtime.
-    pub fn blocking_task<T, O, F>(&self, task: F, res_sender: O) -> Dispatch<DispatchAny>
+    pub fn blocking_task<T, O, F>(&self, task: F, res_sender: O) -> Dispatch<'_, DispatchAny>
     where
         for<'base> F: 'static + Send + FnOnce(GcFrame<'base>) -> JlrsResult<T>,
         O: OneshotSender<JlrsResult<T>>,
@@ -280,7 +368,7 @@ where
     /// `Send`. The second is the sending half of a channel which is used to send the
     /// result back after the task has completed. This task is executed as soon as possible and
     /// can't call async methods, so it blocks the runtime.
-    pub fn blocking_task_with_affinity<A, T, O, F>(&self, task: F, res_sender: O) -> Dispatch<A>
+    pub fn blocking_task_with_affinity<A, T, O, F>(&self, task: F, res_sender: O) -> Dispatch<'_, A>
     where
         A: Affinity,
         for<'base> F: 'static + Send + FnOnce(GcFrame<'base>) -> JlrsResult<T>,
@@ -300,7 +388,7 @@ where
     /// `Send`. The second is the sending half of a channel which is used to send the
     /// result back after the task has completed. This task not called directly, but executed in
     /// a spawned task.
-    pub fn post_blocking_task<T, O, F>(&self, task: F, res_sender: O) -> Dispatch<DispatchAny>
+    pub fn post_blocking_task<T, O, F>(&self, task: F, res_sender: O) -> Dispatch<'_, DispatchAny>
     where
         for<'base> F: 'static + Send + FnOnce(GcFrame<'base>) -> JlrsResult<T>,
         O: OneshotSender<JlrsResult<T>>,
@@ -318,7 +406,7 @@ where
     /// and a `OneshotSender` to send a [`PersistentHandle`] after the task's `init` method has
     /// completed. You must also provide an implementation of [`Channel`] as a type parameter.
     /// This channel is used by the handle to communicate with the persistent task.
-    pub fn persistent<C, P, O>(&self, task: P, handle_sender: O) -> Dispatch<P::Affinity>
+    pub fn persistent<C, P, O>(&self, task: P, handle_sender: O) -> Dispatch<'_, P::Affinity>
     where
         C: Channel<PersistentMessage<P>>,
         P: PersistentTask,
@@ -333,12 +421,40 @@ where
         Dispatch::new(&self.sender, msg)
     }
 
+    /// Send a new supervised persistent task to the runtime.
+    ///
+    /// This method is equivalent to [`AsyncJulia::persistent`], except that the task is
+    /// restarted according to `policy` if it fails. See the [`supervision`] module for more
+    /// information.
+    ///
+    /// The handle is sent after `init` has completed successfully, if the initial call to `init`
+    /// fails it's retried until the task can no longer be restarted.
+    pub fn supervised_persistent<C, P, O>(
+        &self,
+        task: P,
+        policy: SupervisionPolicy,
+        handle_sender: O,
+    ) -> Dispatch<'_, P::Affinity>
+    where
+        C: Channel<PersistentMessage<P>>,
+        P: PersistentTask,
+        O: OneshotSender<JlrsResult<PersistentHandle<P>>>,
+    {
+        let pending_task = PendingTask::<_, _, Persistent>::new(
+            task,
+            PersistentComms::<C, _, _>::supervised(handle_sender, policy),
+        );
+        let boxed = Box::new(pending_task);
+        let msg = MessageInner::Task(boxed).wrap();
+        Dispatch::new(&self.sender, msg)
+    }
+
     /// Register a persistent task.
     ///
     /// This method waits if there's no room in the channel. It takes one argument, the sending
     /// half of a channel which is used to send the result back after the registration has
     /// completed.
-    pub fn register_persistent<P, O>(&self, res_sender: O) -> Dispatch<P::Affinity>
+    pub fn register_persistent<P, O>(&self, res_sender: O) -> Dispatch<'_, P::Affinity>
     where
         P: PersistentTask,
         O: OneshotSender<JlrsResult<()>>,
@@ -349,6 +465,95 @@ where
         Dispatch::new(&self.sender, msg)
     }
 
+    /// Stream the elements of a Julia iterable, e.g. a `Channel`.
+    ///
+    /// The closure `iterable` is called by the runtime and must return the iterable. The runtime
+    /// calls `Base.iterate` as a new Julia task to compute each element, converts it to `T` with
+    /// [`FromJulia`], and sends it to the returned [`JuliaStream`] through a channel of type `C`
+    /// with the given capacity. If that channel is full, no new elements are computed until the
+    /// stream has received one. The stream ends after the iterable has been exhausted, after the
+    /// first error, or when it's dropped.
+    ///
+    /// [`FromJulia`]: crate::convert::from_julia::FromJulia
+    pub fn stream<C, T, F>(
+        &self,
+        iterable: F,
+        capacity: Option<NonZeroUsize>,
+    ) -> (Dispatch<'_, DispatchAny>, JuliaStream<T>)
+    where
+        C: Channel<JlrsResult<T>>,
+        T: FromJulia + Send + 'static,
+        for<'base> F:
+            'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+    {
+        let (sender, receiver) = C::channel(capacity);
+        let pending_task = StreamTask::new(iterable, sender);
+        let msg = MessageInner::Task(Box::new(pending_task)).wrap();
+        (Dispatch::new(&self.sender, msg), JuliaStream::new(receiver))
+    }
+
+    /// Bridge a Julia `Channel{T}` to a Rust async channel.
+    ///
+    /// The closure `channel` is called by the runtime and must return a `Channel{T}`. Every value
+    /// that is put into this channel by Julia is unboxed and sent to the returned receiver through
+    /// a channel of type `C` with the given capacity. If that channel is full, no new values are
+    /// taken from the Julia channel until the receiver has received one. The bridge is closed when
+    /// the Julia channel has been closed and emptied, or when the receiver is dropped, after which
+    /// the result is sent to `res_sender`.
+    ///
+    /// See the [`bridge`] module for more information.
+    ///
+    /// [`bridge`]: crate::async_util::bridge
+    pub fn bridge_from_julia<C, T, F, O>(
+        &self,
+        channel: F,
+        capacity: Option<NonZeroUsize>,
+        res_sender: O,
+    ) -> (Dispatch<'_, DispatchAny>, C::Receiver)
+    where
+        C: Channel<T>,
+        T: IntoJulia + Unbox<Output = T> + Typecheck + Send + 'static,
+        for<'base> F:
+            'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+        O: OneshotSender<JlrsResult<()>>,
+    {
+        let (sender, receiver) = C::channel(capacity);
+        let pending_task = FromJuliaBridge::new(channel, sender, res_sender);
+        let msg = MessageInner::Task(Box::new(pending_task)).wrap();
+        (Dispatch::new(&self.sender, msg), receiver)
+    }
+
+    /// Bridge a Rust async channel to a Julia `Channel{T}`.
+    ///
+    /// The closure `channel` is called by the runtime and must return a `Channel{T}`. Every value
+    /// that is sent with the returned sender, a channel of type `C` with the given capacity, is
+    /// converted to Julia data and put into this channel. If the Julia channel is full, no new
+    /// values are received until Julia has taken one. The bridge is closed when all senders have
+    /// been dropped, which also closes the Julia channel, after which the result is sent to
+    /// `res_sender`.
+    ///
+    /// See the [`bridge`] module for more information.
+    ///
+    /// [`bridge`]: crate::async_util::bridge
+    pub fn bridge_to_julia<C, T, F, O>(
+        &self,
+        channel: F,
+        capacity: Option<NonZeroUsize>,
+        res_sender: O,
+    ) -> (Dispatch<'_, DispatchAny>, C::Sender)
+    where
+        C: Channel<T>,
+        T: IntoJulia + Send + 'static,
+        for<'base> F:
+            'static + Send + FnOnce(&mut GcFrame<'base>) -> JlrsResult<Value<'base, 'static>>,
+        O: OneshotSender<JlrsResult<()>>,
+    {
+        let (sender, receiver) = C::channel(capacity);
+        let pending_task = ToJuliaBridge::new(channel, receiver, res_sender);
+        let msg = MessageInner::Task(Box::new(pending_task)).wrap();
+        (Dispatch::new(&self.sender, msg), sender)
+    }
+
     /// Include a Julia file by calling `Main.include` as a blocking task.
     ///
     /// This method waits if there's no room in the channel. It takes two arguments, the path to
@@ -357,7 +562,11 @@ where
     ///
     /// Safety: this method evaluates the contents of the file if it exists, which can't be
     /// checked for correctness.
-    pub unsafe fn include<P, O>(&self, path: P, res_sender: O) -> JlrsResult<Dispatch<DispatchMain>>
+    pub unsafe fn include<P, O>(
+        &self,
+        path: P,
+        res_sender: O,
+    ) -> JlrsResult<Dispatch<'_, DispatchMain>>
     where
         P: AsRef<Path>,
         O: OneshotSender<JlrsResult<()>>,
@@ -380,7 +589,7 @@ where
     /// to send the result back after the option is set.
     ///
     /// This feature is disabled by default.
-    pub fn error_color<O>(&self, enable: bool, res_sender: O) -> Dispatch<DispatchMain>
+    pub fn error_color<O>(&self, enable: bool, res_sender: O) -> Dispatch<'_, DispatchMain>
     where
         O: OneshotSender<JlrsResult<()>>,
     {
@@ -389,15 +598,66 @@ where
         Dispatch::new(&self.sender, msg)
     }
 
+    /// Redirect Julia's `stdout` or `stderr` to `writer` as a blocking task.
+    ///
+    /// This method waits if there's no room in the channel. It takes three arguments, the stream
+    /// that is redirected, the writer it's redirected to, and the sending half of a channel which
+    /// is used to send the result back after the stream has been redirected.
+    ///
+    /// See the [`redirect`] module for more information.
+    ///
+    /// [`redirect`]: crate::redirect
+    pub fn redirect_output<W, O>(
+        &self,
+        stream: StdStream,
+        writer: W,
+        res_sender: O,
+    ) -> Dispatch<'_, DispatchAny>
+    where
+        W: std::io::Write + Send + 'static,
+        O: OneshotSender<JlrsResult<()>>,
+    {
+        self.blocking_task(
+            move |frame| redirect_output(&frame, stream, writer),
+            res_sender,
+        )
+    }
+
+    /// Restore Julia's original `stdout` or `stderr` as a blocking task.
+    ///
+    /// This method waits if there's no room in the channel. It takes two arguments, the stream
+    /// that is restored and the sending half of a channel which is used to send the result back
+    /// after the stream has been restored.
+    pub fn restore_output<O>(&self, stream: StdStream, res_sender: O) -> Dispatch<'_, DispatchAny>
+    where
+        O: OneshotSender<JlrsResult<()>>,
+    {
+        self.blocking_task(move |frame| restore_output(&frame, stream), res_sender)
+    }
+
     pub(crate) unsafe fn init<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
     ) -> JlrsResult<(Self, std::thread::JoinHandle<JlrsResult<()>>)> {
+        let options = builder.builder.options.validate()?;
+        options.set_depot_path();
         let has_workers = builder.has_workers();
-        let (sender, receiver) = channel(builder.channel_capacity.get(), has_workers);
-        let handle = R::spawn_thread(move || Self::run_async::<N>(builder, receiver));
+        let (sender, receiver) = channel(
+            &builder.class_capacities(),
+            builder.aging_interval,
+            has_workers,
+        );
+        let stats = Arc::new(StatsCollector::new(builder.n_runtime_threads()));
+        let shutdown = Arc::new(ShutdownState::new());
+        let rt_stats = stats.clone();
+        let rt_shutdown = shutdown.clone();
+        let handle = R::spawn_thread(move || {
+            Self::run_async::<N>(builder, options, receiver, rt_stats, rt_shutdown)
+        });
 
         let julia = AsyncJulia {
             sender,
+            stats,
+            shutdown,
             _runtime: PhantomData,
         };
 
@@ -408,12 +668,26 @@ where
     pub(crate) unsafe fn init_async<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
     ) -> JlrsResult<(Self, R::RuntimeHandle)> {
+        let options = builder.builder.options.validate()?;
+        options.set_depot_path();
         let has_workers = builder.has_workers();
-        let (sender, receiver) = channel(builder.channel_capacity.get(), has_workers);
-        let handle = R::spawn_blocking(move || Self::run_async::<N>(builder, receiver));
+        let (sender, receiver) = channel(
+            &builder.class_capacities(),
+            builder.aging_interval,
+            has_workers,
+        );
+        let stats = Arc::new(StatsCollector::new(builder.n_runtime_threads()));
+        let shutdown = Arc::new(ShutdownState::new());
+        let rt_stats = stats.clone();
+        let rt_shutdown = shutdown.clone();
+        let handle = R::spawn_blocking(move || {
+            Self::run_async::<N>(builder, options, receiver, rt_stats, rt_shutdown)
+        });
 
         let julia = AsyncJulia {
             sender,
+            stats,
+            shutdown,
             _runtime: PhantomData,
         };
 
@@ -422,7 +696,10 @@ where
 
     fn run_async<const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
+        options: ValidatedOptions,
         receiver: Receiver<Message>,
+        stats: Arc<StatsCollector>,
+        shutdown: Arc<ShutdownState>,
     ) -> JlrsResult<()> {
         unsafe {
             if jl_is_initialized() != 0 || INIT.swap(true, Ordering::Relaxed) {
@@ -467,6 +744,8 @@ where
                 }
             }
 
+            options.apply();
+
             if let Some((ref julia_bindir, ref image_path)) = builder.builder.image {
                 let julia_bindir_str = julia_bindir.to_string_lossy().to_string();
                 let image_path_str = image_path.to_string_lossy().to_string();
@@ -490,11 +769,13 @@ where
             } else {
                 jl_init();
             }
+
+            options.preload()?;
         }
 
         let mut base_frame = StackFrame::<N>::new_n();
         R::block_on(
-            unsafe { Self::run_inner(builder, receiver, &mut base_frame) },
+            unsafe { Self::run_inner(builder, receiver, stats, shutdown, &mut base_frame) },
             None,
         )
     }
@@ -502,6 +783,8 @@ where
     async unsafe fn run_inner<'ctx, const N: usize>(
         builder: AsyncRuntimeBuilder<R>,
         receiver: Receiver<Message>,
+        stats: Arc<StatsCollector>,
+        shutdown: Arc<ShutdownState>,
         base_frame: &'ctx mut StackFrame<N>,
     ) -> Result<(), Box<JlrsError>> {
         let base_frame: &'static mut StackFrame<N> = std::mem::transmute(base_frame);
@@ -537,14 +820,29 @@ where
         let mut workers = Vec::with_capacity(builder.n_workers);
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         for i in 0..builder.n_workers {
-            let worker = init_worker::<R, N>(i, recv_timeout, receiver.clone());
+            let worker = init_worker::<R, N>(
+                i,
+                recv_timeout,
+                receiver.clone(),
+                stats.clone(),
+                shutdown.clone(),
+            );
             workers.push(worker)
         }
 
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         jl_enter_threaded_region();
 
+        set_current_shutdown(shutdown.clone());
+
         loop {
+            stats.update_gc();
+
+            if shutdown.deadline_passed() {
+                shutdown.add_discarded(receiver.clear());
+                break;
+            }
+
             if free_stacks.borrow().len() == 0 {
                 jl_process_events();
                 R::yield_now().await;
@@ -557,61 +855,79 @@ where
                     jl_process_events();
                     jl_yield();
                 }
-                Some(Ok(msg)) => match msg.inner {
-                    MessageInner::Task(task) => {
-                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                        let stack = base_frame.nth_stack(idx);
-
-                        let task = {
-                            let free_stacks = free_stacks.clone();
-                            let running_tasks = running_tasks.clone();
-
-                            R::spawn_local(async move {
-                                task.call(stack).await;
-                                free_stacks.borrow_mut().push_back(idx);
-                                running_tasks.borrow_mut()[idx] = None;
-                            })
-                        };
-
-                        running_tasks.borrow_mut()[idx] = Some(task);
-                    }
-                    MessageInner::BlockingTask(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
-                    }
-                    MessageInner::PostBlockingTask(task) => {
-                        let idx = free_stacks.borrow_mut().pop_front().unwrap();
-                        let stack = base_frame.nth_stack(idx);
-
-                        let task = {
-                            let free_stacks = free_stacks.clone();
-                            let running_tasks = running_tasks.clone();
-
-                            R::spawn_local(async move {
-                                task.post(stack).await;
-                                free_stacks.borrow_mut().push_back(idx);
-                                running_tasks.borrow_mut()[idx] = None;
-                            })
-                        };
-
-                        running_tasks.borrow_mut()[idx] = Some(task);
+                Some(Ok(msg)) => {
+                    shutdown.add_drained();
+                    let received_at = stats.received(0, msg.queued_at);
+                    match msg.inner {
+                        MessageInner::Task(task) => {
+                            let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                            let stack = base_frame.nth_stack(idx);
+                            let persistent = task.is_persistent();
+                            stats.started(0, persistent);
+
+                            let task = {
+                                let free_stacks = free_stacks.clone();
+                                let running_tasks = running_tasks.clone();
+                                let stats = stats.clone();
+
+                                RunningTask::<R>::spawn(async move {
+                                    task.call(stack).await;
+                                    free_stacks.borrow_mut().push_back(idx);
+                                    running_tasks.borrow_mut()[idx] = None;
+                                    stats.finished(0, persistent, received_at);
+                                })
+                            };
+
+                            running_tasks.borrow_mut()[idx] = Some(task);
+                        }
+                        MessageInner::BlockingTask(task) => {
+                            let stack = base_frame.sync_stack();
+                            task.call(stack);
+                            stats.completed(received_at);
+                        }
+                        MessageInner::PostBlockingTask(task) => {
+                            let idx = free_stacks.borrow_mut().pop_front().unwrap();
+                            let stack = base_frame.nth_stack(idx);
+                            stats.started(0, false);
+
+                            let task = {
+                                let free_stacks = free_stacks.clone();
+                                let running_tasks = running_tasks.clone();
+                                let stats = stats.clone();
+
+                                RunningTask::<R>::spawn(async move {
+                                    task.post(stack).await;
+                                    free_stacks.borrow_mut().push_back(idx);
+                                    running_tasks.borrow_mut()[idx] = None;
+                                    stats.finished(0, false, received_at);
+                                })
+                            };
+
+                            running_tasks.borrow_mut()[idx] = Some(task);
+                        }
+                        MessageInner::Include(task) => {
+                            let stack = base_frame.sync_stack();
+                            task.call(stack);
+                            stats.completed(received_at);
+                        }
+                        MessageInner::ErrorColor(task) => {
+                            let stack = base_frame.sync_stack();
+                            task.call(stack);
+                            stats.completed(received_at);
+                        }
                     }
-                    MessageInner::Include(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
-                    }
-                    MessageInner::ErrorColor(task) => {
-                        let stack = base_frame.sync_stack();
-                        task.call(stack);
-                    }
-                },
+                }
                 Some(Err(_)) => break,
             }
         }
 
-        for i in 0..N {
+        'wait: for i in 0..N {
             loop {
                 if running_tasks.borrow()[i].is_some() {
+                    if shutdown.deadline_passed() {
+                        break 'wait;
+                    }
+
                     R::yield_now().await;
                     sleep(&Unrooted::new(), recv_timeout);
                     jl_process_events();
@@ -621,6 +937,11 @@ where
             }
         }
 
+        // The stacks used by abandoned tasks are freed and Julia is shut down after this
+        // function returns, so they must be dropped now.
+        let n_abandoned = abort_running(&running_tasks);
+        shutdown.add_abandoned(n_abandoned);
+
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         for worker in workers.into_iter() {
             loop {
@@ -637,7 +958,9 @@ where
         #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
         jl_exit_threaded_region();
 
+        crate::data::rooted::julia_exited();
         jl_atexit_hook(0);
+        shutdown.complete();
         Ok(())
     }
 }
@@ -645,6 +968,7 @@ where
 /// The message type used by the async runtime for communication.
 pub struct Message {
     inner: MessageInner,
+    queued_at: Instant,
 }
 
 pub(crate) enum MessageInner {
@@ -666,7 +990,10 @@ unsafe impl Sync for Message {}
 impl MessageInner {
     #[inline]
     pub(crate) fn wrap(self) -> Message {
-        Message { inner: self }
+        Message {
+            inner: self,
+            queued_at: Instant::now(),
+        }
     }
 }
 
@@ -677,6 +1004,34 @@ fn set_custom_fns(stack: &Stack) -> JlrsResult<()> {
         let cmd = CStr::from_bytes_with_nul_unchecked(b"const JlrsThreads = JlrsCore.Threads\0");
         Value::eval_cstring(&mut frame, cmd).expect("using JlrsCore threw an exception");
 
+        // Used to interrupt tasks that have been cancelled or timed out.
+        let cmd = CStr::from_bytes_with_nul_unchecked(
+            b"module JlrsCancellation
+            function interrupt(t::Task)
+                istaskdone(t) && return false
+                try
+                    schedule(t, InterruptException(); error=true)
+                    return true
+                catch
+                    return false
+                end
+            end
+            interruptafter(t::Task, secs::Float64) = Timer(_ -> interrupt(t), secs)
+            end\0",
+        );
+        Value::eval_cstring(&mut frame, cmd).expect("JlrsCancellation threw an exception");
+
+        // Used to collect GC statistics.
+        let cmd = CStr::from_bytes_with_nul_unchecked(
+            b"module JlrsStats
+            function gcstats()
+                num = Base.gc_num()
+                (Int(num.pause), Int(num.full_sweep), Int(num.total_time))
+            end
+            end\0",
+        );
+        Value::eval_cstring(&mut frame, cmd).expect("JlrsStats threw an exception");
+
         let wake_rust = Value::new(&mut frame, wake_task as *mut c_void);
         Module::main(&frame)
             .submodule(&frame, "JlrsThreads")?
@@ -719,14 +1074,37 @@ where
     P: PersistentTask,
 {
     sender: Arc<dyn ChannelSender<PersistentMessage<P>>>,
+    health: Arc<PersistentHealth>,
 }
 
 impl<P> PersistentHandle<P>
 where
     P: PersistentTask,
 {
-    pub(crate) fn new(sender: Arc<dyn ChannelSender<PersistentMessage<P>>>) -> Self {
-        PersistentHandle { sender }
+    pub(crate) fn new(
+        sender: Arc<dyn ChannelSender<PersistentMessage<P>>>,
+        health: Arc<PersistentHealth>,
+    ) -> Self {
+        PersistentHandle { sender, health }
+    }
+
+    /// Returns the liveness of the persistent task.
+    #[inline]
+    pub fn liveness(&self) -> Liveness {
+        self.health.liveness()
+    }
+
+    /// Returns the number of times the persistent task has been restarted.
+    #[inline]
+    pub fn restart_count(&self) -> usize {
+        self.health.restart_count()
+    }
+
+    /// Returns the error that caused the most recent failure of the persistent task, if it has
+    /// failed.
+    #[inline]
+    pub fn last_error(&self) -> Option<String> {
+        self.health.last_error()
     }
 
     /// Call the persistent task with the provided input.
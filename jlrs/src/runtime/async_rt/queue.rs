@@ -1,34 +1,446 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+//! The queues used to send tasks to the async runtime.
+//!
+//! Every queue is split into one or more priority classes, each class has its own capacity. When
+//! a runtime thread takes a new task from a queue it takes the task with the highest effective
+//! priority. The effective priority of a task is initially its priority class, if aging is
+//! enabled it increases by one for every aging interval the task has been waiting in the queue.
+//! This ensures tasks in the lowest class can't be starved by a continuous stream of
+//! high-priority tasks. A task whose deadline has passed is taken before all other tasks.
+//!
+//! Tasks with the same effective priority are taken in the order they were sent.
+//!
+//! A queue is closed when the last sender has been dropped or the runtime is shut down. Tasks
+//! that are sent to a closed queue are returned to the sender, tasks that are already in the
+//! queue can still be taken.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
-use deadqueue::resizable::Queue;
-use futures::Future;
+use futures::future::poll_fn;
 use futures_concurrency::future::Race;
 use jlrs_macros::julia_version;
+use parking_lot::Mutex;
 
+use super::stats::QueueStats;
 use crate::error::{JlrsResult, RuntimeError};
 
+/// How a task is scheduled, set with [`Dispatch::with_priority`] and
+/// [`Dispatch::with_deadline`].
+///
+/// [`Dispatch::with_priority`]: crate::runtime::async_rt::dispatch::Dispatch::with_priority
+/// [`Dispatch::with_deadline`]: crate::runtime::async_rt::dispatch::Dispatch::with_deadline
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Schedule {
+    pub(crate) priority: usize,
+    pub(crate) deadline: Option<Instant>,
+}
+
+// Returned by `try_push` if an item can't be pushed to a queue.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum PushError<T> {
+    // The class of the item is full.
+    Full(T),
+    // The queue has been closed.
+    Closed(T),
+}
+
+struct Entry<T> {
+    item: T,
+    seq: u64,
+    enqueued: Instant,
+    deadline: Option<Instant>,
+}
+
+struct Class<T> {
+    entries: VecDeque<Entry<T>>,
+    capacity: usize,
+    // The capacity the class was created with, used to scale the capacity when the queue is
+    // resized.
+    initial_capacity: usize,
+    n_deadlines: usize,
+}
+
+impl<T> Class<T> {
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.entries.len() >= self.capacity
+    }
+}
+
+struct State<T> {
+    classes: Box<[Class<T>]>,
+    next_seq: u64,
+    closed: bool,
+    push_wakers: Vec<Waker>,
+    pop_wakers: Vec<Waker>,
+}
+
+// The position of the next task and the key it has been selected with.
+#[derive(Clone, Copy)]
+struct Candidate {
+    class: usize,
+    index: usize,
+    expired: Option<Instant>,
+    effective: usize,
+    seq: u64,
+}
+
+impl Candidate {
+    // Returns true if `self` must be taken before `other`.
+    #[inline]
+    fn precedes(&self, other: &Candidate) -> bool {
+        match (self.expired, other.expired) {
+            (Some(a), Some(b)) => a < b || (a == b && self.seq < other.seq),
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => {
+                self.effective > other.effective
+                    || (self.effective == other.effective && self.seq < other.seq)
+            }
+        }
+    }
+}
+
+impl<T> State<T> {
+    fn push(&mut self, item: T, schedule: Schedule) -> Result<(), T> {
+        let class = schedule.priority.min(self.classes.len() - 1);
+        let class = &mut self.classes[class];
+        if class.is_full() {
+            return Err(item);
+        }
+
+        if schedule.deadline.is_some() {
+            class.n_deadlines += 1;
+        }
+
+        class.entries.push_back(Entry {
+            item,
+            seq: self.next_seq,
+            enqueued: Instant::now(),
+            deadline: schedule.deadline,
+        });
+        self.next_seq += 1;
+
+        Ok(())
+    }
+
+    fn pop(&mut self, aging_interval: Option<Duration>) -> Option<T> {
+        let now = Instant::now();
+        let top = self.classes.len() - 1;
+        let mut best: Option<Candidate> = None;
+
+        let mut consider = |candidate: Candidate| match best {
+            Some(ref b) if !candidate.precedes(b) => (),
+            _ => best = Some(candidate),
+        };
+
+        for (class_idx, class) in self.classes.iter().enumerate() {
+            // Within a class the task at the front has been waiting longest, so it has the
+            // highest effective priority unless the deadline of another task has passed.
+            let n_candidates = if class.n_deadlines > 0 {
+                class.entries.len()
+            } else {
+                class.entries.len().min(1)
+            };
+
+            for (index, entry) in class.entries.iter().take(n_candidates).enumerate() {
+                let expired = entry.deadline.filter(|d| *d <= now);
+                if index != 0 && expired.is_none() {
+                    continue;
+                }
+
+                let effective = match aging_interval {
+                    Some(interval) if !interval.is_zero() => {
+                        let waited = now.duration_since(entry.enqueued).as_nanos();
+                        let steps = waited / interval.as_nanos();
+                        (class_idx as u128 + steps).min(top as u128) as usize
+                    }
+                    Some(_) => top,
+                    None => class_idx,
+                };
+
+                consider(Candidate {
+                    class: class_idx,
+                    index,
+                    expired,
+                    effective,
+                    seq: entry.seq,
+                });
+            }
+        }
+
+        let best = best?;
+        let class = &mut self.classes[best.class];
+        let entry = class.entries.remove(best.index)?;
+        if entry.deadline.is_some() {
+            class.n_deadlines -= 1;
+        }
+
+        Some(entry.item)
+    }
+
+    #[inline]
+    fn fits(&self) -> bool {
+        self.classes.iter().all(|c| c.entries.len() <= c.capacity)
+    }
+}
+
+#[inline]
+fn register(wakers: &mut Vec<Waker>, waker: &Waker) {
+    if !wakers.iter().any(|w| w.will_wake(waker)) {
+        wakers.push(waker.clone())
+    }
+}
+
+#[inline]
+fn wake_all(wakers: Vec<Waker>) {
+    for waker in wakers {
+        waker.wake()
+    }
+}
+
+// A bounded queue with priority classes.
+struct Queue<T> {
+    state: Mutex<State<T>>,
+    aging_interval: Option<Duration>,
+}
+
+impl<T> Queue<T> {
+    fn new(capacities: &[usize], aging_interval: Option<Duration>) -> Self {
+        let classes = capacities
+            .iter()
+            .map(|&capacity| Class {
+                entries: VecDeque::new(),
+                capacity,
+                initial_capacity: capacity,
+                n_deadlines: 0,
+            })
+            .collect();
+
+        Queue {
+            state: Mutex::new(State {
+                classes,
+                next_seq: 0,
+                closed: false,
+                push_wakers: Vec::new(),
+                pop_wakers: Vec::new(),
+            }),
+            aging_interval,
+        }
+    }
+
+    fn poll_push(
+        &self,
+        item: &mut Option<T>,
+        schedule: Schedule,
+        cx: &mut Context,
+    ) -> Poll<Result<(), T>> {
+        let mut state = self.state.lock();
+        if state.closed {
+            drop(state);
+            return Poll::Ready(Err(item.take().unwrap()));
+        }
+
+        match state.push(item.take().unwrap(), schedule) {
+            Ok(()) => {
+                let wakers = std::mem::take(&mut state.pop_wakers);
+                drop(state);
+                wake_all(wakers);
+                Poll::Ready(Ok(()))
+            }
+            Err(it) => {
+                *item = Some(it);
+                register(&mut state.push_wakers, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+
+    // Returns the item if the queue has been closed.
+    async fn push(&self, item: T, schedule: Schedule) -> Result<(), T> {
+        let mut item = Some(item);
+        poll_fn(|cx| self.poll_push(&mut item, schedule, cx)).await
+    }
+
+    fn try_push(&self, item: T, schedule: Schedule) -> Result<(), PushError<T>> {
+        let mut state = self.state.lock();
+        if state.closed {
+            drop(state);
+            return Err(PushError::Closed(item));
+        }
+
+        state.push(item, schedule).map_err(PushError::Full)?;
+        let wakers = std::mem::take(&mut state.pop_wakers);
+        drop(state);
+        wake_all(wakers);
+        Ok(())
+    }
+
+    fn poll_pop(&self, cx: &mut Context) -> Poll<Option<T>> {
+        let mut state = self.state.lock();
+        match state.pop(self.aging_interval) {
+            Some(item) => {
+                let wakers = std::mem::take(&mut state.push_wakers);
+                drop(state);
+                wake_all(wakers);
+                Poll::Ready(Some(item))
+            }
+            None if state.closed => Poll::Ready(None),
+            None => {
+                register(&mut state.pop_wakers, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+
+    // Cancel-safe: a task is only removed from the queue when the returned future resolves.
+    // Resolves to `None` if the queue is empty and has been closed.
+    async fn pop(&self) -> Option<T> {
+        poll_fn(|cx| self.poll_pop(cx)).await
+    }
+
+    fn try_pop(&self) -> Option<T> {
+        let mut state = self.state.lock();
+        let item = state.pop(self.aging_interval)?;
+        let wakers = std::mem::take(&mut state.push_wakers);
+        drop(state);
+        wake_all(wakers);
+        Some(item)
+    }
+
+    fn close(&self) {
+        let mut state = self.state.lock();
+        state.closed = true;
+        let push_wakers = std::mem::take(&mut state.push_wakers);
+        let pop_wakers = std::mem::take(&mut state.pop_wakers);
+        drop(state);
+        wake_all(push_wakers);
+        wake_all(pop_wakers);
+    }
+
+    #[inline]
+    fn is_closed(&self) -> bool {
+        self.state.lock().closed
+    }
+
+    // Remove all tasks from the queue, returns the number of removed tasks.
+    fn clear(&self) -> usize {
+        let mut state = self.state.lock();
+        let mut removed = Vec::new();
+        for class in state.classes.iter_mut() {
+            class.n_deadlines = 0;
+            removed.extend(class.entries.drain(..));
+        }
+        let wakers = std::mem::take(&mut state.push_wakers);
+        drop(state);
+        wake_all(wakers);
+        removed.len()
+    }
+
+    fn stats(&self) -> QueueStats {
+        let state = self.state.lock();
+        let lengths = state.classes.iter().map(|c| c.entries.len()).collect();
+        let capacities = state.classes.iter().map(|c| c.capacity).collect();
+        QueueStats::new(lengths, capacities)
+    }
+
+    // The largest class is resized to `capacity`, the other classes are scaled proportionally to
+    // the capacities they were created with.
+    async fn resize(&self, capacity: usize) {
+        let wakers = {
+            let mut state = self.state.lock();
+            let largest = state
+                .classes
+                .iter()
+                .map(|c| c.initial_capacity)
+                .max()
+                .unwrap_or(0);
+
+            for class in state.classes.iter_mut() {
+                class.capacity = scaled_capacity(class.initial_capacity, largest, capacity);
+            }
+            std::mem::take(&mut state.push_wakers)
+        };
+        wake_all(wakers);
+
+        poll_fn(|cx| {
+            let mut state = self.state.lock();
+            if state.fits() {
+                Poll::Ready(())
+            } else {
+                register(&mut state.push_wakers, cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+// Scales `initial` by `capacity / largest`, rounding up so no class with a non-zero capacity
+// ends up with a capacity of zero.
+#[inline]
+fn scaled_capacity(initial: usize, largest: usize, capacity: usize) -> usize {
+    if largest == 0 {
+        return capacity;
+    }
+
+    let scaled = (initial as u128 * capacity as u128 + largest as u128 - 1) / largest as u128;
+    scaled as usize
+}
+
 struct Queues<T> {
     main_queue: Queue<T>,
     any_queue: Option<Queue<T>>,
     worker_queue: Option<Queue<T>>,
-    // there's no method that closes the queue, so the number of senders must be tracked.
+    // The queues are closed when the last sender has been dropped.
     n_senders: AtomicUsize,
 }
 
+impl<T> Queues<T> {
+    fn queues(&self) -> impl Iterator<Item = &Queue<T>> {
+        std::iter::once(&self.main_queue)
+            .chain(self.any_queue.as_ref())
+            .chain(self.worker_queue.as_ref())
+    }
+
+    fn close(&self) {
+        for queue in self.queues() {
+            queue.close()
+        }
+    }
+
+    fn clear(&self) -> usize {
+        self.queues().map(|q| q.clear()).sum()
+    }
+
+    #[inline]
+    fn is_closed(&self) -> bool {
+        self.main_queue.is_closed()
+    }
+}
+
 impl<T> Queues<T> {
     #[inline]
-    fn new(capacity: usize, has_workers: bool) -> Arc<Self> {
+    fn new(capacities: &[usize], aging_interval: Option<Duration>, has_workers: bool) -> Arc<Self> {
         let (worker_queue, any_queue) = if has_workers {
-            (Some(Queue::new(capacity)), Some(Queue::new(capacity)))
+            (
+                Some(Queue::new(capacities, aging_interval)),
+                Some(Queue::new(capacities, aging_interval)),
+            )
         } else {
             (None, None)
         };
 
         Arc::new(Queues {
-            main_queue: Queue::new(capacity),
+            main_queue: Queue::new(capacities, aging_interval),
             any_queue,
             worker_queue,
             n_senders: AtomicUsize::new(1),
@@ -45,7 +457,9 @@ unsafe impl<T: Sync> Sync for Sender<T> {}
 
 impl<T> Drop for Sender<T> {
     fn drop(&mut self) {
-        self.queues.n_senders.fetch_sub(1, Ordering::AcqRel);
+        if self.queues.n_senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queues.close();
+        }
     }
 }
 
@@ -59,22 +473,21 @@ impl<T> Clone for Sender<T> {
 
 impl<T: Send> Sender<T> {
     #[inline]
-    pub(crate) async fn send(&self, item: T) {
+    pub(crate) async fn send(&self, item: T, schedule: Schedule) -> Result<(), T> {
         if let Some(ref q) = self.queues.any_queue {
-            q.push(item).await
+            q.push(item, schedule).await
         } else {
-            self.send_main(item).await
+            self.send_main(item, schedule).await
         }
     }
 
     #[inline]
-    pub(crate) fn try_send(&self, item: T) -> Option<T> {
+    pub(crate) fn try_send(&self, item: T, schedule: Schedule) -> Result<(), PushError<T>> {
         if let Some(ref q) = self.queues.any_queue {
-            q.try_push(item).err()?;
+            q.try_push(item, schedule)
         } else {
-            self.try_send_main(item)?;
+            self.try_send_main(item, schedule)
         }
-        None
     }
 
     #[inline]
@@ -86,14 +499,13 @@ impl<T: Send> Sender<T> {
     }
 
     #[inline]
-    pub(crate) async fn send_main(&self, item: T) {
-        self.queues.main_queue.push(item).await
+    pub(crate) async fn send_main(&self, item: T, schedule: Schedule) -> Result<(), T> {
+        self.queues.main_queue.push(item, schedule).await
     }
 
     #[inline]
-    pub(crate) fn try_send_main(&self, item: T) -> Option<T> {
-        self.queues.main_queue.try_push(item).err()?;
-        None
+    pub(crate) fn try_send_main(&self, item: T, schedule: Schedule) -> Result<(), PushError<T>> {
+        self.queues.main_queue.try_push(item, schedule)
     }
 
     #[inline]
@@ -105,22 +517,21 @@ impl<T: Send> Sender<T> {
     }
 
     #[inline]
-    pub(crate) async fn send_worker(&self, item: T) {
+    pub(crate) async fn send_worker(&self, item: T, schedule: Schedule) -> Result<(), T> {
         if let Some(ref q) = self.queues.worker_queue {
-            q.push(item).await
+            q.push(item, schedule).await
         } else {
-            self.send_main(item).await
+            self.send_main(item, schedule).await
         }
     }
 
     #[inline]
-    pub(crate) fn try_send_worker(&self, item: T) -> Option<T> {
+    pub(crate) fn try_send_worker(&self, item: T, schedule: Schedule) -> Result<(), PushError<T>> {
         if let Some(ref q) = self.queues.worker_queue {
-            q.try_push(item).err()?;
+            q.try_push(item, schedule)
         } else {
-            self.try_send_main(item)?;
+            self.try_send_main(item, schedule)
         }
-        None
     }
 
     #[inline]
@@ -135,6 +546,29 @@ impl<T: Send> Sender<T> {
     }
 }
 
+impl<T> Sender<T> {
+    // Close all queues, tasks that are sent afterwards are returned to the sender.
+    #[inline]
+    pub(crate) fn close(&self) {
+        self.queues.close()
+    }
+
+    // Remove all tasks from all queues, returns the number of removed tasks.
+    #[inline]
+    pub(crate) fn clear(&self) -> usize {
+        self.queues.clear()
+    }
+
+    // The statistics of the main, any, and worker queue.
+    pub(crate) fn stats(&self) -> (QueueStats, Option<QueueStats>, Option<QueueStats>) {
+        (
+            self.queues.main_queue.stats(),
+            self.queues.any_queue.as_ref().map(|q| q.stats()),
+            self.queues.worker_queue.as_ref().map(|q| q.stats()),
+        )
+    }
+}
+
 pub(crate) struct Receiver<T> {
     queue: Arc<Queues<T>>,
 }
@@ -150,24 +584,29 @@ impl<T> Clone for Receiver<T> {
     }
 }
 
+impl<T> Receiver<T> {
+    // Remove all tasks from all queues, returns the number of removed tasks.
+    #[inline]
+    pub(crate) fn clear(&self) -> usize {
+        self.queue.clear()
+    }
+}
+
 impl<T: Send> Receiver<T> {
     pub(crate) async fn recv_main(&self) -> JlrsResult<T> {
-        if self.queue.n_senders.load(Ordering::Acquire) == 0 {
-            return match self.try_recv_main() {
-                Some(t) => Ok(t),
-                None => Err(RuntimeError::ChannelClosed)?,
-            };
-        }
-
-        if self.queue.any_queue.is_some() {
-            Ok((
-                self.queue.main_queue.pop(),
-                self.queue.any_queue.as_ref().unwrap().pop(),
-            )
-                .race()
-                .await)
+        let popped = if self.queue.is_closed() {
+            None
+        } else if let Some(ref any_queue) = self.queue.any_queue {
+            (self.queue.main_queue.pop(), any_queue.pop()).race().await
         } else {
-            Ok(self.queue.main_queue.pop().await)
+            self.queue.main_queue.pop().await
+        };
+
+        // A closed queue resolves immediately if it's empty, the other queue might still contain
+        // tasks.
+        match popped.or_else(|| self.try_recv_main()) {
+            Some(t) => Ok(t),
+            None => Err(RuntimeError::ChannelClosed)?,
         }
     }
 
@@ -176,24 +615,26 @@ impl<T: Send> Receiver<T> {
             return Some(popped_main);
         }
 
-        self.queue.any_queue.as_ref().map(|q| q.try_pop()).flatten()
+        self.queue.any_queue.as_ref().and_then(|q| q.try_pop())
     }
 
     #[julia_version(since = "1.9")]
     pub(crate) async fn recv_worker(&self) -> JlrsResult<T> {
-        if self.queue.n_senders.load(Ordering::Acquire) == 0 {
-            return match self.try_recv_worker() {
-                Some(t) => Ok(t),
-                None => Err(RuntimeError::ChannelClosed)?,
-            };
-        }
+        let popped = if self.queue.is_closed() {
+            None
+        } else {
+            (
+                self.queue.worker_queue.as_ref().unwrap().pop(),
+                self.queue.any_queue.as_ref().unwrap().pop(),
+            )
+                .race()
+                .await
+        };
 
-        Ok((
-            self.queue.worker_queue.as_ref().unwrap().pop(),
-            self.queue.any_queue.as_ref().unwrap().pop(),
-        )
-            .race()
-            .await)
+        match popped.or_else(|| self.try_recv_worker()) {
+            Some(t) => Ok(t),
+            None => Err(RuntimeError::ChannelClosed)?,
+        }
     }
 
     #[julia_version(since = "1.9")]
@@ -206,9 +647,23 @@ impl<T: Send> Receiver<T> {
     }
 }
 
-pub(crate) fn channel<T>(capacity: usize, has_workers: bool) -> (Sender<T>, Receiver<T>) {
-    let capacity = if capacity == 0 { 32 } else { capacity };
-    let queue = Queues::new(capacity, has_workers);
+// Every element of `capacities` is the capacity of a priority class, the first element is the
+// lowest class. If it's empty a single class with the default capacity is used.
+pub(crate) fn channel<T>(
+    capacities: &[usize],
+    aging_interval: Option<Duration>,
+    has_workers: bool,
+) -> (Sender<T>, Receiver<T>) {
+    let capacities = if capacities.is_empty() {
+        vec![32]
+    } else {
+        capacities
+            .iter()
+            .map(|&c| if c == 0 { 32 } else { c })
+            .collect()
+    };
+
+    let queue = Queues::new(&capacities, aging_interval, has_workers);
     let sender = Sender {
         queues: queue.clone(),
     };
@@ -217,3 +672,107 @@ pub(crate) fn channel<T>(capacity: usize, has_workers: bool) -> (Sender<T>, Rece
 
     (sender, receiver)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{PushError, Queue, Schedule};
+
+    fn schedule(priority: usize) -> Schedule {
+        Schedule {
+            priority,
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn fifo_single_class() {
+        let queue = Queue::new(&[4], None);
+        for i in 0..4 {
+            assert!(queue.try_push(i, schedule(0)).is_ok());
+        }
+        assert_eq!(queue.try_push(4, schedule(0)), Err(PushError::Full(4)));
+
+        for i in 0..4 {
+            assert_eq!(queue.try_pop(), Some(i));
+        }
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn higher_class_first() {
+        let queue = Queue::new(&[4, 4], None);
+        queue.try_push(1, schedule(0)).unwrap();
+        queue.try_push(2, schedule(1)).unwrap();
+        queue.try_push(3, schedule(5)).unwrap();
+
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), Some(1));
+    }
+
+    #[test]
+    fn capacity_per_class() {
+        let queue = Queue::new(&[1, 2], None);
+        queue.try_push(1, schedule(0)).unwrap();
+        assert_eq!(queue.try_push(2, schedule(0)), Err(PushError::Full(2)));
+        queue.try_push(3, schedule(1)).unwrap();
+        queue.try_push(4, schedule(1)).unwrap();
+        assert_eq!(queue.try_push(5, schedule(1)), Err(PushError::Full(5)));
+    }
+
+    #[test]
+    fn resize_scales_class_capacities() {
+        let queue = Queue::<usize>::new(&[2, 8, 4], None);
+        futures::executor::block_on(queue.resize(4));
+        assert_eq!(queue.stats().class_capacities(), &[1, 4, 2]);
+
+        futures::executor::block_on(queue.resize(16));
+        assert_eq!(queue.stats().class_capacities(), &[4, 16, 8]);
+
+        futures::executor::block_on(queue.resize(3));
+        assert_eq!(queue.stats().class_capacities(), &[1, 3, 2]);
+    }
+
+    #[test]
+    fn aging_promotes_waiting_tasks() {
+        let queue = Queue::new(&[4, 4], Some(Duration::from_millis(10)));
+        queue.try_push(1, schedule(0)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        queue.try_push(2, schedule(1)).unwrap();
+
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+    }
+
+    #[test]
+    fn expired_deadline_first() {
+        let queue = Queue::new(&[4, 4], None);
+        queue.try_push(1, schedule(1)).unwrap();
+        queue.try_push(2, schedule(0)).unwrap();
+        let expired = Schedule {
+            priority: 0,
+            deadline: Some(Instant::now()),
+        };
+        queue.try_push(3, expired).unwrap();
+
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+    }
+
+    #[test]
+    fn closed_queue_returns_item() {
+        let queue = Queue::new(&[4], None);
+        queue.try_push(1, schedule(0)).unwrap();
+        queue.close();
+
+        assert_eq!(queue.try_push(2, schedule(0)), Err(PushError::Closed(2)));
+        let pushed = futures::executor::block_on(queue.push(3, schedule(0)));
+        assert_eq!(pushed, Err(3));
+
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), None);
+    }
+}
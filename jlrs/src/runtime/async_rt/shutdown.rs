@@ -0,0 +1,306 @@
+//! Shut down the async runtime.
+//!
+//! By default the async runtime shuts down when the last [`AsyncJulia`] handle has been dropped,
+//! after all tasks that have been sent to it have completed. [`AsyncJulia::shutdown`] can be
+//! used to shut it down explicitly:
+//!
+//! - The task queues are closed, new tasks are rejected.
+//! - Depending on the [`ShutdownMode`], tasks that are still waiting in the queues are either
+//!   handled or dropped.
+//! - Persistent tasks stop accepting new calls and run their `exit` method.
+//! - The runtime waits until all running tasks have completed. If a deadline has been set, tasks
+//!   that are still running when it has passed are abandoned. Abandoned tasks are dropped
+//!   before Julia is shut down.
+//! - Julia's `atexit` hooks are run.
+//!
+//! When the runtime has shut down, a [`ShutdownSummary`] is returned.
+//!
+//! [`AsyncJulia`]: crate::runtime::async_rt::AsyncJulia
+//! [`AsyncJulia::shutdown`]: crate::runtime::async_rt::AsyncJulia::shutdown
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+use futures::{channel::oneshot, future::poll_fn};
+
+use super::AsyncRuntime;
+use crate::error::{JlrsResult, RuntimeError};
+
+/// How the async runtime handles tasks that are waiting in its queues when it's shut down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownMode {
+    /// Handle all tasks that are waiting in the queues before shutting down.
+    ///
+    /// If the deadline passes before the queues have been drained, the remaining tasks are
+    /// dropped.
+    Drain {
+        /// Tasks that haven't completed when this deadline has passed are abandoned.
+        deadline: Option<Instant>,
+    },
+    /// Drop all tasks that are waiting in the queues.
+    Discard {
+        /// Tasks that haven't completed when this deadline has passed are abandoned.
+        deadline: Option<Instant>,
+    },
+}
+
+impl ShutdownMode {
+    #[inline]
+    fn deadline(self) -> Option<Instant> {
+        match self {
+            ShutdownMode::Drain { deadline } => deadline,
+            ShutdownMode::Discard { deadline } => deadline,
+        }
+    }
+}
+
+/// A summary of how the async runtime has been shut down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    drained: usize,
+    discarded: usize,
+    abandoned: usize,
+}
+
+impl ShutdownSummary {
+    /// The number of tasks that were waiting in the queues and have been handled after the
+    /// shutdown was requested.
+    #[inline]
+    pub fn drained(&self) -> usize {
+        self.drained
+    }
+
+    /// The number of tasks that were waiting in the queues and have been dropped without being
+    /// handled.
+    #[inline]
+    pub fn discarded(&self) -> usize {
+        self.discarded
+    }
+
+    /// The number of tasks that were still running when the deadline passed.
+    #[inline]
+    pub fn abandoned(&self) -> usize {
+        self.abandoned
+    }
+}
+
+// The shutdown state of the runtime, shared by the handle and the runtime threads.
+pub(crate) struct ShutdownState {
+    requested: AtomicBool,
+    deadline: Mutex<Option<Instant>>,
+    wakers: Mutex<Vec<Waker>>,
+    summary_sender: Mutex<Option<oneshot::Sender<ShutdownSummary>>>,
+    drained: AtomicUsize,
+    discarded: AtomicUsize,
+    abandoned: AtomicUsize,
+}
+
+impl ShutdownState {
+    pub(crate) fn new() -> Self {
+        ShutdownState {
+            requested: AtomicBool::new(false),
+            deadline: Mutex::new(None),
+            wakers: Mutex::new(Vec::new()),
+            summary_sender: Mutex::new(None),
+            drained: AtomicUsize::new(0),
+            discarded: AtomicUsize::new(0),
+            abandoned: AtomicUsize::new(0),
+        }
+    }
+
+    // Request a shutdown, the summary is sent to the returned receiver when the runtime has shut
+    // down. Returns an error if a shutdown has already been requested.
+    pub(crate) fn request(
+        &self,
+        mode: ShutdownMode,
+    ) -> JlrsResult<oneshot::Receiver<ShutdownSummary>> {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut summary_sender = self.summary_sender.lock().unwrap();
+            if self.requested.load(Ordering::Acquire) {
+                Err(RuntimeError::ShuttingDown)?
+            }
+
+            *summary_sender = Some(sender);
+            *self.deadline.lock().unwrap() = mode.deadline();
+            self.requested.store(true, Ordering::Release);
+        }
+
+        let wakers = std::mem::take(&mut *self.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake()
+        }
+
+        Ok(receiver)
+    }
+
+    #[inline]
+    pub(crate) fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::Acquire)
+    }
+
+    // Returns `true` if a shutdown has been requested and its deadline has passed.
+    #[inline]
+    pub(crate) fn deadline_passed(&self) -> bool {
+        self.is_requested()
+            && self
+                .deadline
+                .lock()
+                .unwrap()
+                .map_or(false, |d| d <= Instant::now())
+    }
+
+    #[inline]
+    pub(crate) fn add_drained(&self) {
+        if self.is_requested() {
+            self.drained.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn add_discarded(&self, n: usize) {
+        self.discarded.fetch_add(n, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn add_abandoned(&self, n: usize) {
+        self.abandoned.fetch_add(n, Ordering::Relaxed);
+    }
+
+    // Returns a future that resolves when a shutdown has been requested.
+    #[inline]
+    pub(crate) fn requested(&self) -> Requested<'_> {
+        Requested { state: self }
+    }
+
+    // Called by the main runtime thread after it has shut down.
+    pub(crate) fn complete(&self) {
+        let summary = ShutdownSummary {
+            drained: self.drained.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+            abandoned: self.abandoned.load(Ordering::Relaxed),
+        };
+
+        if let Some(sender) = self.summary_sender.lock().unwrap().take() {
+            let _ = sender.send(summary);
+        }
+    }
+}
+
+// Resolves when a shutdown has been requested.
+pub(crate) struct Requested<'a> {
+    state: &'a ShutdownState,
+}
+
+impl Future for Requested<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.is_requested() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.state.wakers.lock().unwrap();
+        // The shutdown might have been requested before the lock was acquired.
+        if self.state.is_requested() {
+            return Poll::Ready(());
+        }
+
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+thread_local! {
+    // The shutdown state of the runtime this thread belongs to.
+    static CURRENT: RefCell<Option<Arc<ShutdownState>>> = const { RefCell::new(None) };
+}
+
+// Called by every runtime thread before it starts handling tasks.
+#[inline]
+pub(crate) fn set_current_shutdown(state: Arc<ShutdownState>) {
+    CURRENT.with(|current| *current.borrow_mut() = Some(state));
+}
+
+// Returns the shutdown state of the runtime this thread belongs to.
+#[inline]
+pub(crate) fn current_shutdown() -> Option<Arc<ShutdownState>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+type TaskFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+// A task that has been spawned on a runtime thread. The future of the task is shared with the
+// runtime thread so an abandoned task can be dropped before the stacks it uses are freed and
+// Julia has been shut down.
+pub(crate) struct RunningTask<R: AsyncRuntime> {
+    future: Rc<RefCell<Option<TaskFuture>>>,
+    _handle: R::JoinHandle,
+}
+
+impl<R: AsyncRuntime> RunningTask<R> {
+    pub(crate) fn spawn<F>(future: F) -> Self
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let future: Rc<RefCell<Option<TaskFuture>>> = Rc::new(RefCell::new(Some(Box::pin(future))));
+
+        let shared = future.clone();
+        let handle = R::spawn_local(poll_fn(move |cx| {
+            let mut future = shared.borrow_mut();
+            match future.as_mut() {
+                Some(fut) => {
+                    let res = fut.as_mut().poll(cx);
+                    if res.is_ready() {
+                        *future = None;
+                    }
+                    res
+                }
+                None => Poll::Ready(()),
+            }
+        }));
+
+        RunningTask {
+            future,
+            _handle: handle,
+        }
+    }
+
+    // Drop the future of the task, the spawned task completes immediately if it's polled again.
+    pub(crate) fn abort(self) {
+        let future = self.future.borrow_mut().take();
+        std::mem::drop(future);
+    }
+}
+
+// Drop all tasks that are still running, returns the number of dropped tasks. Must be called
+// before the stacks of the runtime thread are freed.
+pub(crate) fn abort_running<R: AsyncRuntime>(
+    running_tasks: &RefCell<Box<[Option<RunningTask<R>>]>>,
+) -> usize {
+    let abandoned = running_tasks
+        .borrow_mut()
+        .iter_mut()
+        .filter_map(Option::take)
+        .collect::<Vec<_>>();
+
+    let n_abandoned = abandoned.len();
+    for task in abandoned {
+        task.abort();
+    }
+
+    n_abandoned
+}
@@ -0,0 +1,504 @@
+//! Runtime statistics.
+//!
+//! The async runtime keeps track of what it's doing: how many tasks are waiting in its queues,
+//! how many tasks are running on each thread, how many persistent tasks are active, how much
+//! time has been spent collecting garbage, and how long tasks wait in the queue and run. A
+//! snapshot of these statistics can be taken with [`AsyncJulia::stats`].
+//!
+//! If the `metrics` feature is enabled the snapshot also contains histograms of the queued and
+//! run durations of tasks, and the number of tasks that each thread has handled.
+//!
+//! The GC statistics are updated by the main runtime thread periodically, they can lag behind
+//! by up to 100 milliseconds or until the thread is no longer blocked by a blocking task.
+//!
+//! [`AsyncJulia::stats`]: crate::runtime::async_rt::AsyncJulia::stats
+
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    call::Call,
+    data::{
+        layout::tuple::Tuple3,
+        managed::{module::Module, value::Value},
+    },
+    memory::target::unrooted::Unrooted,
+};
+
+// The minimum time between two updates of the GC statistics.
+const GC_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A snapshot of the statistics of the async runtime.
+#[derive(Clone, Debug)]
+pub struct RuntimeStats {
+    main_queue: QueueStats,
+    any_queue: Option<QueueStats>,
+    worker_queue: Option<QueueStats>,
+    in_flight: Vec<usize>,
+    active_persistent: usize,
+    gc: GcStats,
+    tasks: TaskStats,
+    #[cfg(feature = "metrics")]
+    metrics: RuntimeMetrics,
+}
+
+impl RuntimeStats {
+    /// The statistics of the queue of the main runtime thread.
+    #[inline]
+    pub fn main_queue(&self) -> &QueueStats {
+        &self.main_queue
+    }
+
+    /// The statistics of the queue shared by all runtime threads, if worker threads are used.
+    #[inline]
+    pub fn any_queue(&self) -> Option<&QueueStats> {
+        self.any_queue.as_ref()
+    }
+
+    /// The statistics of the queue of the worker threads, if worker threads are used.
+    #[inline]
+    pub fn worker_queue(&self) -> Option<&QueueStats> {
+        self.worker_queue.as_ref()
+    }
+
+    /// The number of tasks that are currently running on each runtime thread.
+    ///
+    /// The first element is the main runtime thread, the others are the worker threads. Active
+    /// persistent tasks are included.
+    #[inline]
+    pub fn in_flight_tasks(&self) -> &[usize] {
+        &self.in_flight
+    }
+
+    /// The number of persistent tasks that are currently active.
+    #[inline]
+    pub fn active_persistent_tasks(&self) -> usize {
+        self.active_persistent
+    }
+
+    /// Statistics of the garbage collector.
+    #[inline]
+    pub fn gc(&self) -> &GcStats {
+        &self.gc
+    }
+
+    /// Statistics of the tasks that have completed.
+    #[inline]
+    pub fn tasks(&self) -> &TaskStats {
+        &self.tasks
+    }
+
+    /// Histograms and counters.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub fn metrics(&self) -> &RuntimeMetrics {
+        &self.metrics
+    }
+}
+
+/// Statistics of a task queue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueueStats {
+    lengths: Vec<usize>,
+    capacities: Vec<usize>,
+}
+
+impl QueueStats {
+    #[inline]
+    pub(crate) fn new(lengths: Vec<usize>, capacities: Vec<usize>) -> Self {
+        QueueStats {
+            lengths,
+            capacities,
+        }
+    }
+
+    /// The total number of tasks waiting in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+
+    /// Returns `true` if no tasks are waiting in the queue.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of tasks waiting in each priority class, starting with the lowest class.
+    #[inline]
+    pub fn class_lengths(&self) -> &[usize] {
+        &self.lengths
+    }
+
+    /// The capacity of each priority class, starting with the lowest class.
+    #[inline]
+    pub fn class_capacities(&self) -> &[usize] {
+        &self.capacities
+    }
+}
+
+/// Statistics of the garbage collector, derived from `Base.gc_num()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pauses: u64,
+    full_sweeps: u64,
+    total_pause_time: Duration,
+}
+
+impl GcStats {
+    /// The number of times the GC has paused all threads to collect garbage.
+    #[inline]
+    pub fn pauses(&self) -> u64 {
+        self.pauses
+    }
+
+    /// The number of full collections.
+    #[inline]
+    pub fn full_sweeps(&self) -> u64 {
+        self.full_sweeps
+    }
+
+    /// The total time spent collecting garbage.
+    #[inline]
+    pub fn total_pause_time(&self) -> Duration {
+        self.total_pause_time
+    }
+}
+
+/// Statistics of the tasks that have been handled by the async runtime.
+///
+/// The queued duration of a task is the time between dispatching it and a runtime thread
+/// receiving it, the run duration is the time between a runtime thread receiving it and its
+/// completion. The run duration of persistent tasks isn't tracked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    received: u64,
+    completed: u64,
+    total_queued: Duration,
+    max_queued: Duration,
+    total_run: Duration,
+    max_run: Duration,
+}
+
+impl TaskStats {
+    /// The number of tasks that have been received by a runtime thread.
+    #[inline]
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    /// The number of tasks that have completed, persistent tasks excluded.
+    #[inline]
+    pub fn completed(&self) -> u64 {
+        self.completed
+    }
+
+    /// The total time tasks have been waiting in the queue.
+    #[inline]
+    pub fn total_queued(&self) -> Duration {
+        self.total_queued
+    }
+
+    /// The longest time a task has been waiting in the queue.
+    #[inline]
+    pub fn max_queued(&self) -> Duration {
+        self.max_queued
+    }
+
+    /// The average time a task has been waiting in the queue.
+    #[inline]
+    pub fn mean_queued(&self) -> Option<Duration> {
+        mean(self.total_queued, self.received)
+    }
+
+    /// The total time tasks have been running.
+    #[inline]
+    pub fn total_run(&self) -> Duration {
+        self.total_run
+    }
+
+    /// The longest time a task has been running.
+    #[inline]
+    pub fn max_run(&self) -> Duration {
+        self.max_run
+    }
+
+    /// The average time a task has been running.
+    #[inline]
+    pub fn mean_run(&self) -> Option<Duration> {
+        mean(self.total_run, self.completed)
+    }
+}
+
+#[inline]
+fn mean(total: Duration, n: u64) -> Option<Duration> {
+    if n == 0 {
+        None
+    } else {
+        Some(Duration::from_nanos((total.as_nanos() / n as u128) as u64))
+    }
+}
+
+/// Histograms of the queued and run durations of tasks, and the number of tasks each runtime
+/// thread has handled.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeMetrics {
+    queued: Histogram,
+    run: Histogram,
+    handled: Vec<u64>,
+}
+
+#[cfg(feature = "metrics")]
+impl RuntimeMetrics {
+    /// Histogram of the time tasks have been waiting in the queue.
+    #[inline]
+    pub fn queued(&self) -> &Histogram {
+        &self.queued
+    }
+
+    /// Histogram of the time tasks have been running.
+    #[inline]
+    pub fn run(&self) -> &Histogram {
+        &self.run
+    }
+
+    /// The number of tasks each runtime thread has received.
+    ///
+    /// The first element is the main runtime thread, the others are the worker threads.
+    #[inline]
+    pub fn handled(&self) -> &[u64] {
+        &self.handled
+    }
+}
+
+/// A histogram of durations with exponentially growing buckets.
+///
+/// The first bucket counts durations shorter than 2 microseconds, bucket `i` counts durations
+/// of at least `2^i` and less than `2^(i + 1)` microseconds. The last bucket also counts all
+/// longer durations.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; Histogram::N_BUCKETS],
+}
+
+#[cfg(feature = "metrics")]
+impl Histogram {
+    /// The number of buckets.
+    pub const N_BUCKETS: usize = 32;
+
+    /// The number of durations in each bucket.
+    #[inline]
+    pub fn buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The total number of durations.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// The lower bound of bucket `i`.
+    #[inline]
+    pub fn lower_bound(i: usize) -> Duration {
+        if i == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_micros(1 << i)
+        }
+    }
+
+    #[inline]
+    fn record(&mut self, duration: Duration) {
+        let micros = duration.as_micros();
+        let bucket = if micros < 2 {
+            0
+        } else {
+            (127 - micros.leading_zeros() as usize).min(Self::N_BUCKETS - 1)
+        };
+
+        self.buckets[bucket] += 1;
+    }
+}
+
+#[derive(Default)]
+struct TaskTotals {
+    stats: TaskStats,
+    #[cfg(feature = "metrics")]
+    queued: Histogram,
+    #[cfg(feature = "metrics")]
+    run: Histogram,
+}
+
+struct ThreadCounters {
+    in_flight: AtomicUsize,
+    #[cfg(feature = "metrics")]
+    handled: AtomicU64,
+}
+
+// Collects the statistics of the runtime, shared by the handle and the runtime threads.
+pub(crate) struct StatsCollector {
+    threads: Box<[ThreadCounters]>,
+    active_persistent: AtomicUsize,
+    gc_pauses: AtomicU64,
+    gc_full_sweeps: AtomicU64,
+    gc_time_ns: AtomicU64,
+    last_gc_update: Mutex<Option<Instant>>,
+    tasks: Mutex<TaskTotals>,
+}
+
+impl StatsCollector {
+    pub(crate) fn new(n_threads: usize) -> Self {
+        let threads = (0..n_threads)
+            .map(|_| ThreadCounters {
+                in_flight: AtomicUsize::new(0),
+                #[cfg(feature = "metrics")]
+                handled: AtomicU64::new(0),
+            })
+            .collect();
+
+        StatsCollector {
+            threads,
+            active_persistent: AtomicUsize::new(0),
+            gc_pauses: AtomicU64::new(0),
+            gc_full_sweeps: AtomicU64::new(0),
+            gc_time_ns: AtomicU64::new(0),
+            last_gc_update: Mutex::new(None),
+            tasks: Mutex::new(TaskTotals::default()),
+        }
+    }
+
+    pub(crate) fn snapshot(
+        &self,
+        main_queue: QueueStats,
+        any_queue: Option<QueueStats>,
+        worker_queue: Option<QueueStats>,
+    ) -> RuntimeStats {
+        let in_flight = self
+            .threads
+            .iter()
+            .map(|t| t.in_flight.load(Ordering::Relaxed))
+            .collect();
+
+        let gc = GcStats {
+            pauses: self.gc_pauses.load(Ordering::Relaxed),
+            full_sweeps: self.gc_full_sweeps.load(Ordering::Relaxed),
+            total_pause_time: Duration::from_nanos(self.gc_time_ns.load(Ordering::Relaxed)),
+        };
+
+        let totals = self.tasks.lock();
+
+        RuntimeStats {
+            main_queue,
+            any_queue,
+            worker_queue,
+            in_flight,
+            active_persistent: self.active_persistent.load(Ordering::Relaxed),
+            gc,
+            tasks: totals.stats,
+            #[cfg(feature = "metrics")]
+            metrics: RuntimeMetrics {
+                queued: totals.queued.clone(),
+                run: totals.run.clone(),
+                handled: self
+                    .threads
+                    .iter()
+                    .map(|t| t.handled.load(Ordering::Relaxed))
+                    .collect(),
+            },
+        }
+    }
+
+    // Called when `thread` receives a task that was dispatched at `queued_at`. Returns the time
+    // the task has been received.
+    pub(crate) fn received(&self, thread: usize, queued_at: Instant) -> Instant {
+        let now = Instant::now();
+        let queued = now.saturating_duration_since(queued_at);
+
+        #[cfg(feature = "metrics")]
+        self.threads[thread].handled.fetch_add(1, Ordering::Relaxed);
+        #[cfg(not(feature = "metrics"))]
+        let _ = thread;
+
+        let mut totals = self.tasks.lock();
+        totals.stats.received += 1;
+        totals.stats.total_queued += queued;
+        totals.stats.max_queued = totals.stats.max_queued.max(queued);
+        #[cfg(feature = "metrics")]
+        totals.queued.record(queued);
+
+        now
+    }
+
+    // Called when a task starts running in the background on `thread`.
+    #[inline]
+    pub(crate) fn started(&self, thread: usize, persistent: bool) {
+        self.threads[thread]
+            .in_flight
+            .fetch_add(1, Ordering::Relaxed);
+        if persistent {
+            self.active_persistent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Called when a task that was running in the background on `thread` has completed.
+    pub(crate) fn finished(&self, thread: usize, persistent: bool, received_at: Instant) {
+        self.threads[thread]
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+        if persistent {
+            self.active_persistent.fetch_sub(1, Ordering::Relaxed);
+        } else {
+            self.completed(received_at);
+        }
+    }
+
+    // Called when a task that was received at `received_at` has completed.
+    pub(crate) fn completed(&self, received_at: Instant) {
+        let run = received_at.elapsed();
+
+        let mut totals = self.tasks.lock();
+        totals.stats.completed += 1;
+        totals.stats.total_run += run;
+        totals.stats.max_run = totals.stats.max_run.max(run);
+        #[cfg(feature = "metrics")]
+        totals.run.record(run);
+    }
+
+    // Update the GC statistics if they haven't been updated recently.
+    //
+    // Safety: must be called from the main runtime thread after `JlrsStats` has been defined.
+    pub(crate) unsafe fn update_gc(&self) {
+        {
+            let mut last = self.last_gc_update.lock();
+            match *last {
+                Some(t) if t.elapsed() < GC_UPDATE_INTERVAL => return,
+                _ => *last = Some(Instant::now()),
+            }
+        }
+
+        let unrooted = Unrooted::new();
+        let func =
+            match Module::typed_global_cached::<Value, _, _>(&unrooted, "Main.JlrsStats.gcstats") {
+                Ok(func) => func,
+                Err(_) => return,
+            };
+
+        if let Ok(res) = func.call0(unrooted) {
+            if let Ok(Tuple3(pauses, full_sweeps, time_ns)) =
+                res.as_value().unbox::<Tuple3<i64, i64, i64>>()
+            {
+                self.gc_pauses.store(pauses as u64, Ordering::Relaxed);
+                self.gc_full_sweeps
+                    .store(full_sweeps as u64, Ordering::Relaxed);
+                self.gc_time_ns.store(time_ns as u64, Ordering::Relaxed);
+            }
+        }
+    }
+}
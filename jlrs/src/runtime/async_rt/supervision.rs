@@ -0,0 +1,238 @@
+//! Supervise persistent tasks.
+//!
+//! A [`PersistentTask`] that fails stays broken: if its `init` method returns an error no handle
+//! is created, and if a call to `run` panics its state can no longer be trusted. When a
+//! persistent task is sent to the runtime with [`AsyncJulia::supervised_persistent`], the
+//! [`SupervisionPolicy`] decides whether it's restarted after a failure. A restart calls `exit`
+//! with the old state if it exists, waits for the backoff duration, and calls `init` again.
+//!
+//! The handle of a supervised task remains valid across restarts. Calls that are made while the
+//! task is restarting wait in its channel and are handled after `init` has completed
+//! successfully. The health of a persistent task can be inspected with
+//! [`PersistentHandle::liveness`], [`PersistentHandle::restart_count`] and
+//! [`PersistentHandle::last_error`].
+//!
+//! A call that panics always fails the persistent task, a call that returns an error only does
+//! so if [`SupervisionPolicy::restart_on_error`] is enabled. The call that failed is not retried,
+//! its caller receives the error or [`RuntimeError::TaskPanicked`].
+//!
+//! [`PersistentTask`]: crate::async_util::task::PersistentTask
+//! [`AsyncJulia::supervised_persistent`]: crate::runtime::async_rt::AsyncJulia::supervised_persistent
+//! [`PersistentHandle::liveness`]: crate::runtime::async_rt::PersistentHandle::liveness
+//! [`PersistentHandle::restart_count`]: crate::runtime::async_rt::PersistentHandle::restart_count
+//! [`PersistentHandle::last_error`]: crate::runtime::async_rt::PersistentHandle::last_error
+//! [`RuntimeError::TaskPanicked`]: crate::error::RuntimeError::TaskPanicked
+
+use std::{
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+
+const DEFAULT_MAX_RESTARTS: usize = 5;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Decides if and when a failed persistent task is restarted.
+///
+/// By default a task is restarted at most 5 times. The first restart waits 100 milliseconds, the
+/// backoff is doubled for every following restart up to a maximum of 10 seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SupervisionPolicy {
+    max_restarts: Option<usize>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    restart_on_error: bool,
+}
+
+impl SupervisionPolicy {
+    /// Create a new policy with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        SupervisionPolicy {
+            max_restarts: Some(DEFAULT_MAX_RESTARTS),
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            restart_on_error: false,
+        }
+    }
+
+    /// Set the maximum number of times the task is restarted, if it's `None` the task is always
+    /// restarted.
+    #[inline]
+    pub fn max_restarts(mut self, max_restarts: Option<usize>) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Set the backoff before the first restart and the maximum backoff. The backoff is doubled
+    /// after every restart until the maximum has been reached.
+    #[inline]
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// If enabled, a call to `run` that returns an error is treated as a failure. This should be
+    /// enabled if an error can leave the state of the task in an invalid state.
+    #[inline]
+    pub fn restart_on_error(mut self, restart_on_error: bool) -> Self {
+        self.restart_on_error = restart_on_error;
+        self
+    }
+
+    // A policy that never restarts the task, used for unsupervised tasks.
+    #[inline]
+    pub(crate) fn never() -> Self {
+        SupervisionPolicy {
+            max_restarts: Some(0),
+            ..Self::new()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn restarts_on_error(&self) -> bool {
+        self.restart_on_error
+    }
+
+    // Returns the backoff before the next restart, or `None` if `n_restarts` restarts have
+    // already happened and the task must not be restarted again.
+    pub(crate) fn next_backoff(&self, n_restarts: usize) -> Option<Duration> {
+        if let Some(max_restarts) = self.max_restarts {
+            if n_restarts >= max_restarts {
+                return None;
+            }
+        }
+
+        let factor = 1u32
+            .checked_shl(n_restarts.min(31) as u32)
+            .unwrap_or(u32::MAX);
+        let backoff = self
+            .initial_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff);
+
+        Some(backoff.min(self.max_backoff))
+    }
+}
+
+impl Default for SupervisionPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The liveness of a persistent task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Liveness {
+    /// The task is being initialized for the first time.
+    Starting,
+    /// The task has been initialized and handles calls.
+    Running,
+    /// The task has failed and is being restarted, calls wait until it's running again.
+    Restarting,
+    /// The task has stopped, either because all handles have been dropped, the runtime has shut
+    /// down, or it has failed and can't be restarted.
+    Stopped,
+}
+
+impl Liveness {
+    #[inline]
+    fn from_u8(liveness: u8) -> Self {
+        match liveness {
+            0 => Liveness::Starting,
+            1 => Liveness::Running,
+            2 => Liveness::Restarting,
+            _ => Liveness::Stopped,
+        }
+    }
+}
+
+// The health of a persistent task, shared by its handles and the task itself.
+pub(crate) struct PersistentHealth {
+    liveness: AtomicU8,
+    restarts: AtomicUsize,
+    last_error: Mutex<Option<String>>,
+}
+
+impl PersistentHealth {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        PersistentHealth {
+            liveness: AtomicU8::new(Liveness::Starting as u8),
+            restarts: AtomicUsize::new(0),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn liveness(&self) -> Liveness {
+        Liveness::from_u8(self.liveness.load(Ordering::Acquire))
+    }
+
+    #[inline]
+    pub(crate) fn restart_count(&self) -> usize {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub(crate) fn last_error(&self) -> Option<String> {
+        self.last_error.lock().clone()
+    }
+
+    #[inline]
+    pub(crate) fn set_liveness(&self, liveness: Liveness) {
+        self.liveness.store(liveness as u8, Ordering::Release)
+    }
+
+    #[inline]
+    pub(crate) fn failed(&self, error: String) {
+        *self.last_error.lock() = Some(error);
+    }
+
+    #[inline]
+    pub(crate) fn restarted(&self) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Liveness, PersistentHealth, SupervisionPolicy};
+
+    #[test]
+    fn backoff_is_doubled_and_capped() {
+        let policy = SupervisionPolicy::new()
+            .max_restarts(Some(4))
+            .backoff(Duration::from_millis(10), Duration::from_millis(50));
+
+        assert_eq!(policy.next_backoff(0), Some(Duration::from_millis(10)));
+        assert_eq!(policy.next_backoff(1), Some(Duration::from_millis(20)));
+        assert_eq!(policy.next_backoff(2), Some(Duration::from_millis(40)));
+        assert_eq!(policy.next_backoff(3), Some(Duration::from_millis(50)));
+        assert_eq!(policy.next_backoff(4), None);
+    }
+
+    #[test]
+    fn health_starts_in_starting_state() {
+        let health = PersistentHealth::new();
+        assert_eq!(health.liveness(), Liveness::Starting);
+
+        for liveness in [Liveness::Running, Liveness::Restarting, Liveness::Stopped] {
+            health.set_liveness(liveness);
+            assert_eq!(health.liveness(), liveness);
+        }
+    }
+
+    #[test]
+    fn unlimited_restarts() {
+        let policy = SupervisionPolicy::new().max_restarts(None);
+        assert_eq!(policy.next_backoff(1000), Some(Duration::from_secs(10)));
+        assert_eq!(SupervisionPolicy::never().next_backoff(0), None);
+    }
+}
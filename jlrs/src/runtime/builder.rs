@@ -1,17 +1,17 @@
 //! Build a runtime.
 //!
 //! Before Julia can be used it must be initialized. The builders provided by this module must be
-//! used to initialize Julia and set custom parameters. The [`RuntimeBuilder`] only lets you
-//! provide a custom system image, [`AsyncRuntimeBuilder`] provides additional methods to set the
-//! number of threads available to Julia among others.
+//! used to initialize Julia and set custom parameters. The [`RuntimeBuilder`] lets you provide a
+//! custom system image and set Julia's startup options, [`AsyncRuntimeBuilder`] provides
+//! additional methods to set the number of threads available to Julia among others.
 
 #[cfg(feature = "async-rt")]
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
-#[cfg(feature = "async-rt")]
 use jlrs_macros::julia_version;
 
+use super::options::{CheckBounds, CompileMode, JuliaOptions};
 #[cfg(feature = "sync-rt")]
 use super::sync_rt::PendingJulia;
 #[cfg(any(feature = "sync-rt", feature = "async-rt"))]
@@ -27,6 +27,7 @@ use crate::InstallJlrsCore;
 pub struct RuntimeBuilder {
     pub(crate) image: Option<(PathBuf, PathBuf)>,
     pub(crate) install_jlrs_core: InstallJlrsCore,
+    pub(crate) options: JuliaOptions,
 }
 
 cfg_if::cfg_if! {
@@ -45,6 +46,9 @@ cfg_if::cfg_if! {
             pub(crate) builder: RuntimeBuilder,
             pub(crate) n_threads: usize,
             pub(crate) channel_capacity: NonZeroUsize,
+            pub(crate) n_priority_classes: NonZeroUsize,
+            pub(crate) class_capacities: Vec<Option<NonZeroUsize>>,
+            pub(crate) aging_interval: Option<Duration>,
             pub(crate) recv_timeout: Duration,
             #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
             pub(crate) n_threadsi: usize,
@@ -100,6 +104,52 @@ cfg_if::cfg_if! {
                 self
             }
 
+            /// Set the number of priority classes of the channel used to communicate with the
+            /// async runtime.
+            ///
+            /// The priority of a task can be set with [`Dispatch::with_priority`], classes are
+            /// numbered from 0, the lowest priority, to `n - 1`. Each class has its own capacity,
+            /// which is the channel capacity unless it's set with
+            /// [`AsyncRuntimeBuilder::class_capacity`].
+            ///
+            /// The default value is 1, i.e. all tasks have the same priority.
+            ///
+            /// [`Dispatch::with_priority`]: crate::runtime::async_rt::dispatch::Dispatch::with_priority
+            #[inline]
+            pub fn priority_classes(mut self, n: NonZeroUsize) -> Self {
+                self.n_priority_classes = n;
+                self
+            }
+
+            /// Set the capacity of a priority class.
+            ///
+            /// Classes whose capacity hasn't been set use the channel capacity. Setting the
+            /// capacity of a class that doesn't exist has no effect. The ratios between the
+            /// capacities of the classes are preserved when a queue is resized.
+            #[inline]
+            pub fn class_capacity(mut self, class: usize, capacity: NonZeroUsize) -> Self {
+                if self.class_capacities.len() <= class {
+                    self.class_capacities.resize(class + 1, None);
+                }
+
+                self.class_capacities[class] = Some(capacity);
+                self
+            }
+
+            /// Set the aging interval of the channel used to communicate with the async runtime.
+            ///
+            /// For every interval a task has been waiting in the channel, its priority is
+            /// increased by one class. This prevents tasks with a low priority from being starved
+            /// by a continuous stream of tasks with a higher priority. If it's set to `None`,
+            /// tasks don't age.
+            ///
+            /// The default value is 100 milliseconds.
+            #[inline]
+            pub fn aging_interval(mut self, interval: Option<Duration>) -> Self {
+                self.aging_interval = interval;
+                self
+            }
+
             /// Set the receive timeout of the channel used to communicate with the async runtime.
             ///
             /// If no message is received before the timeout occurs, the async runtime yields
@@ -147,6 +197,110 @@ cfg_if::cfg_if! {
                 self
             }
 
+            /// Set the optimization level, equivalent to `-O`.
+            ///
+            /// The level must be between 0 and 3, the default level is 2.
+            #[inline]
+            pub fn optimization_level(mut self, level: u8) -> Self {
+                self.builder.options.opt_level = Some(level);
+                self
+            }
+
+            /// Set whether bounds checks are emitted, equivalent to `--check-bounds`.
+            #[inline]
+            pub fn check_bounds(mut self, check_bounds: CheckBounds) -> Self {
+                self.builder.options.check_bounds = Some(check_bounds);
+                self
+            }
+
+            /// Set the active project, equivalent to `--project`.
+            ///
+            /// The project can be the path to a directory that contains a `Project.toml` file, or the path
+            /// to the project file itself. `@.` searches the current directory and its parents. The project
+            /// is activated when Julia is initialized, if the path doesn't exist starting the runtime returns
+            /// an error.
+            #[inline]
+            pub fn project<P>(mut self, project: P) -> Self
+            where
+                P: AsRef<Path>,
+            {
+                self.builder.options.project = Some(project.as_ref().to_path_buf());
+                self
+            }
+
+            /// Set the depot path.
+            ///
+            /// Julia reads the depot path from the `JULIA_DEPOT_PATH` environment variable, which is set
+            /// by the thread that starts the runtime before Julia is initialized. Setting an environment
+            /// variable isn't thread-safe on all platforms, no other thread may access the environment
+            /// while the runtime is started.
+            #[inline]
+            pub fn depot_path<I, P>(mut self, paths: I) -> Self
+            where
+                I: IntoIterator<Item = P>,
+                P: AsRef<Path>,
+            {
+                self.builder.options.depot_path = Some(
+                    paths
+                        .into_iter()
+                        .map(|path| path.as_ref().to_path_buf())
+                        .collect(),
+                );
+                self
+            }
+
+            /// Set whether the startup file is loaded, equivalent to `--startup-file`.
+            #[inline]
+            pub fn startup_file(mut self, load: bool) -> Self {
+                self.builder.options.startup_file = Some(load);
+                self
+            }
+
+            /// Enable or disable the JIT compiler, equivalent to `--compile`.
+            #[inline]
+            pub fn compile(mut self, mode: CompileMode) -> Self {
+                self.builder.options.compile = Some(mode);
+                self
+            }
+
+            #[julia_version(since = "1.9")]
+            /// Set the heap size hint in bytes, equivalent to `--heap-size-hint`.
+            ///
+            /// The garbage collector is run more aggressively when this limit is reached.
+            #[inline]
+            pub fn heap_size_hint(mut self, bytes: u64) -> Self {
+                self.builder.options.heap_size_hint = Some(bytes);
+                self
+            }
+
+            /// Enable or disable Julia's signal handlers, equivalent to `--handle-signals`.
+            ///
+            /// Julia's signal handlers are enabled by default.
+            #[inline]
+            pub fn handle_signals(mut self, handle: bool) -> Self {
+                self.builder.options.handle_signals = Some(handle);
+                self
+            }
+
+            /// Load packages with `using` after Julia has been initialized.
+            ///
+            /// The packages must be available in the active project or be part of the standard library,
+            /// they're never installed. If a package can't be found or fails to load, the runtime thread
+            /// returns an error.
+            #[inline]
+            pub fn preload<I, S>(mut self, packages: I) -> Self
+            where
+                I: IntoIterator<Item = S>,
+                S: AsRef<str>,
+            {
+                self.builder.options.preload.extend(
+                    packages
+                        .into_iter()
+                        .map(|package| package.as_ref().to_string()),
+                );
+                self
+            }
+
             /// Initialize Julia on another thread.
             ///
             /// You must set the maximum number of concurrent tasks with the `N` const generic.
@@ -163,6 +317,34 @@ cfg_if::cfg_if! {
                 AsyncJulia::init_async::<N>(self)
             }
 
+            #[inline]
+            pub(crate) fn class_capacities(&self) -> Vec<usize> {
+                (0..self.n_priority_classes.get())
+                    .map(|class| {
+                        self.class_capacities
+                            .get(class)
+                            .copied()
+                            .flatten()
+                            .unwrap_or(self.channel_capacity)
+                            .get()
+                    })
+                    .collect()
+            }
+
+            // The number of threads used by the runtime, including the main runtime thread.
+            #[inline]
+            pub(crate) fn n_runtime_threads(&self) -> usize {
+                #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
+                {
+                    self.n_workers + 1
+                }
+
+                #[cfg(not(any(feature = "julia-1-10", feature = "julia-1-9")))]
+                {
+                    1
+                }
+            }
+
             #[inline]
             pub(crate) fn has_workers(&self) -> bool {
                 #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
@@ -186,6 +368,7 @@ impl RuntimeBuilder {
         RuntimeBuilder {
             image: None,
             install_jlrs_core: InstallJlrsCore::Default,
+            options: JuliaOptions::default(),
         }
     }
 
@@ -240,6 +423,9 @@ impl RuntimeBuilder {
             builder: self,
             n_threads: 0,
             channel_capacity: unsafe { NonZeroUsize::new_unchecked(16) },
+            n_priority_classes: unsafe { NonZeroUsize::new_unchecked(1) },
+            class_capacities: Vec::new(),
+            aging_interval: Some(Duration::from_millis(100)),
             recv_timeout: Duration::from_millis(1),
             #[cfg(any(feature = "julia-1-10", feature = "julia-1-9"))]
             n_threadsi: 0,
@@ -281,4 +467,108 @@ impl RuntimeBuilder {
         self.install_jlrs_core = install;
         self
     }
+
+    /// Set the optimization level, equivalent to `-O`.
+    ///
+    /// The level must be between 0 and 3, the default level is 2.
+    #[inline]
+    pub fn optimization_level(mut self, level: u8) -> Self {
+        self.options.opt_level = Some(level);
+        self
+    }
+
+    /// Set whether bounds checks are emitted, equivalent to `--check-bounds`.
+    #[inline]
+    pub fn check_bounds(mut self, check_bounds: CheckBounds) -> Self {
+        self.options.check_bounds = Some(check_bounds);
+        self
+    }
+
+    /// Set the active project, equivalent to `--project`.
+    ///
+    /// The project can be the path to a directory that contains a `Project.toml` file, or the path
+    /// to the project file itself. `@.` searches the current directory and its parents. The project
+    /// is activated when Julia is initialized, if the path doesn't exist starting the runtime returns
+    /// an error.
+    #[inline]
+    pub fn project<P>(mut self, project: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.options.project = Some(project.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the depot path.
+    ///
+    /// Julia reads the depot path from the `JULIA_DEPOT_PATH` environment variable, which is set
+    /// by the thread that starts the runtime before Julia is initialized. Setting an environment
+    /// variable isn't thread-safe on all platforms, no other thread may access the environment
+    /// while the runtime is started.
+    #[inline]
+    pub fn depot_path<I, P>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.options.depot_path = Some(
+            paths
+                .into_iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .collect(),
+        );
+        self
+    }
+
+    /// Set whether the startup file is loaded, equivalent to `--startup-file`.
+    #[inline]
+    pub fn startup_file(mut self, load: bool) -> Self {
+        self.options.startup_file = Some(load);
+        self
+    }
+
+    /// Enable or disable the JIT compiler, equivalent to `--compile`.
+    #[inline]
+    pub fn compile(mut self, mode: CompileMode) -> Self {
+        self.options.compile = Some(mode);
+        self
+    }
+
+    #[julia_version(since = "1.9")]
+    /// Set the heap size hint in bytes, equivalent to `--heap-size-hint`.
+    ///
+    /// The garbage collector is run more aggressively when this limit is reached.
+    #[inline]
+    pub fn heap_size_hint(mut self, bytes: u64) -> Self {
+        self.options.heap_size_hint = Some(bytes);
+        self
+    }
+
+    /// Load packages with `using` after Julia has been initialized.
+    ///
+    /// The packages must be available in the active project or be part of the standard library,
+    /// they're never installed. If a package can't be found or fails to load, starting the runtime
+    /// returns an error.
+    #[inline]
+    pub fn preload<I, S>(mut self, packages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.options.preload.extend(
+            packages
+                .into_iter()
+                .map(|package| package.as_ref().to_string()),
+        );
+        self
+    }
+
+    /// Enable or disable Julia's signal handlers, equivalent to `--handle-signals`.
+    ///
+    /// Julia's signal handlers are enabled by default.
+    #[inline]
+    pub fn handle_signals(mut self, handle: bool) -> Self {
+        self.options.handle_signals = Some(handle);
+        self
+    }
 }
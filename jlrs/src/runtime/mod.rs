@@ -16,6 +16,7 @@ use std::sync::atomic::AtomicBool;
 #[cfg(feature = "async-rt")]
 pub mod async_rt;
 pub mod builder;
+pub mod options;
 #[cfg(feature = "sync-rt")]
 pub mod sync_rt;
 
@@ -0,0 +1,315 @@
+//! Julia startup options.
+//!
+//! Julia reads its startup options from the global `jl_options` struct, which is normally
+//! populated from the command line arguments of the `julia` executable. The options in this
+//! module can be set with the [`RuntimeBuilder`] and [`AsyncRuntimeBuilder`], they're validated
+//! when the runtime is started and applied right before Julia is initialized.
+//!
+//! The builders can also preload a list of packages. These packages are loaded with `using`
+//! right after Julia has been initialized, they must be available in the active project or be
+//! part of the standard library. Packages are never installed, so no network access is required.
+//!
+//! [`RuntimeBuilder`]: crate::runtime::builder::RuntimeBuilder
+//! [`AsyncRuntimeBuilder`]: crate::runtime::builder::AsyncRuntimeBuilder
+
+use std::{
+    env,
+    ffi::{CString, OsString},
+    path::{Path, PathBuf},
+};
+
+use jl_sys::jl_options;
+use jlrs_macros::julia_version;
+
+use crate::{
+    data::managed::{string::JuliaString, value::Value},
+    error::{IOError, JlrsResult, RuntimeError, CANNOT_DISPLAY_VALUE},
+    memory::target::unrooted::Unrooted,
+};
+
+// Values of `jl_options.check_bounds`.
+const JL_OPTIONS_CHECK_BOUNDS_DEFAULT: i8 = 0;
+const JL_OPTIONS_CHECK_BOUNDS_ON: i8 = 1;
+const JL_OPTIONS_CHECK_BOUNDS_OFF: i8 = 2;
+
+// Values of `jl_options.compile_enabled`.
+const JL_OPTIONS_COMPILE_OFF: i8 = 0;
+const JL_OPTIONS_COMPILE_ON: i8 = 1;
+const JL_OPTIONS_COMPILE_ALL: i8 = 2;
+const JL_OPTIONS_COMPILE_MIN: i8 = 3;
+
+// Values of `jl_options.startupfile`.
+const JL_OPTIONS_STARTUPFILE_ON: i8 = 1;
+const JL_OPTIONS_STARTUPFILE_OFF: i8 = 2;
+
+// Values of `jl_options.handle_signals`.
+const JL_OPTIONS_HANDLE_SIGNALS_ON: i8 = 1;
+const JL_OPTIONS_HANDLE_SIGNALS_OFF: i8 = 0;
+
+// The highest supported optimization level.
+const MAX_OPT_LEVEL: u8 = 3;
+
+/// Emit bounds checks, equivalent to `--check-bounds`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckBounds {
+    /// Respect `@inbounds` declarations, this is the default behavior.
+    Auto,
+    /// Always emit bounds checks.
+    Yes,
+    /// Never emit bounds checks.
+    No,
+}
+
+impl CheckBounds {
+    #[inline]
+    fn as_option(self) -> i8 {
+        match self {
+            CheckBounds::Auto => JL_OPTIONS_CHECK_BOUNDS_DEFAULT,
+            CheckBounds::Yes => JL_OPTIONS_CHECK_BOUNDS_ON,
+            CheckBounds::No => JL_OPTIONS_CHECK_BOUNDS_OFF,
+        }
+    }
+}
+
+/// Enable or disable the JIT compiler, equivalent to `--compile`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompileMode {
+    /// Enable the compiler, this is the default behavior.
+    Yes,
+    /// Disable the compiler, only code that has been compiled in advance can be used.
+    No,
+    /// Compile all code in advance.
+    All,
+    /// Compile as little code as possible.
+    Min,
+}
+
+impl CompileMode {
+    #[inline]
+    fn as_option(self) -> i8 {
+        match self {
+            CompileMode::Yes => JL_OPTIONS_COMPILE_ON,
+            CompileMode::No => JL_OPTIONS_COMPILE_OFF,
+            CompileMode::All => JL_OPTIONS_COMPILE_ALL,
+            CompileMode::Min => JL_OPTIONS_COMPILE_MIN,
+        }
+    }
+}
+
+// The startup options that have been set with a builder. Options that are `None` keep the value
+// Julia uses by default.
+#[derive(Clone, Default)]
+pub(crate) struct JuliaOptions {
+    pub(crate) opt_level: Option<u8>,
+    pub(crate) check_bounds: Option<CheckBounds>,
+    pub(crate) project: Option<PathBuf>,
+    pub(crate) depot_path: Option<Vec<PathBuf>>,
+    pub(crate) startup_file: Option<bool>,
+    pub(crate) compile: Option<CompileMode>,
+    pub(crate) heap_size_hint: Option<u64>,
+    pub(crate) handle_signals: Option<bool>,
+    pub(crate) preload: Vec<String>,
+}
+
+// The validated options, ready to be applied.
+pub(crate) struct ValidatedOptions {
+    options: JuliaOptions,
+    project: Option<CString>,
+    depot_path: Option<OsString>,
+}
+
+impl JuliaOptions {
+    // Checks if all options have a valid value.
+    pub(crate) fn validate(&self) -> JlrsResult<ValidatedOptions> {
+        if let Some(opt_level) = self.opt_level {
+            if opt_level > MAX_OPT_LEVEL {
+                Err(RuntimeError::InvalidOption {
+                    option: "optimization level".into(),
+                    reason: format!("{opt_level} is larger than {MAX_OPT_LEVEL}"),
+                })?
+            }
+        }
+
+        let project = match self.project {
+            Some(ref project) => {
+                let project = project.to_string_lossy().to_string();
+                // Projects like `@.` and `@v1.10` are resolved by Julia.
+                if !project.starts_with('@') && !Path::new(&project).exists() {
+                    Err(IOError::NotFound {
+                        path: project.clone(),
+                    })?
+                }
+
+                match CString::new(project) {
+                    Ok(project) => Some(project),
+                    Err(_) => Err(RuntimeError::InvalidOption {
+                        option: "project".into(),
+                        reason: "path contains a null byte".into(),
+                    })?,
+                }
+            }
+            None => None,
+        };
+
+        let depot_path = match self.depot_path {
+            Some(ref paths) => match env::join_paths(paths) {
+                Ok(joined) => Some(joined),
+                Err(e) => Err(RuntimeError::InvalidOption {
+                    option: "depot path".into(),
+                    reason: e.to_string(),
+                })?,
+            },
+            None => None,
+        };
+
+        if self.heap_size_hint == Some(0) {
+            Err(RuntimeError::InvalidOption {
+                option: "heap size hint".into(),
+                reason: "must be larger than 0".into(),
+            })?
+        }
+
+        for package in self.preload.iter() {
+            if !is_package_name(package) {
+                Err(RuntimeError::InvalidOption {
+                    option: "preload".into(),
+                    reason: format!("{package} is not a valid package name"),
+                })?
+            }
+        }
+
+        Ok(ValidatedOptions {
+            options: self.clone(),
+            project,
+            depot_path,
+        })
+    }
+}
+
+impl ValidatedOptions {
+    // Sets the `JULIA_DEPOT_PATH` environment variable, which is read when Julia is initialized.
+    //
+    // Safety: setting an environment variable is not thread-safe on all platforms, this must be
+    // called on the thread that starts the runtime before any runtime thread has been spawned, and
+    // no other thread may access the environment at the same time.
+    pub(crate) unsafe fn set_depot_path(&self) {
+        if let Some(ref depot_path) = self.depot_path {
+            env::set_var("JULIA_DEPOT_PATH", depot_path);
+        }
+    }
+
+    // Writes the options to `jl_options`, must be called before Julia is initialized.
+    pub(crate) unsafe fn apply(&self) {
+        let options = &self.options;
+
+        if let Some(opt_level) = options.opt_level {
+            jl_options.opt_level = opt_level as _;
+        }
+
+        if let Some(check_bounds) = options.check_bounds {
+            jl_options.check_bounds = check_bounds.as_option();
+        }
+
+        if let Some(ref project) = self.project {
+            // Julia can read this option at any time, so the string is leaked.
+            jl_options.project = Box::leak(project.clone().into_boxed_c_str()).as_ptr();
+        }
+
+        if let Some(startup_file) = options.startup_file {
+            jl_options.startupfile = if startup_file {
+                JL_OPTIONS_STARTUPFILE_ON
+            } else {
+                JL_OPTIONS_STARTUPFILE_OFF
+            };
+        }
+
+        if let Some(compile) = options.compile {
+            jl_options.compile_enabled = compile.as_option();
+        }
+
+        if let Some(heap_size_hint) = options.heap_size_hint {
+            set_heap_size_hint(heap_size_hint);
+        }
+
+        if let Some(handle_signals) = options.handle_signals {
+            jl_options.handle_signals = if handle_signals {
+                JL_OPTIONS_HANDLE_SIGNALS_ON
+            } else {
+                JL_OPTIONS_HANDLE_SIGNALS_OFF
+            };
+        }
+    }
+
+    // Loads the preloaded packages, must be called after Julia has been initialized.
+    pub(crate) unsafe fn preload(&self) -> JlrsResult<()> {
+        let unrooted = Unrooted::new();
+
+        for package in self.options.preload.iter() {
+            let cmd = format!("Base.find_package(\"{package}\") !== nothing");
+            let found =
+                eval_bool(unrooted, &cmd).map_err(|reason| RuntimeError::PreloadFailed {
+                    package: package.clone(),
+                    reason,
+                })?;
+
+            if !found {
+                Err(RuntimeError::PreloadFailed {
+                    package: package.clone(),
+                    reason: "package not found in the active environment".into(),
+                })?
+            }
+
+            let cmd = format!("using {package}\ntrue");
+            eval_bool(unrooted, &cmd).map_err(|reason| RuntimeError::PreloadFailed {
+                package: package.clone(),
+                reason,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+// Evaluates `cmd`, which must evaluate to a `Bool`, at the top level of `Main`. If an exception
+// is thrown its error message is returned instead. JlrsCore hasn't been loaded yet when packages
+// are preloaded, so the message is rendered with `Base.showerror` in the same evaluation.
+unsafe fn eval_bool(unrooted: Unrooted, cmd: &str) -> Result<bool, String> {
+    let cmd = format!("try\n{cmd}\ncatch e\nBase.sprint(Base.showerror, e)\nend");
+
+    match Value::eval_string(unrooted, cmd) {
+        Ok(res) => {
+            let res = res.as_value();
+            if let Ok(msg) = res.cast::<JuliaString>() {
+                Err(msg.as_str().unwrap_or(CANNOT_DISPLAY_VALUE).into())
+            } else {
+                res.unbox::<bool>()
+                    .map(|b| b.as_bool())
+                    .map_err(|e| e.to_string())
+            }
+        }
+        Err(exc) => Err(exc
+            .as_value()
+            .datatype_name()
+            .unwrap_or(CANNOT_DISPLAY_VALUE)
+            .into()),
+    }
+}
+
+// Returns `true` if `name` is a valid package name.
+fn is_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+#[julia_version(since = "1.9")]
+#[inline]
+unsafe fn set_heap_size_hint(heap_size_hint: u64) {
+    jl_options.heap_size_hint = heap_size_hint;
+}
+
+#[julia_version(until = "1.8")]
+#[inline]
+unsafe fn set_heap_size_hint(_heap_size_hint: u64) {}
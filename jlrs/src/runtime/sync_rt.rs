@@ -2,11 +2,30 @@
 //!
 //! This module is only available if the `sync-rt` feature is enabled, it provides the sync
 //! runtime which initializes Julia on the current thread.
+//!
+//! Starting with Julia 1.9, other threads can call into Julia too. A [`ThreadAdopter`] can be
+//! created with [`Julia::thread_adopter`] and shared with other threads, every thread that calls
+//! [`ThreadAdopter::scope`] is adopted by Julia the first time it does so. An adopted thread is
+//! put in a GC-safe state whenever it leaves that scope, so it never prevents the GC from
+//! running while it's idle.
+//!
+//! The thread that initialized Julia doesn't enter a GC-safe state automatically. While it waits
+//! for other threads that call into Julia, e.g. while it waits for the tasks it has submitted to
+//! a thread pool to complete, it must do so inside [`Julia::gc_safe`]. Otherwise the GC can't
+//! run and the other threads will deadlock as soon as they need to collect garbage.
 
 use std::{ffi::c_void, marker::PhantomData, path::Path, sync::atomic::Ordering};
 
+#[julia_version(since = "1.9")]
+use jl_sys::{
+    jl_adopt_thread, jl_get_pgcstack, jlrs_gc_safe_enter, jlrs_gc_safe_leave, jlrs_gc_unsafe_enter,
+    jlrs_gc_unsafe_leave,
+};
 use jl_sys::{jl_atexit_hook, jl_init, jl_init_with_image, jl_is_initialized};
+use jlrs_macros::julia_version;
 
+#[julia_version(since = "1.9")]
+use crate::memory::{get_tls, PTls};
 use crate::{
     call::Call,
     convert::into_jlrs_result::IntoJlrsResult,
@@ -18,6 +37,7 @@ use crate::{
         stack_frame::{PinnedFrame, StackFrame},
         target::frame::GcFrame,
     },
+    redirect::{redirect_output, restore_output, StdStream},
     runtime::{builder::RuntimeBuilder, INIT},
     INSTALL_METHOD,
 };
@@ -31,10 +51,14 @@ pub struct PendingJulia {
 
 impl PendingJulia {
     pub(crate) unsafe fn init(builder: RuntimeBuilder) -> JlrsResult<Self> {
+        let options = builder.options.validate()?;
         if jl_is_initialized() != 0 || INIT.swap(true, Ordering::Relaxed) {
             Err(RuntimeError::AlreadyInitialized)?;
         }
 
+        options.set_depot_path();
+        options.apply();
+
         if let Some((julia_bindir, image_path)) = builder.image {
             let julia_bindir_str = julia_bindir.to_string_lossy().to_string();
             let image_path_str = image_path.to_string_lossy().to_string();
@@ -60,6 +84,7 @@ impl PendingJulia {
         }
 
         assert!(jl_is_initialized() != 0);
+        options.preload()?;
 
         let install_method = builder.install_jlrs_core.clone();
         INSTALL_METHOD.get_or_init(|| install_method);
@@ -95,6 +120,7 @@ impl PendingJulia {
 impl Drop for PendingJulia {
     fn drop(&mut self) {
         unsafe {
+            crate::data::rooted::julia_exited();
             jl_atexit_hook(0);
         }
     }
@@ -132,6 +158,25 @@ impl Julia<'_> {
         Ok(())
     }
 
+    /// Redirect Julia's `stdout` or `stderr` to `writer`. Everything Julia writes to the
+    /// redirected stream is written to `writer` until the stream is restored with
+    /// [`Julia::restore_output`].
+    ///
+    /// See the [`redirect`] module for more information.
+    ///
+    /// [`redirect`]: crate::redirect
+    pub fn redirect_output<W>(&mut self, stream: StdStream, writer: W) -> JlrsResult<()>
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        self.scope(|frame| redirect_output(&frame, stream, writer))
+    }
+
+    /// Restore Julia's original `stdout` or `stderr`.
+    pub fn restore_output(&mut self, stream: StdStream) -> JlrsResult<()> {
+        self.scope(|frame| restore_output(&frame, stream))
+    }
+
     /// Calls `include` in the `Main` module in Julia, which executes the file's contents in that
     /// module. This has the same effect as calling `include` in the Julia REPL.
     ///
@@ -205,4 +250,118 @@ impl Julia<'_> {
             ret
         }
     }
+
+    #[julia_version(since = "1.9")]
+    /// Call `func` in a GC-safe state.
+    ///
+    /// While `func` is called the GC can run without waiting for this thread to reach a
+    /// safepoint. This must be used when this thread waits for other threads that call into
+    /// Julia, otherwise the GC can't run and those threads will deadlock. The closure can't
+    /// call into Julia because `self` is borrowed mutably.
+    pub fn gc_safe<T, F>(&mut self, func: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        unsafe {
+            let ptls = get_tls();
+            let _guard = GcStateGuard {
+                ptls,
+                state: jlrs_gc_safe_enter(ptls),
+                safe: true,
+            };
+
+            func()
+        }
+    }
+}
+
+impl<'context> Julia<'context> {
+    #[julia_version(since = "1.9")]
+    /// Returns a [`ThreadAdopter`], which lets other threads call into Julia.
+    ///
+    /// The adopter borrows the [`PendingJulia`] this instance was created from, so it can't be
+    /// used after Julia has been shut down.
+    #[inline]
+    pub fn thread_adopter(&self) -> ThreadAdopter<'context> {
+        ThreadAdopter {
+            _runtime: PhantomData,
+        }
+    }
+}
+
+#[julia_version(since = "1.9")]
+/// A handle that lets other threads call into Julia.
+///
+/// This handle can be created with [`Julia::thread_adopter`], it can be copied and shared across
+/// threads freely. Every thread that calls [`ThreadAdopter::scope`] is adopted by Julia the first
+/// time it does so. The handle can't outlive the runtime it was created from.
+#[derive(Clone, Copy, Debug)]
+pub struct ThreadAdopter<'context> {
+    _runtime: PhantomData<&'context ()>,
+}
+
+#[julia_version(since = "1.9")]
+impl ThreadAdopter<'_> {
+    /// Create a new scope on the current thread, call the given closure, and return its result.
+    ///
+    /// If the current thread is unknown to Julia it's adopted first. The thread is put in a
+    /// GC-unsafe state while `func` is called, and is returned to its previous state afterwards.
+    /// Adopted threads are in a GC-safe state while they're not in a scope.
+    ///
+    /// While the thread is in this scope the GC can only run after it has reached a safepoint.
+    /// Every function call into Julia has one, but this scope should not be used for long-running
+    /// computations that don't call into Julia.
+    pub fn scope<T, F>(&self, func: F) -> JlrsResult<T>
+    where
+        for<'base> F: FnOnce(GcFrame<'base>) -> JlrsResult<T>,
+    {
+        unsafe {
+            if jl_get_pgcstack().is_null() {
+                // An adopted thread starts in a GC-unsafe state, it's kept in a GC-safe state
+                // while it's not in a scope.
+                jl_adopt_thread();
+                jlrs_gc_safe_enter(get_tls());
+            }
+
+            let ptls = get_tls();
+            let _guard = GcStateGuard {
+                ptls,
+                state: jlrs_gc_unsafe_enter(ptls),
+                safe: false,
+            };
+
+            let mut frame = StackFrame::new();
+            let mut pinned = frame.pin();
+            let stack = pinned.stack_frame().sync_stack();
+
+            let (owner, frame) = GcFrame::base(stack);
+            let ret = func(frame);
+            std::mem::drop(owner);
+            std::mem::drop(pinned);
+            ret
+        }
+    }
+}
+
+// Restores the previous GC state when it's dropped, even if the closure called while the guard
+// is active panics.
+#[julia_version(since = "1.9")]
+struct GcStateGuard {
+    ptls: PTls,
+    state: i8,
+    safe: bool,
+}
+
+#[julia_version(since = "1.9")]
+impl Drop for GcStateGuard {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            if self.safe {
+                jlrs_gc_safe_leave(self.ptls, self.state)
+            } else {
+                jlrs_gc_unsafe_leave(self.ptls, self.state)
+            }
+        }
+    }
 }
@@ -0,0 +1,179 @@
+//! Evaluate code in isolated modules.
+//!
+//! [`Value::eval_string`] and [`Value::include`] always evaluate code in the `Main` module, so
+//! all code shares the same global scope. A [`Sandbox`] owns a new anonymous module that isn't
+//! reachable from any other module, code evaluated in a sandbox can only define and change
+//! globals in that module. Only `Core` is available in a new sandbox, other modules like `Base`
+//! must be imported explicitly when the sandbox is created.
+//!
+//! The globals of a sandbox can be accessed with the methods of [`Module`], e.g.
+//! [`Module::global`] and [`Module::set_global`]. After the sandbox has been dropped and the
+//! frame it has been rooted in has been popped, its module and globals become unreachable and
+//! can be freed by the garbage collector. Note that a sandbox is not a security boundary: code
+//! evaluated in it can still access and modify other modules, e.g. by adding methods to
+//! functions defined in `Base`.
+//!
+//! [`Value::eval_string`]: crate::data::managed::value::Value::eval_string
+//! [`Value::include`]: crate::data::managed::value::Value::include
+
+use std::path::Path;
+
+use crate::{
+    call::Call,
+    convert::into_jlrs_result::IntoJlrsResult,
+    data::managed::{
+        module::Module,
+        string::JuliaString,
+        symbol::Symbol,
+        value::{Value, ValueResult},
+        Managed,
+    },
+    error::{AccessError, IOError, JlrsResult},
+    memory::target::{Target, TargetType},
+};
+
+/// An anonymous module that code can be evaluated in.
+///
+/// See the [module-level docs] for more information.
+///
+/// [module-level docs]: self
+#[derive(Clone, Copy, Debug)]
+pub struct Sandbox<'scope> {
+    module: Module<'scope>,
+}
+
+impl<'scope> Sandbox<'scope> {
+    /// Create a new sandbox, every module in `imports` is made available in the sandbox with a
+    /// `using` statement. The names in `imports` must be valid module paths like `Base` or
+    /// `Base.Iterators`.
+    ///
+    /// The module is rooted in `target`, which must be a rooting target like a mutable reference
+    /// to a frame.
+    pub fn new<'target, Tgt>(target: Tgt, imports: &[&str]) -> JlrsResult<Sandbox<'target>>
+    where
+        Tgt:
+            Target<'target> + TargetType<'target, Data<'static, Module<'target>> = Module<'target>>,
+    {
+        for import in imports.iter().copied() {
+            if !is_module_path(import) {
+                Err(AccessError::ModuleNotFound {
+                    module: import.into(),
+                })?
+            }
+        }
+
+        target.with_local_scope::<_, _, 1>(|target, mut frame| unsafe {
+            let name = Symbol::new(&frame, "Sandbox").as_value();
+            let std_imports = Value::false_v(&frame);
+            let module = Module::core(&frame)
+                .global(&frame, "Module")?
+                .as_value()
+                .call2(&mut frame, name, std_imports)
+                .into_jlrs_result()?
+                .cast::<Module>()?;
+
+            // The results of the imports are not used, so they're not rooted.
+            let sandbox = Sandbox { module };
+            for import in imports {
+                sandbox
+                    .eval_string(&frame, format!("using {import}"))?
+                    .map_err(|e| e.as_value())
+                    .into_jlrs_result()?;
+            }
+
+            Ok(Sandbox {
+                module: module.root(target),
+            })
+        })
+    }
+
+    /// Returns the module of this sandbox.
+    #[inline]
+    pub fn module(self) -> Module<'scope> {
+        self.module
+    }
+
+    /// Evaluate `code` in this sandbox with `Base.include_string`, the result of the last
+    /// expression is returned.
+    ///
+    /// # Safety
+    ///
+    /// The code can't be checked for correctness, nothing prevents you from causing a
+    /// segmentation fault with code like `unsafe_load(Ptr{Float64}(C_NULL))`.
+    pub unsafe fn eval_string<'target, C, Tgt>(
+        self,
+        target: Tgt,
+        code: C,
+    ) -> JlrsResult<ValueResult<'target, 'static, Tgt>>
+    where
+        C: AsRef<str>,
+        Tgt: Target<'target>,
+    {
+        target.with_local_scope::<_, _, 1>(|target, mut frame| {
+            let code = JuliaString::new(&mut frame, code);
+            let include_string =
+                Module::typed_global_cached::<Value, _, _>(&frame, "Base.include_string")?;
+
+            Ok(include_string.call2(target, self.module.as_value(), code.as_value()))
+        })
+    }
+
+    /// Evaluate the contents of the file at `path` in this sandbox with `Base.include`, the
+    /// result of the last expression is returned.
+    ///
+    /// # Safety
+    ///
+    /// The content of the file can't be checked for correctness, nothing prevents you from
+    /// causing a segmentation fault with code like `unsafe_load(Ptr{Float64}(C_NULL))`.
+    pub unsafe fn include<'target, P, Tgt>(
+        self,
+        target: Tgt,
+        path: P,
+    ) -> JlrsResult<ValueResult<'target, 'static, Tgt>>
+    where
+        P: AsRef<Path>,
+        Tgt: Target<'target>,
+    {
+        if !path.as_ref().exists() {
+            Err(IOError::NotFound {
+                path: path.as_ref().to_string_lossy().into(),
+            })?
+        }
+
+        target.with_local_scope::<_, _, 1>(|target, mut frame| {
+            let path = JuliaString::new(&mut frame, path.as_ref().to_string_lossy());
+            let include = Module::typed_global_cached::<Value, _, _>(&frame, "Base.include")?;
+
+            Ok(include.call2(target, self.module.as_value(), path.as_value()))
+        })
+    }
+}
+
+// Returns `true` if `path` is a dot-separated list of identifiers.
+fn is_module_path(path: &str) -> bool {
+    path.split('.').all(|ident| {
+        let mut chars = ident.chars();
+        match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                chars.all(|c| c.is_alphanumeric() || c == '_')
+            }
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_module_path;
+
+    #[test]
+    fn module_paths() {
+        assert!(is_module_path("Base"));
+        assert!(is_module_path("Base.Iterators"));
+        assert!(is_module_path("_Private1"));
+        assert!(!is_module_path(""));
+        assert!(!is_module_path("Base."));
+        assert!(!is_module_path("1Module"));
+        assert!(!is_module_path("Base; rm(\"/\")"));
+    }
+}
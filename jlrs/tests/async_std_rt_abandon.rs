@@ -0,0 +1,70 @@
+#[cfg(all(feature = "async-std-rt",))]
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        time::{Duration, Instant},
+    };
+
+    use jlrs::{prelude::*, runtime::async_rt::shutdown::ShutdownMode};
+
+    // Never completes, the runtime must drop it when it shuts down.
+    struct PendingTask;
+
+    #[async_trait(?Send)]
+    impl AsyncTask for PendingTask {
+        type Output = ();
+        type Affinity = DispatchAny;
+
+        async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+            let _value = Value::new(&mut frame, 1usize);
+            futures::future::pending::<()>().await;
+            Ok(())
+        }
+    }
+
+    fn init() -> AsyncJulia<AsyncStd> {
+        unsafe {
+            RuntimeBuilder::new()
+                .async_runtime::<AsyncStd>()
+                .n_threads(4)
+                .channel_capacity(NonZeroUsize::new_unchecked(32))
+                .start::<2>()
+                .expect("Could not init Julia")
+                .0
+        }
+    }
+
+    // Julia can only be initialized once per process, so everything is tested in a single test.
+    #[test]
+    fn abandoned_tasks_are_dropped() {
+        let julia = init();
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        julia.task(PendingTask, sender).try_dispatch_any().unwrap();
+
+        let (sender2, receiver2) = crossbeam_channel::bounded(1);
+        julia.task(PendingTask, sender2).try_dispatch_any().unwrap();
+
+        // Give the runtime some time to start both tasks.
+        std::thread::sleep(Duration::from_millis(500));
+
+        let deadline = Instant::now() + Duration::from_millis(500);
+        let summary = futures::executor::block_on(julia.shutdown(ShutdownMode::Discard {
+            deadline: Some(deadline),
+        }))
+        .expect("Could not shut down");
+
+        assert_eq!(summary.abandoned(), 2);
+
+        // The abandoned tasks have been dropped, including the senders of their results.
+        assert!(receiver
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap_err()
+            .is_disconnected());
+        assert!(receiver2
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap_err()
+            .is_disconnected());
+    }
+}
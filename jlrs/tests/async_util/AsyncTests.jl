@@ -34,4 +34,15 @@ function complexfunc(dims::Int, iters::Int)::Float64
 
     z
 end
-end
\ No newline at end of file
+
+function sleepyfunc(secs::Float64)::Float64
+    sleep(secs)
+    secs
+end
+function doubled(input::Channel{Int})::Channel{Int}
+    Channel{Int}(ch -> foreach(x -> put!(ch, 2x), input))
+end
+
+const bridgein = Channel{Int}(4)
+const bridgeout = doubled(bridgein)
+end
@@ -1,4 +1,4 @@
-use jlrs::{memory::gc::Gc, prelude::*};
+use jlrs::{error::JlrsError, memory::gc::Gc, prelude::*};
 
 pub struct MyTask {
     pub dims: isize,
@@ -477,6 +477,60 @@ impl PersistentTask for AccumulatorTask {
     }
 }
 
+pub struct PanickingTask {
+    pub n_inits: usize,
+}
+
+#[async_trait(?Send)]
+impl PersistentTask for PanickingTask {
+    type State<'state> = ();
+    type Input = isize;
+    type Output = usize;
+    type Affinity = DispatchAny;
+
+    async fn init<'frame>(&mut self, _frame: AsyncGcFrame<'frame>) -> JlrsResult<()> {
+        self.n_inits += 1;
+        Ok(())
+    }
+
+    async fn run<'frame, 'state: 'frame>(
+        &mut self,
+        _frame: AsyncGcFrame<'frame>,
+        _state: &mut Self::State<'state>,
+        input: Self::Input,
+    ) -> JlrsResult<Self::Output> {
+        if input < 0 {
+            panic!("negative input");
+        }
+
+        Ok(self.n_inits)
+    }
+}
+
+// `init` always fails.
+pub struct FailingInitTask;
+
+#[async_trait(?Send)]
+impl PersistentTask for FailingInitTask {
+    type State<'state> = ();
+    type Input = ();
+    type Output = ();
+    type Affinity = DispatchAny;
+
+    async fn init<'frame>(&mut self, _frame: AsyncGcFrame<'frame>) -> JlrsResult<()> {
+        Err(JlrsError::exception("init failed"))?
+    }
+
+    async fn run<'frame, 'state: 'frame>(
+        &mut self,
+        _frame: AsyncGcFrame<'frame>,
+        _state: &mut Self::State<'state>,
+        _input: Self::Input,
+    ) -> JlrsResult<Self::Output> {
+        Ok(())
+    }
+}
+
 pub struct LocalTask {
     pub dims: isize,
     pub iters: isize,
@@ -889,3 +943,60 @@ impl AsyncTask for BorrowArrayData {
         Ok(v)
     }
 }
+
+pub struct SleepingTask {
+    pub secs: f64,
+}
+
+#[async_trait(?Send)]
+impl AsyncTask for SleepingTask {
+    type Output = f64;
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let secs = Value::new(&mut frame, self.secs);
+
+        let v = unsafe {
+            Module::main(&frame)
+                .submodule(&frame, "AsyncTests")?
+                .as_managed()
+                .function(&frame, "sleepyfunc")?
+                .as_managed()
+                .call_async(&mut frame, [secs])
+                .await
+                .into_jlrs_result()?
+                .unbox::<f64>()?
+        };
+
+        Ok(v)
+    }
+}
+
+pub struct TimeoutTask {
+    pub secs: f64,
+    pub timeout: std::time::Duration,
+}
+
+#[async_trait(?Send)]
+impl AsyncTask for TimeoutTask {
+    type Output = f64;
+    type Affinity = DispatchAny;
+
+    async fn run<'base>(&mut self, mut frame: AsyncGcFrame<'base>) -> JlrsResult<Self::Output> {
+        let secs = Value::new(&mut frame, self.secs);
+
+        let v = unsafe {
+            Module::main(&frame)
+                .submodule(&frame, "AsyncTests")?
+                .as_managed()
+                .function(&frame, "sleepyfunc")?
+                .as_managed()
+                .call_async_with_timeout(&mut frame, [secs], self.timeout)
+                .await?
+                .into_jlrs_result()?
+                .unbox::<f64>()?
+        };
+
+        Ok(v)
+    }
+}
@@ -193,11 +193,22 @@ mod tests {
         })
     }
 
+    fn ccall_catch_panic() {
+        assert_eq!(CCall::catch_panic(|| 1).unwrap(), 1);
+
+        let panic = CCall::catch_panic(|| -> i32 { panic!("caught panic") })
+            .expect_err("panic was not caught");
+        assert_eq!(panic.message(), "caught panic");
+        assert!(panic.location().unwrap().contains("ccall.rs"));
+        assert!(panic.to_string().starts_with("Rust panicked at"));
+    }
+
     #[test]
     fn ccall_tests() {
         ccall_with_array();
         ccall_with_array_and_scope();
         ccall_with_array_and_reallocated_scope_with_slots();
+        ccall_catch_panic();
         #[cfg(feature = "uv")]
         ccall_with_async_condition();
     }
@@ -0,0 +1,85 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "internal-types"))]
+mod tests {
+    use jlrs::{data::managed::internal::expr::Expr, prelude::*, sandbox::Sandbox};
+
+    use super::util::JULIA;
+
+    fn build_and_eval_function() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let sandbox = Sandbox::new(&mut frame, &["Base"])?;
+
+                    // function add_one(x)
+                    //     y = x + 1
+                    //     y
+                    // end
+                    let x = Symbol::new(&frame, "x").as_value();
+                    let y = Symbol::new(&frame, "y").as_value();
+                    let plus = Symbol::new(&frame, "+").as_value();
+                    let one = Value::new(&mut frame, 1isize);
+                    let sum = Expr::new_call(&mut frame, plus, &[x, one])?;
+                    let assign = Expr::new_assignment(&mut frame, y, sum.as_value())?;
+                    let body = Expr::new_block(&mut frame, &[assign.as_value(), y])?;
+                    let func = Expr::new_function(&mut frame, "add_one", &[x], body.as_value())?;
+                    assert_eq!(func.head().unwrap().as_str()?, "function");
+
+                    func.eval(&mut frame, sandbox.module())?
+                        .into_jlrs_result()?;
+
+                    let arg = Value::new(&mut frame, 41isize);
+                    let res = sandbox
+                        .module()
+                        .function(&frame, "add_one")?
+                        .as_managed()
+                        .call1(&mut frame, arg)
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(res, 42);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn literals_are_not_evaluated() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let main = Module::main(&frame);
+                    let base = Module::base(&frame);
+
+                    // A string that would be code if it were formatted into a command.
+                    let s = JuliaString::new(&mut frame, "\"); exit(); (\"").as_value();
+                    let length = Expr::global_ref(&mut frame, base, "length")?;
+                    let call = Expr::new_call(&mut frame, length, &[s])?;
+                    let len = call
+                        .eval(&mut frame, main)?
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(len, 14);
+
+                    let sym = Symbol::new(&frame, "not_a_variable").as_value();
+                    let quoted = Expr::quote_node(&mut frame, sym)?;
+                    let block = Expr::new_block(&mut frame, &[quoted])?;
+                    let res = block.eval(&mut frame, main)?.into_jlrs_result()?;
+                    assert!(res.is::<Symbol>());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn expr_tests() {
+        build_and_eval_function();
+        literals_are_not_evaluated();
+    }
+}
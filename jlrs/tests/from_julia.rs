@@ -0,0 +1,257 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use jlrs::{
+        convert::from_julia::{nth_field_from_julia, ConversionPath, FromJulia},
+        error::{JlrsError, TypeError},
+        prelude::*,
+    };
+
+    use crate::util::JULIA;
+
+    fn convert_primitives_and_strings() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::new(&mut frame, 3.0f64);
+                    assert_eq!(f64::from_julia(&frame, v)?, 3.0);
+                    assert!(i64::from_julia(&frame, v).is_err());
+
+                    let v = Value::new(&mut frame, true);
+                    assert!(bool::from_julia(&frame, v)?);
+
+                    let v = Value::eval_string(&mut frame, "\"foo\"").into_jlrs_result()?;
+                    assert_eq!(String::from_julia(&frame, v)?, "foo");
+
+                    let v = Value::eval_string(&mut frame, ":bar").into_jlrs_result()?;
+                    assert_eq!(String::from_julia(&frame, v)?, "bar");
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_vectors() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "[\"a\", \"b\"]").into_jlrs_result()?;
+                    assert_eq!(Vec::<String>::from_julia(&frame, v)?, ["a", "b"]);
+
+                    let v = Value::eval_string(&mut frame, "[[1, 2], Int[], [3]]")
+                        .into_jlrs_result()?;
+                    let data = Vec::<Vec<i64>>::from_julia(&frame, v)?;
+                    assert_eq!(data, vec![vec![1, 2], vec![], vec![3]]);
+
+                    let v = Value::eval_string(&mut frame, "Union{Nothing, Int32}[1, nothing]")
+                        .into_jlrs_result()?;
+                    assert_eq!(Vec::<Option<i32>>::from_julia(&frame, v)?, [Some(1), None]);
+
+                    let v = Value::eval_string(&mut frame, "Set([1, 2, 2])").into_jlrs_result()?;
+                    let set = BTreeSet::<i64>::from_julia(&frame, v)?;
+                    assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 2]);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_dicts() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(
+                        &mut frame,
+                        "Dict{Symbol, Any}(:a => 1.0, :b => nothing)",
+                    )
+                    .into_jlrs_result()?;
+                    let map = HashMap::<String, Option<f64>>::from_julia(&frame, v)?;
+                    assert_eq!(map.len(), 2);
+                    assert_eq!(map["a"], Some(1.0));
+                    assert_eq!(map["b"], None);
+
+                    let v = Value::eval_string(&mut frame, "(x = [1.0, 2.0], y = Float64[])")
+                        .into_jlrs_result()?;
+                    let map = HashMap::<String, Vec<f64>>::from_julia(&frame, v)?;
+                    assert_eq!(map["x"], [1.0, 2.0]);
+                    assert!(map["y"].is_empty());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_tuples() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v =
+                        Value::eval_string(&mut frame, "(1, \"a\", [2.0])").into_jlrs_result()?;
+                    let data = <(i64, String, Vec<f64>)>::from_julia(&frame, v)?;
+                    assert_eq!(data, (1, String::from("a"), vec![2.0]));
+
+                    assert!(<(i64, String)>::from_julia(&frame, v).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn error_contains_path() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "Dict(\"a\" => [\"x\", 1])")
+                        .into_jlrs_result()?;
+                    let err = HashMap::<String, Vec<String>>::from_julia_named(&frame, v, "x")
+                        .unwrap_err();
+
+                    match *err {
+                        JlrsError::TypeError(TypeError::UnexpectedTypeAt { ref path, .. }) => {
+                            assert!(path.starts_with("x["));
+                            assert!(path.ends_with("][2]"));
+                        }
+                        _ => panic!("unexpected error: {}", err),
+                    }
+
+                    // The path is restored if a field doesn't exist.
+                    let t = Value::eval_string(&mut frame, "(1, 2)").into_jlrs_result()?;
+                    let mut path = ConversionPath::new("t");
+                    assert!(nth_field_from_julia::<i64, _>(&frame, t, 2, &mut path).is_err());
+                    assert!(path.segments().is_empty());
+                    assert_eq!(nth_field_from_julia::<i64, _>(&frame, t, 1, &mut path)?, 2);
+
+                    // The path is restored if an element or entry can't be converted.
+                    let mut path = ConversionPath::new("v");
+                    let v = Value::eval_string(&mut frame, "[\"x\", 1]").into_jlrs_result()?;
+                    assert!(Vec::<String>::from_julia_at(&frame, v, &mut path).is_err());
+                    assert!(path.segments().is_empty());
+
+                    let d =
+                        Value::eval_string(&mut frame, "Dict(\"a\" => 1)").into_jlrs_result()?;
+                    assert!(
+                        HashMap::<String, String>::from_julia_at(&frame, d, &mut path).is_err()
+                    );
+                    assert!(path.segments().is_empty());
+
+                    let nt = Value::eval_string(&mut frame, "(a = 1,)").into_jlrs_result()?;
+                    assert!(
+                        HashMap::<String, String>::from_julia_at(&frame, nt, &mut path).is_err()
+                    );
+                    assert!(path.segments().is_empty());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    #[derive(FromJulia, Debug, PartialEq)]
+    struct Point {
+        label: String,
+        coords: (f64, f64),
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    #[derive(FromJulia, Debug, PartialEq)]
+    struct Shape {
+        name: Option<String>,
+        points: Vec<Point>,
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    fn convert_derived() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(
+                        &mut frame,
+                        "(name = nothing, points = [(label = \"a\", coords = (1.0, 2.0))])",
+                    )
+                    .into_jlrs_result()?;
+
+                    let shape = Shape::from_julia(&frame, v)?;
+                    assert_eq!(
+                        shape,
+                        Shape {
+                            name: None,
+                            points: vec![Point {
+                                label: String::from("a"),
+                                coords: (1.0, 2.0)
+                            }]
+                        }
+                    );
+
+                    let v = Value::eval_string(
+                        &mut frame,
+                        "(name = \"s\", points = [
+                            (label = \"a\", coords = (1.0, 2.0)),
+                            (label = \"b\", coords = (1.0, 2.0)),
+                            (label = 3, coords = (1.0, 2.0))
+                        ])",
+                    )
+                    .into_jlrs_result()?;
+
+                    let err = Shape::from_julia_named(&frame, v, "x").unwrap_err();
+                    match *err {
+                        JlrsError::TypeError(TypeError::UnexpectedTypeAt {
+                            ref path,
+                            ref found,
+                            ..
+                        }) => {
+                            assert_eq!(path, "x.points[3].label");
+                            assert_eq!(found, "Int64");
+                        }
+                        _ => panic!("unexpected error: {}", err),
+                    }
+
+                    let v = Value::eval_string(&mut frame, "(name = \"s\",)").into_jlrs_result()?;
+                    let err = Shape::from_julia_named(&frame, v, "x").unwrap_err();
+                    assert!(matches!(
+                        *err,
+                        JlrsError::TypeError(TypeError::MissingFieldAt { .. })
+                    ));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn from_julia_tests() {
+        convert_primitives_and_strings();
+        convert_vectors();
+        convert_dicts();
+        convert_tuples();
+        error_contains_path();
+        #[cfg(feature = "jlrs-derive")]
+        convert_derived();
+    }
+}
@@ -0,0 +1,118 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "jlrs-derive"))]
+mod tests {
+    use jlrs::{
+        convert::from_julia_exception::{MapException, MappedException},
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    #[derive(FromJuliaException, Debug, PartialEq)]
+    enum MappedError {
+        // Never defined, mapping must skip this variant.
+        #[jlrs(julia_type = "Main.JlrsUndefinedError")]
+        Undefined,
+        #[jlrs(julia_type = "Base.ArgumentError")]
+        Argument { msg: String },
+        #[jlrs(julia_type = "Base.DomainError")]
+        Domain { val: f64, msg: String },
+        #[jlrs(julia_type = "Base.KeyError")]
+        Key(String),
+        #[jlrs(julia_type = "Main.JlrsCustomError")]
+        Custom { code: i64 },
+        #[jlrs(julia_type = "Base.DivideError")]
+        DivideByZero,
+    }
+
+    fn map_exceptions() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct JlrsCustomError <: Exception\n    code::Int\nend",
+                    )
+                    .into_jlrs_result()?;
+
+                    let res = Value::eval_string(&mut frame, "throw(ArgumentError(\"bad\"))")
+                        .map_exception::<MappedError>()?;
+                    assert!(matches!(
+                        res,
+                        Err(MappedException::Matched(MappedError::Argument { ref msg })) if msg == "bad"
+                    ));
+
+                    let res = Value::eval_string(&mut frame, "sqrt(-1.0)")
+                        .map_exception::<MappedError>()?;
+                    match res {
+                        Err(MappedException::Matched(MappedError::Domain { val, .. })) => {
+                            assert_eq!(val, -1.0)
+                        }
+                        _ => panic!("expected a DomainError"),
+                    }
+
+                    let res = Value::eval_string(&mut frame, "Dict(\"a\" => 1)[\"b\"]")
+                        .map_exception::<MappedError>()?;
+                    assert!(matches!(
+                        res,
+                        Err(MappedException::Matched(MappedError::Key(ref key))) if key == "b"
+                    ));
+
+                    let res = Value::eval_string(&mut frame, "throw(JlrsCustomError(3))")
+                        .map_exception::<MappedError>()?;
+                    assert!(matches!(
+                        res,
+                        Err(MappedException::Matched(MappedError::Custom { code: 3 }))
+                    ));
+
+                    let res = Value::eval_string(&mut frame, "div(1, 0)")
+                        .map_exception::<MappedError>()?;
+                    assert!(matches!(
+                        res,
+                        Err(MappedException::Matched(MappedError::DivideByZero))
+                    ));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn unmatched_exception() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let res = Value::eval_string(&mut frame, "error(\"unmatched\")")
+                        .map_exception::<MappedError>()?;
+                    match res {
+                        Err(MappedException::Unmatched(exc)) => {
+                            assert_eq!(exc.datatype_name()?, "ErrorException")
+                        }
+                        _ => panic!("expected an unmatched exception"),
+                    }
+
+                    let res =
+                        Value::eval_string(&mut frame, "1 + 1").map_exception::<MappedError>()?;
+                    assert_eq!(res.unwrap().unbox::<isize>()?, 2);
+
+                    // KeyError with a key that can't be converted to a String.
+                    assert!(Value::eval_string(&mut frame, "Dict(1 => 1)[2]")
+                        .map_exception::<MappedError>()
+                        .is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn from_julia_exception_tests() {
+        map_exceptions();
+        unmatched_exception();
+    }
+}
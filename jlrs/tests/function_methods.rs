@@ -0,0 +1,124 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "internal-types"))]
+mod tests {
+    use jlrs::{
+        data::{
+            layout::tuple::{Tuple1, Tuple2},
+            managed::union_all::UnionAll,
+        },
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    const DEFINE_FUNCTION: &str = "function method_info_fn(x::Int, y::Float64)
+    x + y
+end
+method_info_fn(x::T) where {T <: Integer} = x
+method_info_fn(x::Number) = x";
+
+    fn list_methods() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTION).into_jlrs_result()?;
+                    let func = Module::main(&frame)
+                        .function(&frame, "method_info_fn")?
+                        .as_managed();
+
+                    let methods = func.methods(&mut frame)?;
+                    assert_eq!(methods.len(), 3);
+
+                    let two_args = methods
+                        .iter()
+                        .find(|m| m.argument_names().len() == 2)
+                        .expect("no method with two arguments");
+                    assert_eq!(two_args.argument_names(), &["x", "y"]);
+                    assert_eq!(two_args.module(), Module::main(&frame));
+                    assert_eq!(two_args.line(), 1);
+                    assert!(two_args.signature().is::<DataType>());
+
+                    let parametric = methods
+                        .iter()
+                        .find(|m| m.signature().is::<UnionAll>())
+                        .expect("no parametric method");
+                    assert_eq!(parametric.argument_names(), &["x"]);
+                    assert_eq!(parametric.line(), 4);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn method_applicable() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTION).into_jlrs_result()?;
+                    let func = Module::main(&frame)
+                        .function(&frame, "method_info_fn")?
+                        .as_managed();
+
+                    let int = DataType::int64_type(&frame).as_value();
+                    let float = DataType::float64_type(&frame).as_value();
+                    let string = DataType::string_type(&frame).as_value();
+
+                    assert!(func.applicable(&frame, &[int, float])?);
+                    assert!(func.applicable(&frame, &[float])?);
+                    assert!(!func.applicable(&frame, &[string])?);
+                    assert!(!func.applicable(&frame, &[float, int])?);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn most_specific_method() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTION).into_jlrs_result()?;
+                    let func = Module::main(&frame)
+                        .function(&frame, "method_info_fn")?
+                        .as_managed();
+
+                    let method = func
+                        .most_specific_method::<Tuple1<i64>, _>(&mut frame)?
+                        .expect("no method found");
+                    assert_eq!(method.line(), 4);
+
+                    let method = func
+                        .most_specific_method::<Tuple1<f32>, _>(&mut frame)?
+                        .expect("no method found");
+                    assert_eq!(method.line(), 5);
+
+                    let method = func
+                        .most_specific_method::<Tuple2<i64, f64>, _>(&mut frame)?
+                        .expect("no method found");
+                    assert_eq!(method.argument_names(), &["x", "y"]);
+
+                    assert!(func
+                        .most_specific_method::<Tuple2<f64, f64>, _>(&mut frame)?
+                        .is_none());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn function_methods_tests() {
+        list_methods();
+        method_applicable();
+        most_specific_method();
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        error::{JlrsError, RuntimeError},
+        prelude::*,
+        runtime::options::{CheckBounds, CompileMode},
+    };
+
+    #[test]
+    fn init_with_options() {
+        unsafe {
+            let err = RuntimeBuilder::new()
+                .optimization_level(4)
+                .start()
+                .err()
+                .expect("Invalid optimization level was accepted");
+
+            assert!(matches!(
+                *err,
+                JlrsError::RuntimeError(RuntimeError::InvalidOption { .. })
+            ));
+
+            let err = RuntimeBuilder::new()
+                .preload(["Not A Package"])
+                .start()
+                .err()
+                .expect("Invalid package name was accepted");
+
+            assert!(matches!(
+                *err,
+                JlrsError::RuntimeError(RuntimeError::InvalidOption { .. })
+            ));
+
+            // Julia has not been initialized yet because the options were invalid.
+            let mut julia = RuntimeBuilder::new()
+                .optimization_level(1)
+                .check_bounds(CheckBounds::Yes)
+                .compile(CompileMode::Yes)
+                .startup_file(false)
+                .preload(["LinearAlgebra"])
+                .start()
+                .expect("Could not init Julia");
+
+            let mut frame = StackFrame::new();
+            julia
+                .instance(&mut frame)
+                .scope(|mut frame| {
+                    let opt_level =
+                        Value::eval_string(&mut frame, "Int(Base.JLOptions().opt_level)")
+                            .into_jlrs_result()?
+                            .unbox::<isize>()?;
+                    assert_eq!(opt_level, 1);
+
+                    let check_bounds =
+                        Value::eval_string(&mut frame, "Int(Base.JLOptions().check_bounds)")
+                            .into_jlrs_result()?
+                            .unbox::<isize>()?;
+                    assert_eq!(check_bounds, 1);
+
+                    let startup_file =
+                        Value::eval_string(&mut frame, "Int(Base.JLOptions().startupfile)")
+                            .into_jlrs_result()?
+                            .unbox::<isize>()?;
+                    assert_eq!(startup_file, 2);
+
+                    let preloaded =
+                        Value::eval_string(&mut frame, "isdefined(Main, :LinearAlgebra)")
+                            .into_jlrs_result()?
+                            .unbox::<bool>()?
+                            .as_bool();
+                    assert!(preloaded);
+
+                    Ok(())
+                })
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "sync-rt")]
+mod tests {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use jlrs::{prelude::*, InstallJlrsCore};
+
+    // Returns the path of the installed JlrsCore package and the active project.
+    fn jlrs_core_and_project() -> (PathBuf, PathBuf) {
+        let output = Command::new("julia")
+            .args([
+                "--startup-file=no",
+                "-e",
+                "import JlrsCore; println(pkgdir(JlrsCore)); print(Base.active_project())",
+            ])
+            .output()
+            .expect("Could not run julia");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let mut lines = stdout.lines();
+        let jlrs_core = PathBuf::from(lines.next().unwrap());
+        let project = PathBuf::from(lines.next().unwrap());
+        (jlrs_core, project)
+    }
+
+    #[test]
+    fn install_jlrs_core_path() {
+        let (jlrs_core, project) = jlrs_core_and_project();
+        let manifest = project.with_file_name("Manifest.toml");
+        let project_contents = fs::read(&project).ok();
+        let manifest_contents = fs::read(&manifest).ok();
+
+        let mut julia = unsafe {
+            RuntimeBuilder::new()
+                .install_jlrs(InstallJlrsCore::Path {
+                    path: jlrs_core.clone(),
+                })
+                .start()
+                .expect("Could not init Julia")
+        };
+
+        let mut frame = StackFrame::new();
+        julia
+            .instance(&mut frame)
+            .scope(|mut frame| {
+                let loaded_from = unsafe {
+                    Value::eval_string(&mut frame, "pkgdir(JlrsCore)")
+                        .into_jlrs_result()?
+                        .cast::<JuliaString>()?
+                        .as_str()?
+                        .to_string()
+                };
+                assert_eq!(
+                    fs::canonicalize(loaded_from).unwrap(),
+                    fs::canonicalize(&jlrs_core).unwrap()
+                );
+
+                // The path has been removed from LOAD_PATH after loading JlrsCore.
+                let path = JuliaString::new(&mut frame, jlrs_core.to_string_lossy());
+                let in_load_path = unsafe {
+                    Value::eval_string(&mut frame, "p -> p in LOAD_PATH")
+                        .into_jlrs_result()?
+                        .call1(&mut frame, path.as_value())
+                        .into_jlrs_result()?
+                        .unbox::<bool>()?
+                        .as_bool()
+                };
+                assert!(!in_load_path);
+
+                Ok(())
+            })
+            .unwrap();
+
+        // The active environment has not been modified.
+        assert_eq!(fs::read(&project).ok(), project_contents);
+        assert_eq!(fs::read(&manifest).ok(), manifest_contents);
+    }
+}
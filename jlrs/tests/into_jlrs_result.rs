@@ -1,7 +1,7 @@
 mod util;
 #[cfg(feature = "sync-rt")]
 mod tests {
-    use jlrs::prelude::*;
+    use jlrs::{error::JlrsError, prelude::*};
 
     use super::util::JULIA;
 
@@ -35,9 +35,67 @@ mod tests {
         });
     }
 
+    fn exc_with_backtrace() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "function jlrs_throws_bounds_error(v)\n    v[4]\nend",
+                    )
+                    .into_jlrs_result()?;
+
+                    let err = Value::eval_string(&mut frame, "jlrs_throws_bounds_error([1, 2, 3])")
+                        .into_jlrs_result_with_backtrace()
+                        .expect_err("no exception was thrown");
+
+                    match *err {
+                        JlrsError::JuliaException(ref exc) => {
+                            assert_eq!(exc.type_name(), "BoundsError");
+                            assert!(exc.message().contains("BoundsError"));
+                            assert_eq!(exc.field("a"), Some("[1, 2, 3]"));
+                            assert_eq!(exc.field("i"), Some("(4,)"));
+                            assert!(exc
+                                .backtrace()
+                                .iter()
+                                .any(|frame| frame.function() == "jlrs_throws_bounds_error"
+                                    && frame.line() == 2));
+                        }
+                        _ => panic!("Expected a JuliaException, got {}", err),
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn ok_with_backtrace() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = Value::eval_string(&mut frame, "1 + 1")
+                        .into_jlrs_result_with_backtrace()?
+                        .unbox::<isize>()?;
+                    assert_eq!(v, 2);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
     #[test]
     fn test_into_jlrs_result() {
         ok_to_jlrs_result();
         exc_to_jlrs_result();
+        exc_with_backtrace();
+        ok_with_backtrace();
     }
 }
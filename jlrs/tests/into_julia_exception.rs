@@ -0,0 +1,92 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "jlrs-derive"))]
+mod tests {
+    use jlrs::{
+        convert::{
+            from_julia_exception::FromJuliaException, into_julia_exception::IntoJuliaException,
+        },
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    #[derive(IntoJuliaException, FromJuliaException, Debug)]
+    enum LookupError {
+        #[jlrs(julia_type = "Base.ArgumentError")]
+        InvalidName(String),
+        #[jlrs(julia_type = "Base.DomainError")]
+        Negative { val: i64, msg: String },
+        #[jlrs(julia_type = "Main.JlrsNotFoundError")]
+        NotFound { id: i64 },
+        #[jlrs(julia_type = "Base.DivideError")]
+        DivideByZero,
+    }
+
+    #[derive(IntoJuliaException, Debug)]
+    enum InvalidError {
+        #[jlrs(julia_type = "Main.JlrsUndefinedError")]
+        Undefined,
+    }
+
+    fn convert_to_exception() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct JlrsNotFoundError <: Exception\n    id::Int\nend",
+                    )
+                    .into_jlrs_result()?;
+
+                    let errors = [
+                        (LookupError::InvalidName("bad".into()), "ArgumentError"),
+                        (
+                            LookupError::Negative {
+                                val: -1,
+                                msg: "negative".into(),
+                            },
+                            "DomainError",
+                        ),
+                        (LookupError::NotFound { id: 3 }, "JlrsNotFoundError"),
+                        (LookupError::DivideByZero, "DivideError"),
+                    ];
+
+                    for (error, type_name) in errors {
+                        let expected = format!("{:?}", error);
+                        let exception = error.into_julia_exception(&mut frame)?;
+                        assert_eq!(exception.datatype_name()?, type_name);
+
+                        let converted = LookupError::from_julia_exception(&frame, exception)?
+                            .expect("exception was not converted");
+                        assert_eq!(format!("{:?}", converted), expected);
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_to_undefined_exception() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    assert!(InvalidError::Undefined
+                        .into_julia_exception(&mut frame)
+                        .is_err());
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn into_julia_exception_tests() {
+        convert_to_exception();
+        convert_to_undefined_exception();
+    }
+}
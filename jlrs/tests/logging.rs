@@ -0,0 +1,121 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "log"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use jlrs::{
+        logging::{install_logger, set_min_level, uninstall_logger, LogBackend, LogLevel},
+        prelude::*,
+    };
+    use log::{kv::VisitSource, Level, Log, Metadata, Record};
+
+    use super::util::JULIA;
+
+    #[derive(Debug, PartialEq)]
+    struct CapturedRecord {
+        level: Level,
+        target: String,
+        message: String,
+        key_values: Vec<(String, String)>,
+    }
+
+    struct Collect<'a>(&'a mut Vec<(String, String)>);
+
+    impl<'kvs> VisitSource<'kvs> for Collect<'_> {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    struct CapturingLogger(Mutex<Vec<CapturedRecord>>);
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let mut key_values = Vec::new();
+            record
+                .key_values()
+                .visit(&mut Collect(&mut key_values))
+                .unwrap();
+
+            self.0.lock().unwrap().push(CapturedRecord {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+                key_values,
+            });
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+
+    fn eval(julia: &mut Julia, cmd: &str) {
+        julia
+            .scope(|mut frame| unsafe {
+                Value::eval_string(&mut frame, cmd).into_jlrs_result()?;
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn forward_to_log() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Info);
+
+            julia
+                .scope(|frame| install_logger(&frame, LogBackend::Log, log::max_level().into()))
+                .unwrap();
+
+            eval(&mut julia, "@debug \"hidden\"; @info \"hello\" x = 1");
+            eval(&mut julia, "@warn \"careful\"");
+
+            {
+                let records = LOGGER.0.lock().unwrap();
+                assert_eq!(records.len(), 2);
+                assert_eq!(
+                    records[0],
+                    CapturedRecord {
+                        level: Level::Info,
+                        target: "Main".into(),
+                        message: "hello".into(),
+                        key_values: vec![("x".into(), "1".into())],
+                    }
+                );
+                assert_eq!(records[1].level, Level::Warn);
+                assert_eq!(records[1].message, "careful");
+            }
+
+            log::set_max_level(log::LevelFilter::Debug);
+            julia
+                .scope(|frame| set_min_level(&frame, LogLevel::Debug))
+                .unwrap();
+            eval(&mut julia, "@debug \"visible\"");
+            assert_eq!(LOGGER.0.lock().unwrap()[2].level, Level::Debug);
+
+            julia.scope(|frame| uninstall_logger(&frame)).unwrap();
+            assert!(julia
+                .scope(|frame| set_min_level(&frame, LogLevel::Info))
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn logging_tests() {
+        forward_to_log();
+    }
+}
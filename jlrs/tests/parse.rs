@@ -0,0 +1,98 @@
+mod util;
+#[cfg(all(feature = "sync-rt", feature = "internal-types"))]
+mod tests {
+    use jlrs::{data::managed::internal::expr::Expr, error::JlrsError, prelude::*};
+
+    use super::util::JULIA;
+
+    fn parse_valid_code() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let expr =
+                        Expr::parse(&mut frame, "parsed_not_evaluated = 1 + 2", "script.jl")?;
+                    assert_eq!(expr.head().unwrap().as_str()?, "toplevel");
+
+                    // Parsing doesn't evaluate the code.
+                    assert!(Module::main(&frame)
+                        .global(&frame, "parsed_not_evaluated")
+                        .is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn parse_invalid_code() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let err = Expr::parse(&mut frame, "a = 1\nb = (2 +\n", "script.jl")
+                        .expect_err("Invalid code was parsed");
+
+                    match *err {
+                        JlrsError::ParseError(ref err) => {
+                            assert_eq!(err.filename(), "script.jl");
+                            assert!(err.line() >= 2);
+                            assert!(!err.message().is_empty());
+                        }
+                        _ => panic!("Expected a ParseError, got {}", err),
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn eval_with_location() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let main = Module::main(&frame);
+                    let v = Value::eval_with_location(&mut frame, main, "1 + 2", "script.jl")?
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(v, 3);
+
+                    let file = Value::eval_with_location(
+                        &mut frame,
+                        main,
+                        "\nString(@__FILE__)",
+                        "script.jl",
+                    )?
+                    .into_jlrs_result()?
+                    .unbox::<String>()?
+                    .unwrap();
+                    assert_eq!(file, "script.jl");
+
+                    let err = Value::eval_with_location(
+                        &mut frame,
+                        main,
+                        "\n\nerror(\"failed\")",
+                        "script.jl",
+                    )?
+                    .into_jlrs_result()
+                    .expect_err("error was not thrown");
+                    assert!(err.to_string().contains("script.jl:3"));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn parse_tests() {
+        parse_valid_code();
+        parse_invalid_code();
+        eval_with_location();
+    }
+}
@@ -0,0 +1,109 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use jlrs::{
+        prelude::*,
+        redirect::{LineCallback, StdStream},
+    };
+
+    use super::util::JULIA;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn eval(julia: &mut Julia, cmd: &str) {
+        julia
+            .scope(|mut frame| unsafe {
+                Value::eval_string(&mut frame, cmd).into_jlrs_result()?;
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn redirect_stdout_to_writer() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+
+            let buffer = SharedBuffer::default();
+            julia
+                .redirect_output(StdStream::Stdout, buffer.clone())
+                .unwrap();
+            eval(&mut julia, "println(\"Hello from Julia\"); print(1, 2, 3)");
+            julia.restore_output(StdStream::Stdout).unwrap();
+
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            assert_eq!(output, "Hello from Julia\n123");
+
+            let redirected = julia
+                .scope(|mut frame| unsafe {
+                    Ok(
+                        Value::eval_string(&mut frame, "stdout isa JlrsCore.JlrsRedirect.RustIO")
+                            .into_jlrs_result()?
+                            .unbox::<bool>()?
+                            .as_bool(),
+                    )
+                })
+                .unwrap();
+            assert!(!redirected);
+        });
+    }
+
+    fn redirect_stderr_to_callback() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+
+            let lines = Arc::new(Mutex::new(Vec::new()));
+            let lines_cloned = lines.clone();
+            let callback = LineCallback::new(move |line: &str| {
+                lines_cloned.lock().unwrap().push(line.to_string())
+            });
+
+            julia.redirect_output(StdStream::Stderr, callback).unwrap();
+            eval(
+                &mut julia,
+                "println(stderr, \"first\"); print(stderr, \"sec\"); println(stderr, \"ond\"); print(stderr, \"last\")",
+            );
+            julia.restore_output(StdStream::Stderr).unwrap();
+
+            let lines = lines.lock().unwrap();
+            assert_eq!(lines.as_slice(), &["first", "second", "last"]);
+        });
+    }
+
+    fn restore_without_redirect() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut julia = jlrs.instance(&mut frame);
+
+            assert!(julia.restore_output(StdStream::Stdout).is_ok());
+        });
+    }
+
+    #[test]
+    fn redirect_tests() {
+        redirect_stdout_to_writer();
+        redirect_stderr_to_callback();
+        restore_without_redirect();
+    }
+}
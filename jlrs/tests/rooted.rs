@@ -0,0 +1,130 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::rooted::Rooted,
+        memory::gc::{Gc, GcCollection},
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    fn rooted_outlives_scope() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut jlrs = jlrs.instance(&mut frame);
+
+            let rooted = jlrs
+                .scope(|mut frame| {
+                    let value = Value::new(&mut frame, 3usize);
+                    Rooted::new(&frame, value)
+                })
+                .unwrap();
+
+            jlrs.scope(|frame| {
+                frame.gc_collect(GcCollection::Full);
+                let value = rooted.as_managed(&frame);
+                assert_eq!(value.unbox::<usize>()?, 3);
+                Ok(())
+            })
+            .unwrap();
+        });
+    }
+
+    fn rooted_module() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut jlrs = jlrs.instance(&mut frame);
+
+            let rooted = jlrs
+                .scope(|frame| Rooted::new(&frame, Module::base(&frame)))
+                .unwrap();
+
+            jlrs.scope(|frame| {
+                let module = rooted.as_managed(&frame);
+                assert_eq!(module.name().as_str()?, "Base");
+                Ok(())
+            })
+            .unwrap();
+        });
+    }
+
+    fn cloned_rooted_remains_rooted() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut jlrs = jlrs.instance(&mut frame);
+
+            let rooted = jlrs
+                .scope(|mut frame| {
+                    let value = JuliaString::new(&mut frame, "rooted");
+                    Rooted::new(&frame, value)
+                })
+                .unwrap();
+
+            let cloned = rooted.clone();
+            assert!(Rooted::ptr_eq(&rooted, &cloned));
+            std::mem::drop(rooted);
+
+            jlrs.scope(|mut frame| {
+                frame.gc_collect(GcCollection::Full);
+
+                let value = cloned.as_managed(&frame);
+                assert_eq!(value.as_str()?, "rooted");
+
+                let rerooted = cloned.root(&mut frame);
+                assert_eq!(rerooted.as_str()?, "rooted");
+                Ok(())
+            })
+            .unwrap();
+        });
+    }
+
+    fn rooted_dropped_from_other_thread() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            let mut jlrs = jlrs.instance(&mut frame);
+
+            let rooted = jlrs
+                .scope(|mut frame| {
+                    let value = Value::new(&mut frame, 1usize);
+                    Rooted::new(&frame, value)
+                })
+                .unwrap();
+
+            std::thread::spawn(move || std::mem::drop(rooted))
+                .join()
+                .unwrap();
+
+            jlrs.scope(|mut frame| {
+                frame.gc_collect(GcCollection::Full);
+
+                let rooted = (0..300)
+                    .map(|i| {
+                        let value = Value::new(&mut frame, i as usize);
+                        Rooted::new(&frame, value)
+                    })
+                    .collect::<JlrsResult<Vec<_>>>()?;
+
+                frame.gc_collect(GcCollection::Full);
+                for (i, rooted) in rooted.iter().enumerate() {
+                    assert_eq!(rooted.as_managed(&frame).unbox::<usize>()?, i);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn rooted_tests() {
+        rooted_outlives_scope();
+        rooted_module();
+        cloned_rooted_remains_rooted();
+        rooted_dropped_from_other_thread();
+    }
+}
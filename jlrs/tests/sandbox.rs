@@ -0,0 +1,123 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{prelude::*, sandbox::Sandbox};
+
+    use super::util::JULIA;
+
+    fn sandboxes_are_isolated() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let a = Sandbox::new(&mut frame, &["Base"])?;
+                    let b = Sandbox::new(&mut frame, &["Base"])?;
+
+                    a.eval_string(&mut frame, "x = 1")?.into_jlrs_result()?;
+                    b.eval_string(&mut frame, "x = 2")?.into_jlrs_result()?;
+
+                    let x = a
+                        .module()
+                        .global(&frame, "x")?
+                        .as_value()
+                        .unbox::<isize>()?;
+                    assert_eq!(x, 1);
+                    let x = b
+                        .module()
+                        .global(&frame, "x")?
+                        .as_value()
+                        .unbox::<isize>()?;
+                    assert_eq!(x, 2);
+
+                    let y = a
+                        .eval_string(&mut frame, "y = x + 1; y * 2")?
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(y, 4);
+
+                    assert!(Module::main(&frame).global(&frame, "x").is_err());
+                    assert!(b.module().global(&frame, "y").is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn set_sandbox_global() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let sandbox = Sandbox::new(&mut frame, &["Base"])?;
+                    let value = Value::new(&mut frame, 3usize);
+                    sandbox
+                        .module()
+                        .set_global(&mut frame, "z", value)
+                        .into_jlrs_result()?;
+
+                    let z = sandbox
+                        .eval_string(&mut frame, "z * z")?
+                        .into_jlrs_result()?
+                        .unbox::<usize>()?;
+                    assert_eq!(z, 9);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn sandbox_without_imports() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let sandbox = Sandbox::new(&mut frame, &[])?;
+
+                    // `+` is defined in `Base`, which hasn't been imported.
+                    assert!(sandbox.eval_string(&mut frame, "1 + 1")?.is_err());
+                    assert!(Sandbox::new(&mut frame, &["Base; exit()"]).is_err());
+                    assert!(Sandbox::new(&mut frame, &["NotAModule"]).is_err());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn sandbox_with_several_imports() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let sandbox = Sandbox::new(
+                        &mut frame,
+                        &["Base", "Base.Iterators", "Base.Threads", "Base.Math"],
+                    )?;
+
+                    let n = sandbox
+                        .eval_string(&mut frame, "length(collect(take(countfrom(1), 3)))")?
+                        .into_jlrs_result()?
+                        .unbox::<isize>()?;
+                    assert_eq!(n, 3);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn sandbox_tests() {
+        sandboxes_are_isolated();
+        set_sandbox_global();
+        sandbox_without_imports();
+        sandbox_with_several_imports();
+    }
+}
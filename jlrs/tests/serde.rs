@@ -0,0 +1,217 @@
+mod util;
+
+#[cfg(all(feature = "sync-rt", feature = "serde"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use jlrs::{
+        convert::serde::{from_value, to_value, Serializer, StructRepr},
+        prelude::*,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::util::JULIA;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        threshold: f64,
+        tags: Vec<String>,
+        limits: HashMap<String, u32>,
+        parent: Option<Box<Config>>,
+        mode: Mode,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Mode {
+        Fast,
+        Careful { retries: u8 },
+    }
+
+    fn config() -> Config {
+        let mut limits = HashMap::new();
+        limits.insert(String::from("cpu"), 4);
+
+        Config {
+            name: String::from("child"),
+            threshold: 0.5,
+            tags: vec![String::from("a"), String::from("b")],
+            limits,
+            parent: Some(Box::new(Config {
+                name: String::from("parent"),
+                threshold: 1.0,
+                tags: vec![],
+                limits: HashMap::new(),
+                parent: None,
+                mode: Mode::Fast,
+            })),
+            mode: Mode::Careful { retries: 3 },
+        }
+    }
+
+    fn serialize_primitives() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = to_value(frame.as_extended_target(), &3.0f64)?;
+                    assert_eq!(v.unbox::<f64>()?, 3.0);
+
+                    let v = to_value(frame.as_extended_target(), "foo")?;
+                    assert_eq!(v.cast::<JuliaString>()?.as_str()?, "foo");
+
+                    let v = to_value(frame.as_extended_target(), &None::<u8>)?;
+                    assert!(v.is::<Nothing>());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_collections() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = to_value(frame.as_extended_target(), &vec![1.0f64, 2.0])?;
+                    let ty =
+                        Value::eval_string(&mut frame, "Vector{Float64}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    // Long sequences aren't passed to Julia as separate arguments.
+                    let long = (0..100_000i64).collect::<Vec<_>>();
+                    let v = to_value(frame.as_extended_target(), &long)?;
+                    let ty = Value::eval_string(&mut frame, "Vector{Int64}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+                    let last = Value::eval_string(&mut frame, "last")
+                        .into_jlrs_result()?
+                        .call1(&mut frame, v)
+                        .into_jlrs_result()?
+                        .unbox::<i64>()?;
+                    assert_eq!(last, 99_999);
+
+                    let v = to_value(frame.as_extended_target(), &(1u8, "a"))?;
+                    let ty = Value::eval_string(&mut frame, "Tuple{UInt8, String}")
+                        .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    let mut map = HashMap::new();
+                    map.insert(String::from("a"), 1i64);
+                    let v = to_value(frame.as_extended_target(), &map)?;
+                    let ty =
+                        Value::eval_string(&mut frame, "Dict{String, Int64}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn serialize_struct() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = to_value(frame.as_extended_target(), &config())?;
+                    assert!(v.is::<jlrs::data::types::typecheck::NamedTuple>());
+
+                    let name = v.get_field(&mut frame, "name")?;
+                    assert_eq!(name.cast::<JuliaString>()?.as_str()?, "child");
+
+                    let output = frame.output();
+                    let v = frame.scope(|mut frame| {
+                        let v = serde::Serialize::serialize(
+                            &config(),
+                            Serializer::new(&mut frame).with_struct_repr(StructRepr::Dict),
+                        )?;
+                        Ok(v.root(output))
+                    })?;
+                    assert_eq!(v.datatype_name()?, "Dict");
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn round_trip() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let data = config();
+                    let v = to_value(frame.as_extended_target(), &data)?;
+                    let deserialized: Config = from_value(v)?;
+                    assert_eq!(data, deserialized);
+
+                    let output = frame.output();
+                    let v = frame.scope(|mut frame| {
+                        let v = serde::Serialize::serialize(
+                            &data,
+                            Serializer::new(&mut frame).with_struct_repr(StructRepr::Dict),
+                        )?;
+                        Ok(v.root(output))
+                    })?;
+                    let deserialized: Config = from_value(v)?;
+                    assert_eq!(data, deserialized);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn deserialize_julia_struct() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    #[derive(Deserialize)]
+                    struct Point {
+                        x: f64,
+                        label: String,
+                    }
+
+                    Value::eval_string(
+                        &mut frame,
+                        "struct SerdePoint
+                            x::Float64
+                            label::String
+                        end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let v = Value::eval_string(&mut frame, "[SerdePoint(1.0, \"a\")]")
+                        .into_jlrs_result()?;
+                    let points: Vec<Point> = from_value(v)?;
+                    assert_eq!(points.len(), 1);
+                    assert_eq!(points[0].x, 1.0);
+                    assert_eq!(points[0].label, "a");
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn serde_tests() {
+        serialize_primitives();
+        serialize_collections();
+        serialize_struct();
+        round_trip();
+        deserialize_julia_struct();
+    }
+}
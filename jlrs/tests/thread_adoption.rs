@@ -0,0 +1,85 @@
+#[cfg(all(
+    feature = "sync-rt",
+    not(any(feature = "julia-1-6", feature = "julia-1-7", feature = "julia-1-8"))
+))]
+mod tests {
+    use jlrs::{
+        memory::gc::{Gc, GcCollection},
+        prelude::*,
+    };
+
+    const N_THREADS: usize = 4;
+    const N_CALLS: usize = 100;
+
+    #[test]
+    fn call_julia_from_thread_pool() {
+        let mut pending = unsafe { RuntimeBuilder::new().start().unwrap() };
+        let mut frame = StackFrame::new();
+        let mut julia = pending.instance(&mut frame);
+
+        let adopter = julia.thread_adopter();
+
+        let sums = julia.gc_safe(|| {
+            std::thread::scope(|s| {
+                let handles: Vec<_> = (0..N_THREADS)
+                    .map(|i| {
+                        s.spawn(move || {
+                            let mut sum = 0;
+                            for j in 0..N_CALLS {
+                                sum += adopter
+                                    .scope(|mut frame| unsafe {
+                                        let func = Module::base(&frame)
+                                            .function(&frame, "+")?
+                                            .as_managed();
+                                        let a = Value::new(&mut frame, i);
+                                        let b = Value::new(&mut frame, j);
+                                        let v = func
+                                            .call2(&mut frame, a, b)
+                                            .into_jlrs_result()?
+                                            .unbox::<usize>()?;
+
+                                        // Allocate and collect garbage to make sure the GC can run
+                                        // while other threads are idle.
+                                        if j % 10 == 0 {
+                                            Value::eval_string(&mut frame, "zeros(1000)")
+                                                .into_jlrs_result()?;
+                                            frame.gc_collect(GcCollection::Incremental);
+                                        }
+
+                                        Ok(v)
+                                    })
+                                    .unwrap();
+                            }
+
+                            sum
+                        })
+                    })
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .map(|h| h.join().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        });
+
+        for (i, sum) in sums.into_iter().enumerate() {
+            assert_eq!(sum, i * N_CALLS + N_CALLS * (N_CALLS - 1) / 2);
+        }
+
+        // The main thread can still call into Julia after the other threads are done.
+        let v = julia
+            .scope(|mut frame| unsafe {
+                let a = Value::new(&mut frame, 1usize);
+                Module::base(&frame)
+                    .function(&frame, "+")?
+                    .as_managed()
+                    .call2(&mut frame, a, a)
+                    .into_jlrs_result()?
+                    .unbox::<usize>()
+            })
+            .unwrap();
+
+        assert_eq!(v, 2);
+    }
+}
@@ -0,0 +1,264 @@
+mod util;
+
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use jlrs::{convert::to_julia::ToJulia, prelude::*};
+
+    use crate::util::JULIA;
+
+    fn convert_string() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| {
+                    let v = String::from("Hellõ world!").to_julia(&mut frame)?;
+                    assert!(v.is::<JuliaString>());
+                    assert_eq!(v.cast::<JuliaString>()?.as_str()?, "Hellõ world!");
+
+                    let v = "foo".to_julia(&mut frame)?;
+                    assert_eq!(v.cast::<JuliaString>()?.as_str()?, "foo");
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_vec_of_strings() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data = vec![String::from("a"), String::from("b")];
+                    let v = data.to_julia(&mut frame)?;
+
+                    let ty = Value::eval_string(&mut frame, "Vector{String}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    let arr = v.cast::<Array>()?;
+                    let data = arr.value_data()?;
+                    let second = data[1].unwrap().as_value();
+                    assert_eq!(second.cast::<JuliaString>()?.as_str()?, "b");
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_nested_vec() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let data = vec![vec![1i64, 2], vec![3]];
+                    let v = data.to_julia(&mut frame)?;
+
+                    let ty = Value::eval_string(&mut frame, "Vector{Vector{Int64}}")
+                        .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    let sum = Value::eval_string(&mut frame, "x -> sum(sum, x)")
+                        .into_jlrs_result()?
+                        .call1(&mut frame, v)
+                        .into_jlrs_result()?;
+                    assert_eq!(sum.unbox::<i64>()?, 6);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_hash_map() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut data = HashMap::new();
+                    data.insert(String::from("a"), 1.0f64);
+                    data.insert(String::from("b"), 2.0f64);
+                    let v = data.to_julia(&mut frame)?;
+
+                    let ty = Value::eval_string(&mut frame, "Dict{String, Float64}")
+                        .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    let key = JuliaString::new(&mut frame, "b").as_value();
+                    let value = Module::base(&frame)
+                        .function(&frame, "getindex")?
+                        .as_managed()
+                        .call2(&mut frame, v, key)
+                        .into_jlrs_result()?;
+                    assert_eq!(value.unbox::<f64>()?, 2.0);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_btree_collections() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let mut map = BTreeMap::new();
+                    map.insert(1u8, vec![String::from("a")]);
+                    let v = map.to_julia(&mut frame)?;
+                    let ty = Value::eval_string(&mut frame, "Dict{UInt8, Vector{String}}")
+                        .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    let set: BTreeSet<_> = vec![1i32, 2, 3].into_iter().collect();
+                    let v = set.to_julia(&mut frame)?;
+                    let ty = Value::eval_string(&mut frame, "Set{Int32}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), ty);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_option() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = None::<String>.to_julia(&mut frame)?;
+                    assert!(v.is::<Nothing>());
+
+                    let v = Some(3usize).to_julia(&mut frame)?;
+                    assert_eq!(v.unbox::<usize>()?, 3);
+
+                    let ty = <Option<String> as ToJulia>::julia_type(&mut frame);
+                    let expected = Value::eval_string(&mut frame, "Union{Nothing, String}")
+                        .into_jlrs_result()?;
+                    assert_eq!(ty, expected);
+
+                    let v = vec![Some(1.0f32), None].to_julia(&mut frame)?;
+                    let expected =
+                        Value::eval_string(&mut frame, "Vector{Union{Nothing, Float32}}")
+                            .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), expected);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn convert_tuple() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    let v = (1u32, String::from("a"), vec![2.0f64]).to_julia(&mut frame)?;
+                    let expected =
+                        Value::eval_string(&mut frame, "Tuple{UInt32, String, Vector{Float64}}")
+                            .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), expected);
+
+                    let first = v.get_nth_field(&mut frame, 0)?;
+                    assert_eq!(first.unbox::<u32>()?, 1);
+
+                    // Tuples with abstract element types are created from the values' types.
+                    let s = JuliaString::new(&mut frame, "b").as_value();
+                    let v = (s, 2i64).to_julia(&mut frame)?;
+                    let expected = Value::eval_string(&mut frame, "Tuple{String, Int64}")
+                        .into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), expected);
+
+                    let v = (Some(3i64),).to_julia(&mut frame)?;
+                    let expected =
+                        Value::eval_string(&mut frame, "Tuple{Int64}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), expected);
+
+                    let v = (None::<i64>,).to_julia(&mut frame)?;
+                    let expected =
+                        Value::eval_string(&mut frame, "Tuple{Nothing}").into_jlrs_result()?;
+                    assert_eq!(v.datatype().as_value(), expected);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    #[derive(ToJulia)]
+    #[jlrs(julia_type = "Main.ToJuliaDerived")]
+    struct ToJuliaDerived {
+        name: String,
+        values: Vec<f64>,
+        tags: Option<Vec<String>>,
+    }
+
+    #[cfg(feature = "jlrs-derive")]
+    fn convert_derived() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(
+                        &mut frame,
+                        "struct ToJuliaDerived
+                            name::String
+                            values::Vector{Float64}
+                            tags::Union{Nothing, Vector{String}}
+                        end",
+                    )
+                    .into_jlrs_result()?;
+
+                    let data = ToJuliaDerived {
+                        name: String::from("foo"),
+                        values: vec![1.0, 2.0],
+                        tags: None,
+                    };
+
+                    let v = data.to_julia(&mut frame)?;
+                    assert_eq!(v.datatype_name()?, "ToJuliaDerived");
+
+                    let name = v.get_field(&mut frame, "name")?;
+                    assert_eq!(name.cast::<JuliaString>()?.as_str()?, "foo");
+                    let tags = v.get_field(&mut frame, "tags")?;
+                    assert!(tags.is::<Nothing>());
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn to_julia_tests() {
+        convert_string();
+        convert_vec_of_strings();
+        convert_nested_vec();
+        convert_hash_map();
+        convert_btree_collections();
+        convert_option();
+        convert_tuple();
+        #[cfg(feature = "jlrs-derive")]
+        convert_derived();
+    }
+}
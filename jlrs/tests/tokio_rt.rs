@@ -5,9 +5,19 @@ mod async_util;
 #[cfg(all(feature = "tokio-rt",))]
 #[cfg(test)]
 mod tests {
-    use std::{num::NonZeroUsize, sync::Arc};
-
-    use jlrs::prelude::*;
+    use std::{
+        num::NonZeroUsize,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use futures::StreamExt;
+    use jlrs::{
+        async_util::channel::{ChannelReceiver, ChannelSender},
+        error::{CancellationError, JlrsError},
+        prelude::*,
+        runtime::async_rt::supervision::{Liveness, SupervisionPolicy},
+    };
     use once_cell::sync::OnceCell;
 
     use super::async_util::{async_tasks::*, ASYNC_TESTS_JL};
@@ -19,6 +29,8 @@ mod tests {
                     .async_runtime::<Tokio>()
                     .n_threads(4)
                     .channel_capacity(NonZeroUsize::new_unchecked(32))
+                    .priority_classes(NonZeroUsize::new_unchecked(2))
+                    .class_capacity(1, NonZeroUsize::new_unchecked(8))
                     .start::<4>()
                     .expect("Could not init Julia")
                     .0,
@@ -66,6 +78,63 @@ mod tests {
         assert_eq!(receiver.recv().unwrap().unwrap(), 20_000_004.0);
     }
 
+    #[test]
+    fn test_priority_task() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(
+                MyTask {
+                    dims: 4,
+                    iters: 5_000_000,
+                },
+                sender,
+            )
+            .with_priority(1)
+            .with_deadline(Instant::now() + Duration::from_secs(1))
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 20_000_004.0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(
+                MyTask {
+                    dims: 4,
+                    iters: 5_000_000,
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 20_000_004.0);
+
+        let stats = julia.stats();
+        assert_eq!(stats.main_queue().class_capacities(), &[32, 8]);
+        assert!(stats.any_queue().is_none());
+        assert_eq!(stats.in_flight_tasks().len(), 1);
+        assert!(stats.tasks().received() >= 1);
+        assert!(stats.tasks().completed() >= 1);
+        assert!(stats.tasks().max_run() > std::time::Duration::ZERO);
+
+        #[cfg(feature = "metrics")]
+        {
+            let metrics = stats.metrics();
+            assert!(metrics.run().count() >= 1);
+            assert!(metrics.handled()[0] >= 1);
+        }
+    }
+
     #[test]
     fn test_other_ret_type_task() {
         let julia = JULIA.get_or_init(init);
@@ -239,6 +308,59 @@ mod tests {
     }
     */
 
+    #[test]
+    fn test_supervised_persistent() {
+        let julia = JULIA.get_or_init(init);
+
+        let policy = SupervisionPolicy::new()
+            .max_restarts(Some(1))
+            .backoff(Duration::from_millis(10), Duration::from_millis(10));
+
+        let handle = {
+            let (handle_sender, handle_receiver) = crossbeam_channel::bounded(1);
+            julia
+                .supervised_persistent::<UnboundedChannel<_>, _, _>(
+                    PanickingTask { n_inits: 0 },
+                    policy,
+                    handle_sender,
+                )
+                .try_dispatch_any()
+                .expect("Cannot send task");
+
+            handle_receiver
+                .recv()
+                .expect("Channel was closed")
+                .expect("Cannot init task")
+        };
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(1, sender).unwrap();
+        assert_eq!(receiver.recv().unwrap().unwrap(), 1);
+
+        // The call that panics fails, the call made while the task restarts is buffered.
+        let (panic_sender, panic_receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(-1, panic_sender).unwrap();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(1, sender).unwrap();
+
+        assert!(panic_receiver.recv().unwrap().is_err());
+        assert_eq!(receiver.recv().unwrap().unwrap(), 2);
+        assert_eq!(handle.restart_count(), 1);
+        assert_eq!(handle.liveness(), Liveness::Running);
+        assert!(handle.last_error().is_some());
+
+        // The task can't be restarted again.
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(-1, sender).unwrap();
+        assert!(receiver.recv().unwrap().is_err());
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        if handle.try_call(1, sender).is_ok() {
+            assert!(receiver.recv().is_err());
+        }
+        assert_eq!(handle.liveness(), Liveness::Stopped);
+    }
+
     #[test]
     fn test_persistent() {
         let julia = JULIA.get_or_init(init);
@@ -516,4 +638,142 @@ mod tests {
 
         assert_eq!(receiver.recv().unwrap().unwrap(), 2.0);
     }
+
+    #[test]
+    fn test_cancel_task() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        let (dispatch, handle) = julia.cancellable_task(SleepingTask { secs: 60.0 }, sender);
+        dispatch.try_dispatch_any().unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        handle.cancel();
+        assert!(handle.is_cancelled());
+
+        let err = receiver.recv().unwrap().unwrap_err();
+        assert!(matches!(
+            *err,
+            JlrsError::CancellationError(CancellationError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_timeout_task() {
+        let julia = JULIA.get_or_init(init);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(
+                TimeoutTask {
+                    secs: 60.0,
+                    timeout: std::time::Duration::from_millis(100),
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .unwrap();
+
+        let err = receiver.recv().unwrap().unwrap_err();
+        assert!(matches!(
+            *err,
+            JlrsError::CancellationError(CancellationError::TimedOut { .. })
+        ));
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+
+        julia
+            .task(
+                TimeoutTask {
+                    secs: 0.01,
+                    timeout: std::time::Duration::from_secs(60),
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .unwrap();
+
+        assert_eq!(receiver.recv().unwrap().unwrap(), 0.01);
+    }
+
+    #[test]
+    fn test_stream() {
+        let julia = JULIA.get_or_init(init);
+
+        let (dispatch, stream) = julia.stream::<BoundedChannel<_>, i64, _>(
+            |frame| unsafe {
+                Value::eval_string(frame, "Channel{Int}(ch -> foreach(i -> put!(ch, i), 1:5))")
+                    .into_jlrs_result()
+            },
+            NonZeroUsize::new(2),
+        );
+        dispatch.try_dispatch_any().unwrap();
+
+        let elems = futures::executor::block_on(stream.collect::<Vec<_>>());
+        let elems = elems.into_iter().collect::<JlrsResult<Vec<_>>>().unwrap();
+        assert_eq!(elems, vec![1, 2, 3, 4, 5]);
+
+        let (dispatch, stream) = julia.stream::<BoundedChannel<_>, String, _>(
+            |frame| unsafe {
+                Value::eval_string(frame, "(string(i) for i in 1:3)").into_jlrs_result()
+            },
+            None,
+        );
+        dispatch.try_dispatch_any().unwrap();
+
+        let elems = futures::executor::block_on(stream.collect::<Vec<_>>());
+        let elems = elems.into_iter().collect::<JlrsResult<Vec<_>>>().unwrap();
+        assert_eq!(elems, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_channel_bridge() {
+        let julia = JULIA.get_or_init(init);
+
+        let (in_sender, in_receiver) = crossbeam_channel::bounded(1);
+        let (dispatch, values) = julia.bridge_to_julia::<BoundedChannel<_>, i64, _, _>(
+            |frame| unsafe { Value::eval_string(frame, "AsyncTests.bridgein").into_jlrs_result() },
+            NonZeroUsize::new(2),
+            in_sender,
+        );
+        dispatch.try_dispatch_any().unwrap();
+
+        let (out_sender, out_receiver) = crossbeam_channel::bounded(1);
+        let (dispatch, mut doubled) = julia.bridge_from_julia::<BoundedChannel<_>, i64, _, _>(
+            |frame| unsafe { Value::eval_string(frame, "AsyncTests.bridgeout").into_jlrs_result() },
+            NonZeroUsize::new(2),
+            out_sender,
+        );
+        dispatch.try_dispatch_any().unwrap();
+
+        let received = futures::executor::block_on(async move {
+            let mut received = Vec::new();
+            for i in 1..=5 {
+                ChannelSender::send(&values, i).await.ok().unwrap();
+                received.push(ChannelReceiver::recv(&mut doubled).await.unwrap());
+            }
+
+            // Dropping the sender closes the Julia channel, which ends the bridge from Julia.
+            std::mem::drop(values);
+            assert!(ChannelReceiver::recv(&mut doubled).await.is_err());
+            received
+        });
+
+        assert_eq!(received, vec![2, 4, 6, 8, 10]);
+        assert!(in_receiver.recv().unwrap().is_ok());
+        assert!(out_receiver.recv().unwrap().is_ok());
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let (dispatch, _values) = julia.bridge_to_julia::<BoundedChannel<_>, f64, _, _>(
+            |frame| unsafe { Value::eval_string(frame, "Channel{Int}(1)").into_jlrs_result() },
+            None,
+            sender,
+        );
+        dispatch.try_dispatch_any().unwrap();
+
+        let err = receiver.recv().unwrap().unwrap_err();
+        assert!(matches!(*err, JlrsError::TypeError(_)));
+    }
 }
@@ -0,0 +1,156 @@
+#[cfg(all(feature = "tokio-rt",))]
+#[cfg(test)]
+mod async_util;
+
+#[cfg(all(feature = "tokio-rt",))]
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::NonZeroUsize,
+        time::{Duration, Instant},
+    };
+
+    use jlrs::{
+        prelude::*,
+        runtime::async_rt::{
+            dispatch::TryDispatchError, shutdown::ShutdownMode, supervision::SupervisionPolicy,
+        },
+    };
+
+    use super::async_util::{async_tasks::*, ASYNC_TESTS_JL};
+
+    fn init() -> AsyncJulia<Tokio> {
+        unsafe {
+            let r = RuntimeBuilder::new()
+                .async_runtime::<Tokio>()
+                .n_threads(4)
+                .channel_capacity(NonZeroUsize::new_unchecked(32))
+                .start::<1>()
+                .expect("Could not init Julia")
+                .0;
+
+            let (sender, recv) = tokio::sync::oneshot::channel();
+            r.blocking_task(
+                |mut frame| {
+                    Value::eval_string(&mut frame, ASYNC_TESTS_JL).into_jlrs_result()?;
+                    Ok(())
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .expect("Could not send blocking task");
+
+            recv.blocking_recv()
+                .expect("Could not receive reply")
+                .expect("Could not load AsyncTests module");
+
+            r
+        }
+    }
+
+    // Julia can only be initialized once per process, so everything is tested in a single test.
+    #[test]
+    fn test_shutdown() {
+        let julia = init();
+
+        let (is, ir) = crossbeam_channel::bounded(1);
+        julia
+            .register_persistent::<AccumulatorTask, _>(is)
+            .try_dispatch_any()
+            .unwrap();
+        ir.recv().unwrap().unwrap();
+
+        let handle = {
+            let (handle_sender, handle_receiver) = crossbeam_channel::bounded(1);
+            julia
+                .persistent::<UnboundedChannel<_>, _, _>(
+                    AccumulatorTask { init_value: 5.0 },
+                    handle_sender,
+                )
+                .try_dispatch_any()
+                .expect("Cannot send task");
+
+            handle_receiver
+                .recv()
+                .expect("Channel was closed")
+                .expect("Cannot init task")
+        };
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.try_call(7.0, sender).unwrap();
+        assert_eq!(receiver.recv().unwrap().unwrap(), 12.0);
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        julia
+            .task(
+                MyTask {
+                    dims: 4,
+                    iters: 5_000_000,
+                },
+                sender,
+            )
+            .try_dispatch_any()
+            .unwrap();
+
+        // The handle of a persistent task whose `init` fails is never created, the task waits for
+        // a long time before it's restarted.
+        let (init_sender, init_receiver) = crossbeam_channel::bounded(1);
+        julia
+            .supervised_persistent::<UnboundedChannel<_>, _, _>(
+                FailingInitTask,
+                SupervisionPolicy::new()
+                    .max_restarts(None)
+                    .backoff(Duration::from_secs(60), Duration::from_secs(60)),
+                init_sender,
+            )
+            .try_dispatch_any()
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let summary = futures::executor::block_on(julia.shutdown(ShutdownMode::Drain {
+            deadline: Some(deadline),
+        }))
+        .expect("Could not shut down");
+
+        assert_eq!(summary.abandoned(), 0);
+        assert_eq!(summary.discarded(), 0);
+        assert_eq!(receiver.recv().unwrap().unwrap(), 20_000_004.0);
+
+        // The shutdown interrupted the backoff of the persistent task, an error is sent instead
+        // of its handle.
+        assert!(init_receiver.recv().unwrap().is_err());
+
+        let second =
+            futures::executor::block_on(julia.shutdown(ShutdownMode::Discard { deadline: None }));
+        assert!(second.is_err());
+
+        // Tasks sent after the runtime has shut down are rejected.
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let res = julia
+            .task(
+                MyTask {
+                    dims: 4,
+                    iters: 5_000_000,
+                },
+                sender,
+            )
+            .try_dispatch_any();
+        assert!(matches!(res, Err(TryDispatchError::ShuttingDown)));
+        assert!(receiver.recv().is_err());
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let res = futures::executor::block_on(
+            julia
+                .task(
+                    MyTask {
+                        dims: 4,
+                        iters: 5_000_000,
+                    },
+                    sender,
+                )
+                .dispatch_any(),
+        );
+        assert!(res.is_err());
+        assert!(receiver.recv().is_err());
+    }
+}
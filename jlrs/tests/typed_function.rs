@@ -0,0 +1,108 @@
+mod util;
+#[cfg(feature = "sync-rt")]
+mod tests {
+    use jlrs::{
+        data::managed::function::typed::TypedFunction,
+        error::{JlrsError, TypeError},
+        prelude::*,
+    };
+
+    use super::util::JULIA;
+
+    const DEFINE_FUNCTIONS: &str = "typed_fn_add(x::Int, y::Float64) = x + y
+typed_fn_len(s::String) = length(s)
+typed_fn_unit() = 42
+typed_fn_throws(x::Int) = x > 0 ? x : error(\"negative\")";
+
+    fn call_typed_function() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTIONS).into_jlrs_result()?;
+                    let main = Module::main(&frame);
+
+                    let add = main.function(&frame, "typed_fn_add")?.as_managed();
+                    let add = TypedFunction::<(i64, f64), f64>::new(&frame, add.as_value())?;
+                    assert_eq!(add.call(&frame, (1, 2.5))?, 3.5);
+
+                    let len = main.function(&frame, "typed_fn_len")?.as_managed();
+                    let len = TypedFunction::<(&str,), i64>::new(&frame, len.as_value())?;
+                    assert_eq!(len.call(&frame, ("typed",))?, 5);
+
+                    let unit = main.function(&frame, "typed_fn_unit")?.as_managed();
+                    let unit = TypedFunction::<(), i64>::new(&frame, unit.as_value())?;
+                    assert_eq!(unit.call(&frame, ())?, 42);
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn reject_invalid_signatures() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTIONS).into_jlrs_result()?;
+                    let add = Module::main(&frame)
+                        .function(&frame, "typed_fn_add")?
+                        .as_managed()
+                        .as_value();
+
+                    let err = TypedFunction::<(f64, f64), f64>::new(&frame, add)
+                        .err()
+                        .expect("no method should match");
+                    assert!(matches!(
+                        *err,
+                        JlrsError::TypeError(TypeError::NoMatchingMethod { .. })
+                    ));
+
+                    let err = TypedFunction::<(i64, f64), i64>::new(&frame, add)
+                        .err()
+                        .expect("return type should be incompatible");
+                    assert!(matches!(
+                        *err,
+                        JlrsError::TypeError(TypeError::IncompatibleReturnType { .. })
+                    ));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    fn exception_is_returned() {
+        JULIA.with(|j| {
+            let mut frame = StackFrame::new();
+            let mut jlrs = j.borrow_mut();
+            jlrs.instance(&mut frame)
+                .scope(|mut frame| unsafe {
+                    Value::eval_string(&mut frame, DEFINE_FUNCTIONS).into_jlrs_result()?;
+                    let throws = Module::main(&frame)
+                        .function(&frame, "typed_fn_throws")?
+                        .as_managed();
+                    let throws = TypedFunction::<(i64,), i64>::new(&frame, throws.as_value())?;
+
+                    assert_eq!(throws.call(&frame, (1,))?, 1);
+                    let err = throws
+                        .call(&frame, (-1,))
+                        .expect_err("no exception was thrown");
+                    assert!(matches!(*err, JlrsError::Exception(_)));
+
+                    Ok(())
+                })
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn typed_function_tests() {
+        call_typed_function();
+        reject_invalid_signatures();
+        exception_is_returned();
+    }
+}
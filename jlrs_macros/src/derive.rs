@@ -275,6 +275,323 @@ pub fn impl_into_julia_fn(attrs: &JlrsTypeAttrs) -> Option<TS2> {
     }
 }
 
+pub fn impl_to_julia(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let mut attrs = JlrsTypeAttrs::parse(ast);
+    let jl_type = attrs.julia_type
+        .take()
+        .expect("ToJulia can only be derived if the corresponding Julia type is set with #[jlrs(julia_type = \"Main.MyModule.Submodule.StructType\")]");
+
+    let fields = match &ast.data {
+        syn::Data::Struct(s) => &s.fields,
+        _ => panic!("ToJulia can only be derived for structs."),
+    };
+
+    let field_accessors = fields.iter().enumerate().map(|(idx, field)| -> TS2 {
+        match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let idx = syn::Index::from(idx);
+                quote! { #idx }
+            }
+        }
+    });
+
+    let n_fields = fields.len();
+    let n_slots = n_fields + 1;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let to_julia_impl = quote! {
+        impl #impl_generics ::jlrs::convert::to_julia::ToJulia for #name #ty_generics #where_clause {
+            #[inline]
+            fn julia_type<'target, Tgt>(target: Tgt) -> ::jlrs::data::managed::value::ValueData<'target, 'static, Tgt>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                let ty = ::jlrs::inline_static_ref!(STATIC, ::jlrs::data::managed::value::Value, #jl_type, &target);
+                ::jlrs::data::managed::Managed::root(ty, target)
+            }
+
+            fn to_julia<'target, Tgt>(
+                self,
+                target: Tgt,
+            ) -> ::jlrs::error::JlrsResult<::jlrs::data::managed::value::ValueData<'target, 'static, Tgt>>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                target.with_local_scope::<_, _, #n_slots>(|target, mut frame| {
+                    let fields: [::jlrs::data::managed::value::Value; #n_fields] = [
+                        #(
+                            ::jlrs::convert::to_julia::ToJulia::to_julia(self.#field_accessors, &mut frame)?,
+                        )*
+                    ];
+
+                    let ty = <Self as ::jlrs::convert::to_julia::ToJulia>::julia_type(&frame);
+                    unsafe {
+                        let instance = ::jlrs::call::Call::call(ty.as_value(), &mut frame, fields);
+                        let instance = ::jlrs::convert::into_jlrs_result::IntoJlrsResult::into_jlrs_result(instance)?;
+                        Ok(::jlrs::data::managed::Managed::root(instance, target))
+                    }
+                })
+            }
+        }
+    };
+
+    to_julia_impl.into()
+}
+
+pub fn impl_from_julia(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let fields = match &ast.data {
+        syn::Data::Struct(s) => &s.fields,
+        _ => panic!("FromJulia can only be derived for structs."),
+    };
+
+    let constructor = match fields {
+        syn::Fields::Named(fields) => {
+            let fields = fields.named.iter().map(|field| -> TS2 {
+                let ident = field.ident.as_ref().unwrap();
+                let ty = &field.ty;
+                let field_name = ident.to_string();
+                let field_name = field_name.strip_prefix("r#").unwrap_or(&field_name);
+
+                quote! {
+                    #ident: ::jlrs::convert::from_julia::field_from_julia::<#ty, _>(&target, value, #field_name, path)?
+                }
+            });
+
+            quote! { #name { #(#fields,)* } }
+        }
+        syn::Fields::Unnamed(fields) => {
+            let fields = fields.unnamed.iter().enumerate().map(|(idx, field)| -> TS2 {
+                let ty = &field.ty;
+                quote! {
+                    ::jlrs::convert::from_julia::nth_field_from_julia::<#ty, _>(&target, value, #idx, path)?
+                }
+            });
+
+            quote! { #name ( #(#fields,)* ) }
+        }
+        syn::Fields::Unit => quote! { #name },
+    };
+
+    let allow_unused = match fields {
+        syn::Fields::Unit => Some(quote! { #[allow(unused_variables)] }),
+        _ => None,
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let from_julia_impl = quote! {
+        impl #impl_generics ::jlrs::convert::from_julia::FromJulia for #name #ty_generics #where_clause {
+            #allow_unused
+            fn from_julia_at<'target, Tgt>(
+                target: Tgt,
+                value: ::jlrs::data::managed::value::Value,
+                path: &mut ::jlrs::convert::from_julia::ConversionPath,
+            ) -> ::jlrs::error::JlrsResult<Self>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                Ok(#constructor)
+            }
+        }
+    };
+
+    from_julia_impl.into()
+}
+
+pub fn impl_from_julia_exception(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let variants = match &ast.data {
+        syn::Data::Enum(e) => &e.variants,
+        _ => panic!("FromJuliaException can only be derived for enums."),
+    };
+
+    let has_fields = variants.iter().any(|variant| !variant.fields.is_empty());
+    let matchers = variants.iter().map(|variant| -> TS2 {
+        let ident = &variant.ident;
+        let jl_type = variant_julia_type(&variant.attrs).unwrap_or_else(|| {
+            panic!(
+                "Variant {} must set the corresponding Julia type with #[jlrs(julia_type = \"Base.ErrorException\")]",
+                ident
+            )
+        });
+
+        let constructor = match &variant.fields {
+            syn::Fields::Named(fields) => {
+                let fields = fields.named.iter().map(|field| -> TS2 {
+                    let ident = field.ident.as_ref().unwrap();
+                    let ty = &field.ty;
+                    let field_name = ident.to_string();
+                    let field_name = field_name.strip_prefix("r#").unwrap_or(&field_name);
+
+                    quote! {
+                        #ident: ::jlrs::convert::from_julia::field_from_julia::<#ty, _>(&target, exception, #field_name, path)?
+                    }
+                });
+
+                quote! { #name::#ident { #(#fields,)* } }
+            }
+            syn::Fields::Unnamed(fields) => {
+                let fields = fields.unnamed.iter().enumerate().map(|(idx, field)| -> TS2 {
+                    let ty = &field.ty;
+                    quote! {
+                        ::jlrs::convert::from_julia::nth_field_from_julia::<#ty, _>(&target, exception, #idx, path)?
+                    }
+                });
+
+                quote! { #name::#ident ( #(#fields,)* ) }
+            }
+            syn::Fields::Unit => quote! { #name::#ident },
+        };
+
+        quote! {
+            // Safety: exception types are constants. If the type doesn't exist (yet), the
+            // exception can't be an instance of it.
+            let ty = unsafe {
+                ::jlrs::data::managed::module::Module::typed_global_cached::<::jlrs::data::managed::value::Value, _, _>(&target, #jl_type)
+            };
+            if let Ok(ty) = ty {
+                if exception.isa(ty) {
+                    return Ok(Some(#constructor));
+                }
+            }
+        }
+    });
+
+    let path = if has_fields {
+        Some(quote! {
+            let path = &mut ::jlrs::convert::from_julia::ConversionPath::new("exception");
+        })
+    } else {
+        None
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let from_julia_exception_impl = quote! {
+        impl #impl_generics ::jlrs::convert::from_julia_exception::FromJuliaException for #name #ty_generics #where_clause {
+            fn from_julia_exception<'target, Tgt>(
+                target: Tgt,
+                exception: ::jlrs::data::managed::value::Value,
+            ) -> ::jlrs::error::JlrsResult<Option<Self>>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                #path
+                #(#matchers)*
+                Ok(None)
+            }
+        }
+    };
+
+    from_julia_exception_impl.into()
+}
+
+pub fn impl_into_julia_exception(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let variants = match &ast.data {
+        syn::Data::Enum(e) => &e.variants,
+        _ => panic!("IntoJuliaException can only be derived for enums."),
+    };
+
+    let n_slots = variants
+        .iter()
+        .map(|variant| variant.fields.len())
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    let arms = variants.iter().map(|variant| -> TS2 {
+        let ident = &variant.ident;
+        let jl_type = variant_julia_type(&variant.attrs).unwrap_or_else(|| {
+            panic!(
+                "Variant {} must set the corresponding Julia type with #[jlrs(julia_type = \"Base.ErrorException\")]",
+                ident
+            )
+        });
+
+        let n_fields = variant.fields.len();
+        let bindings = (0..n_fields)
+            .map(|idx| format_ident!("field_{}", idx))
+            .collect::<Vec<_>>();
+
+        let pattern = match &variant.fields {
+            syn::Fields::Named(fields) => {
+                let names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
+                quote! { #name::#ident { #(#names: #bindings,)* } }
+            }
+            syn::Fields::Unnamed(_) => quote! { #name::#ident ( #(#bindings,)* ) },
+            syn::Fields::Unit => quote! { #name::#ident },
+        };
+
+        quote! {
+            #pattern => {
+                let fields: [::jlrs::data::managed::value::Value; #n_fields] = [
+                    #(
+                        ::jlrs::convert::to_julia::ToJulia::to_julia(#bindings, &mut frame)?,
+                    )*
+                ];
+
+                // Safety: exception types are constants.
+                let ty = unsafe {
+                    ::jlrs::data::managed::module::Module::typed_global_cached::<::jlrs::data::managed::value::Value, _, _>(&frame, #jl_type)?
+                };
+                unsafe { ::jlrs::call::Call::call(ty, &mut frame, fields) }
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let into_julia_exception_impl = quote! {
+        impl #impl_generics ::jlrs::convert::into_julia_exception::IntoJuliaException for #name #ty_generics #where_clause {
+            fn into_julia_exception<'target, Tgt>(
+                self,
+                target: Tgt,
+            ) -> ::jlrs::error::JlrsResult<::jlrs::data::managed::value::ValueData<'target, 'static, Tgt>>
+            where
+                Tgt: ::jlrs::memory::target::Target<'target>,
+            {
+                target.with_local_scope::<_, _, #n_slots>(|target, mut frame| {
+                    let exception = match self {
+                        #(#arms)*
+                    };
+                    let exception = ::jlrs::convert::into_jlrs_result::IntoJlrsResult::into_jlrs_result(exception)?;
+                    Ok(::jlrs::data::managed::Managed::root(exception, target))
+                })
+            }
+        }
+    };
+
+    into_julia_exception_impl.into()
+}
+
+fn variant_julia_type(attrs: &[syn::Attribute]) -> Option<String> {
+    for attr in attrs {
+        if attr.path().is_ident("jlrs") {
+            let nested = attr
+                .parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                .unwrap();
+            for meta in nested {
+                match meta {
+                    syn::Meta::NameValue(mnv) if mnv.path.is_ident("julia_type") => {
+                        if let syn::Expr::Lit(lit) = mnv.value {
+                            if let syn::Lit::Str(s) = lit.lit {
+                                return Some(s.value());
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    None
+}
+
 pub fn impl_unbox(ast: &syn::DeriveInput) -> TokenStream {
     let name = &ast.ident;
     if !is_repr_c(ast) {
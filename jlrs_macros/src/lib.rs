@@ -47,6 +47,14 @@ use self::version::emit_if_compatible;
 ///     #[gc_safe]
 ///     fn foo(arr: Array) -> usize as bar;
 ///
+///     // Panicking in an exported function aborts the process. If the function is annotated
+///     // with `#[catch_unwind]`, the panic is caught and thrown as a `JlrsCore.JlrsError` that
+///     // contains the panic message and location instead. This requires that the library is
+///     // built with `panic = "unwind"`. This attribute can also be used with exported methods,
+///     // the borrow of `self` is released before the exception is thrown.
+///     #[catch_unwind]
+///     fn may_panic(arr: Array) -> usize;
+///
 ///     // Exports the function `foo` as `bar!` in the `Base` module.
 ///     //
 ///     // This syntax can be used to extend existing functions.
@@ -164,6 +172,55 @@ pub fn into_julia_derive(input: TokenStream) -> TokenStream {
     impl_into_julia(&ast)
 }
 
+/// Derive `ToJulia`.
+///
+/// The Julia type must be provided with the `julia_type` attribute, e.g.
+/// `#[jlrs(julia_type = "Main.MyModule.MyType")]`. The fields are converted in declaration order
+/// and the converted fields are passed to the constructor of that type.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(ToJulia, attributes(jlrs))]
+pub fn to_julia_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_to_julia(&ast)
+}
+
+/// Derive `FromJulia`.
+///
+/// The fields of named structs are looked up by name, the fields of tuple structs by position.
+/// Every field must implement `FromJulia`.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(FromJulia, attributes(jlrs))]
+pub fn from_julia_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_from_julia(&ast)
+}
+
+/// Derive `FromJuliaException`.
+///
+/// Can only be derived for enums. Every variant must set the Julia exception type it corresponds
+/// to with the `julia_type` attribute, e.g. `#[jlrs(julia_type = "Base.ArgumentError")]`. The
+/// fields of a variant are extracted from the exception with `FromJulia`, named fields are
+/// looked up by name and unnamed fields by position.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(FromJuliaException, attributes(jlrs))]
+pub fn from_julia_exception_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_from_julia_exception(&ast)
+}
+
+/// Derive `IntoJuliaException`.
+///
+/// Can only be derived for enums. Every variant must set the Julia exception type it's converted
+/// to with the `julia_type` attribute, e.g. `#[jlrs(julia_type = "Base.ArgumentError")]`. The
+/// fields of a variant are converted to Julia data with `ToJulia` and passed to the constructor
+/// of that type in declaration order.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(IntoJuliaException, attributes(jlrs))]
+pub fn into_julia_exception_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_into_julia_exception(&ast)
+}
+
 /// Derive `IsBits`.
 ///
 /// Should only be used in combination with layouts generated by JlrsReflect.jl
@@ -234,6 +234,7 @@ impl ExportedFunction {
         env: Option<&ParameterEnvironment>,
         offset: &mut usize,
         gc_safe: bool,
+        catch_unwind: bool,
     ) -> Result<Expr> {
         let n_args = self.func.inputs.len();
         let name_ident = &self.func.ident;
@@ -279,11 +280,7 @@ impl ExportedFunction {
             });
             let names = Punctuated::<_, Comma>::from_iter(names);
 
-            let call_expr: Expr = if gc_safe {
-                parse_quote! {  ::jlrs::memory::gc::gc_safe(|| #name_ident(#names)) }
-            } else {
-                parse_quote! { #name_ident(#names) }
-            };
+            let call_expr = call_expr_fragment(parse_quote! { #name_ident(#names) }, gc_safe, catch_unwind);
 
             let span = self.func.span();
             let invoke_fn: ItemFn = parse_quote_spanned! {
@@ -425,6 +422,7 @@ impl ExportedMethod {
         offset: &mut usize,
         untracked_self: bool,
         gc_safe: bool,
+        catch_unwind: bool,
     ) -> Result<Expr> {
         let n_args = self.func.inputs.len();
         let name_ident = &self.func.ident;
@@ -459,7 +457,7 @@ impl ExportedMethod {
             let ccall_arg_idx = 0..n_args;
             let julia_arg_idx = 0..n_args;
 
-            let (ccall_arg_types, julia_arg_types, invoke_fn) = method_arg_type_fragments_in_env(self, &resolver, untracked_self, gc_safe);
+            let (ccall_arg_types, julia_arg_types, invoke_fn) = method_arg_type_fragments_in_env(self, &resolver, untracked_self, gc_safe, catch_unwind);
 
             let ex = parse_quote! {
                 {
@@ -1042,10 +1040,12 @@ impl<'a> GenericEnvironment<'a> {
             .map(|it| it.get_exported_fn())
             .map(|it| {
                 let mut gc_safe = false;
+                let mut catch_unwind = false;
                 if let Some(attrs) = it.1 {
                     gc_safe = has_outer_path_attr(attrs, "gc_safe");
+                    catch_unwind = has_outer_path_attr(attrs, "catch_unwind");
                 }
-                it.0.init_with_env(self, env, offset, gc_safe)
+                it.0.init_with_env(self, env, offset, gc_safe, catch_unwind)
             })
             .collect::<Result<Vec<_>>>()?;
 
@@ -1081,11 +1081,13 @@ impl<'a> GenericEnvironment<'a> {
             .map(|it| {
                 let mut untracked_self = false;
                 let mut gc_safe = false;
+                let mut catch_unwind = false;
                 if let Some(attrs) = it.1 {
                     untracked_self = has_outer_path_attr(attrs, "untracked_self");
                     gc_safe = has_outer_path_attr(attrs, "gc_safe");
+                    catch_unwind = has_outer_path_attr(attrs, "catch_unwind");
                 }
-                it.0.init_with_env(self, env, offset, untracked_self, gc_safe)
+                it.0.init_with_env(self, env, offset, untracked_self, gc_safe, catch_unwind)
             }) // TODO: attrs
             .collect::<Result<Vec<_>>>()?;
 
@@ -2447,15 +2449,13 @@ fn function_info_fragment(
     let names = Punctuated::<_, Comma>::from_iter(names);
 
     let mut gc_safe = false;
+    let mut catch_unwind = false;
     if let Some(attrs) = attrs {
         gc_safe = has_outer_path_attr(attrs, "gc_safe");
+        catch_unwind = has_outer_path_attr(attrs, "catch_unwind");
     }
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {  ::jlrs::memory::gc::gc_safe(|| #name_ident(#names)) }
-    } else {
-        parse_quote! { #name_ident(#names) }
-    };
+    let call_expr = call_expr_fragment(parse_quote! { #name_ident(#names) }, gc_safe, catch_unwind);
 
     let span = info.func.span();
     let invoke_fn: ItemFn = parse_quote_spanned! {
@@ -2754,10 +2754,12 @@ fn method_info_fragment<'a>(
 
     let mut untracked_self = false;
     let mut gc_safe = false;
+    let mut catch_unwind = false;
 
     if let Some(attrs) = attrs {
         untracked_self = has_outer_path_attr(attrs, "untracked_self");
         gc_safe = has_outer_path_attr(attrs, "gc_safe");
+        catch_unwind = has_outer_path_attr(attrs, "catch_unwind");
     }
 
     let override_module_fragment = override_module_fragment(&info.name_override);
@@ -2780,7 +2782,7 @@ fn method_info_fragment<'a>(
     let julia_arg_idx = 0..n_args;
 
     let (ccall_arg_types, julia_arg_types, invoke_fn) =
-        method_arg_type_fragments(info, untracked_self, gc_safe);
+        method_arg_type_fragments(info, untracked_self, gc_safe, catch_unwind);
 
     parse_quote! {
         {
@@ -3012,6 +3014,7 @@ fn method_arg_type_fragments<'a>(
     info: &'a ExportedMethod,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> (
     impl 'a + Iterator<Item = Expr>,
     impl 'a + Iterator<Item = Expr>,
@@ -3025,10 +3028,16 @@ fn method_arg_type_fragments<'a>(
     };
 
     let invoke_fn = match takes_self {
-        None => invoke_fn_no_self_method_fragment(info, gc_safe),
-        Some((true, true)) => invoke_fn_mut_self_method_fragment(info, untracked_self, gc_safe),
-        Some((false, true)) => invoke_fn_ref_self_method_fragment(info, untracked_self, gc_safe),
-        Some((_, false)) => invoke_fn_move_self_method_fragment(info, untracked_self, gc_safe),
+        None => invoke_fn_no_self_method_fragment(info, gc_safe, catch_unwind),
+        Some((true, true)) => {
+            invoke_fn_mut_self_method_fragment(info, untracked_self, gc_safe, catch_unwind)
+        }
+        Some((false, true)) => {
+            invoke_fn_ref_self_method_fragment(info, untracked_self, gc_safe, catch_unwind)
+        }
+        Some((_, false)) => {
+            invoke_fn_move_self_method_fragment(info, untracked_self, gc_safe, catch_unwind)
+        }
     };
 
     let parent = &info.parent;
@@ -3080,6 +3089,7 @@ fn method_arg_type_fragments_in_env<'a>(
     resolver: &'a ResolvedParameterList,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> (
     impl 'a + Iterator<Item = Expr>,
     impl 'a + Iterator<Item = Expr>,
@@ -3093,16 +3103,28 @@ fn method_arg_type_fragments_in_env<'a>(
     };
 
     let invoke_fn = match takes_self {
-        None => invoke_fn_no_self_method_fragment_in_env(info, resolver, gc_safe),
-        Some((true, true)) => {
-            invoke_fn_mut_self_method_fragment_in_env(info, resolver, untracked_self, gc_safe)
-        }
-        Some((false, true)) => {
-            invoke_fn_ref_self_method_fragment_in_env(info, resolver, untracked_self, gc_safe)
-        }
-        Some((_, false)) => {
-            invoke_fn_move_self_method_fragment_in_env(info, resolver, untracked_self, gc_safe)
-        }
+        None => invoke_fn_no_self_method_fragment_in_env(info, resolver, gc_safe, catch_unwind),
+        Some((true, true)) => invoke_fn_mut_self_method_fragment_in_env(
+            info,
+            resolver,
+            untracked_self,
+            gc_safe,
+            catch_unwind,
+        ),
+        Some((false, true)) => invoke_fn_ref_self_method_fragment_in_env(
+            info,
+            resolver,
+            untracked_self,
+            gc_safe,
+            catch_unwind,
+        ),
+        Some((_, false)) => invoke_fn_move_self_method_fragment_in_env(
+            info,
+            resolver,
+            untracked_self,
+            gc_safe,
+            catch_unwind,
+        ),
     };
 
     let parent = resolver.apply(&info.parent);
@@ -3387,7 +3409,11 @@ fn invoke_async_callback_in_env<'a>(
     }
 }
 
-fn invoke_fn_no_self_method_fragment(info: &ExportedMethod, gc_safe: bool) -> ItemFn {
+fn invoke_fn_no_self_method_fragment(
+    info: &ExportedMethod,
+    gc_safe: bool,
+    catch_unwind: bool,
+) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
     let ty = &info.parent;
@@ -3403,15 +3429,8 @@ fn invoke_fn_no_self_method_fragment(info: &ExportedMethod, gc_safe: bool) -> It
 
     let names = Punctuated::<_, Comma>::from_iter(names);
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                <#ty>::#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { <#ty>::#name(#names) }
-    };
+    let call_expr =
+        call_expr_fragment(parse_quote! { <#ty>::#name(#names) }, gc_safe, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args) #new_ret_ty {
@@ -3425,6 +3444,7 @@ fn invoke_fn_no_self_method_fragment_in_env(
     info: &ExportedMethod,
     resolver: &ResolvedParameterList,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3442,15 +3462,8 @@ fn invoke_fn_no_self_method_fragment_in_env(
         })
         .collect();
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                <#ty>::#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { <#ty>::#name(#names) }
-    };
+    let call_expr =
+        call_expr_fragment(parse_quote! { <#ty>::#name(#names) }, gc_safe, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args) #new_ret_ty {
@@ -3464,6 +3477,7 @@ fn invoke_fn_ref_self_method_fragment(
     info: &ExportedMethod,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3495,25 +3509,18 @@ fn invoke_fn_ref_self_method_fragment(
         parse_quote! { (&this).track_shared() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.#name(#names) }
-    };
+    let call_expr =
+        catching_call_expr_fragment(parse_quote! { this.#name(#names) }, gc_safe, catch_unwind);
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
-                Ok(this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+            let res = match #to_ref_expr {
+                Ok(this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
-            }
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
         }
     }
 }
@@ -3523,6 +3530,7 @@ fn invoke_fn_ref_self_method_fragment_in_env(
     resolver: &ResolvedParameterList,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3554,25 +3562,18 @@ fn invoke_fn_ref_self_method_fragment_in_env(
         parse_quote! { (&this).track_shared() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.#name(#names) }
-    };
+    let call_expr =
+        catching_call_expr_fragment(parse_quote! { this.#name(#names) }, gc_safe, catch_unwind);
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
-                Ok(this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+            let res = match #to_ref_expr {
+                Ok(this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
-            }
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
         }
     }
 }
@@ -3581,6 +3582,7 @@ fn invoke_fn_move_self_method_fragment(
     info: &ExportedMethod,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3612,25 +3614,21 @@ fn invoke_fn_move_self_method_fragment(
         parse_quote! { (&this).track_shared() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.clone().#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.clone().#name(#names) }
-    };
+    let call_expr = catching_call_expr_fragment(
+        parse_quote! { this.clone().#name(#names) },
+        gc_safe,
+        catch_unwind,
+    );
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
-                Ok(this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+            let res = match #to_ref_expr {
+                Ok(this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
-            }
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
         }
     }
 }
@@ -3640,6 +3638,7 @@ fn invoke_fn_move_self_method_fragment_in_env(
     resolver: &ResolvedParameterList,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3671,25 +3670,21 @@ fn invoke_fn_move_self_method_fragment_in_env(
         parse_quote! { (&this).track_shared() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.clone().#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.clone().#name(#names) }
-    };
+    let call_expr = catching_call_expr_fragment(
+        parse_quote! { this.clone().#name(#names) },
+        gc_safe,
+        catch_unwind,
+    );
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
-                Ok(this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+            let res = match #to_ref_expr {
+                Ok(this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
-            }
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
         }
     }
 }
@@ -3698,6 +3693,7 @@ fn invoke_fn_mut_self_method_fragment(
     info: &ExportedMethod,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3728,26 +3724,19 @@ fn invoke_fn_mut_self_method_fragment(
         parse_quote! { (&mut this).track_exclusive() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.#name(#names) }
-    };
+    let call_expr =
+        catching_call_expr_fragment(parse_quote! { this.#name(#names) }, gc_safe, catch_unwind);
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
+            let res = match #to_ref_expr {
                 #[allow(unused_mut)]
-                Ok(mut this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+                Ok(mut this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
-            }
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
         }
     }
 }
@@ -3757,6 +3746,7 @@ fn invoke_fn_mut_self_method_fragment_in_env(
     resolver: &ResolvedParameterList,
     untracked_self: bool,
     gc_safe: bool,
+    catch_unwind: bool,
 ) -> ItemFn {
     let name = &info.func.ident;
     let span = info.func.ident.span();
@@ -3787,27 +3777,58 @@ fn invoke_fn_mut_self_method_fragment_in_env(
         parse_quote! { (&mut this).track_exclusive() }
     };
 
-    let call_expr: Expr = if gc_safe {
-        parse_quote! {
-            ::jlrs::memory::gc::gc_safe(|| {
-                this.#name(#names)
-            })
-        }
-    } else {
-        parse_quote! { this.#name(#names) }
-    };
+    let call_expr =
+        catching_call_expr_fragment(parse_quote! { this.#name(#names) }, gc_safe, catch_unwind);
+
+    let rethrow_expr = rethrow_panic_fragment(parse_quote! { res }, catch_unwind);
 
     parse_quote_spanned! {
         span=> unsafe extern "C" fn invoke(#args_self_renamed) #new_ret_ty {
-            match #to_ref_expr {
+            let res = match #to_ref_expr {
                 #[allow(unused_mut)]
-                Ok(mut this) => {
-                    let res = #call_expr;
-                    <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(res)
-                },
+                Ok(mut this) => #call_expr,
                 Err(_) => ::jlrs::ccall::CCall::throw_borrow_exception()
+            };
+            <#ret_ty as ::jlrs::convert::ccall_types::CCallReturn>::return_or_throw(#rethrow_expr)
+        }
+    }
+}
+
+// Wraps `call_expr` in a call to `gc_safe` if `gc_safe` is true. If `catch_unwind` is true, a
+// panic is caught and rethrown as a Julia exception after leaving the GC-safe state.
+fn call_expr_fragment(call_expr: Expr, gc_safe: bool, catch_unwind: bool) -> Expr {
+    let call_expr = catching_call_expr_fragment(call_expr, gc_safe, catch_unwind);
+    rethrow_panic_fragment(call_expr, catch_unwind)
+}
+
+// Like `call_expr_fragment`, but a caught panic is returned as an `Err` instead of being
+// rethrown. Methods use this to release the borrow of `self` before the panic is rethrown,
+// throwing an exception doesn't run any destructors.
+fn catching_call_expr_fragment(call_expr: Expr, gc_safe: bool, catch_unwind: bool) -> Expr {
+    let call_expr: Expr = if catch_unwind {
+        parse_quote! { ::jlrs::ccall::CCall::catch_panic(|| #call_expr) }
+    } else {
+        call_expr
+    };
+
+    if gc_safe {
+        parse_quote! { ::jlrs::memory::gc::gc_safe(|| #call_expr) }
+    } else {
+        call_expr
+    }
+}
+
+// Rethrows a panic caught by `catching_call_expr_fragment` as a Julia exception.
+fn rethrow_panic_fragment(res_expr: Expr, catch_unwind: bool) -> Expr {
+    if catch_unwind {
+        parse_quote! {
+            match #res_expr {
+                Ok(res) => res,
+                Err(panic) => ::jlrs::ccall::CCall::throw_panic(panic),
             }
         }
+    } else {
+        res_expr
     }
 }
 
@@ -34,6 +34,19 @@ using Test
     @test JuliaModuleTest.freestanding_func_ret_rust_result(false) == 3
     @inferred JuliaModuleTest.freestanding_func_ret_rust_result(false)
     @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_ret_rust_result(true)
+
+    @test JuliaModuleTest.freestanding_func_panics(false) == 3
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_panics(true)
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.freestanding_func_panics_gc_safe(true)
+    @test JuliaModuleTest.freestanding_func_panics_gc_safe(false) == 3
+
+    msg = try
+        JuliaModuleTest.freestanding_func_panics(true)
+    catch e
+        sprint(showerror, e)
+    end
+    @test occursin("freestanding_func_panics panicked", msg)
+    @test occursin("lib.rs", msg)
 end
 
 @testset "OpaqueInt" begin
@@ -55,6 +68,11 @@ end
 
     @test JuliaModuleTest.unbox_opaque(opaque_int) == Int32(1)
     @inferred JuliaModuleTest.unbox_opaque(opaque_int)
+
+    # The borrow is released before the panic is thrown as an exception
+    @test_throws JlrsCore.JlrsError JuliaModuleTest.increment_panics!(opaque_int)
+    @test isnothing(JuliaModuleTest.increment!(opaque_int))
+    @test JuliaModuleTest.unbox_opaque(opaque_int) == Int32(2)
 end
 
 @testset "ForeignThing" begin
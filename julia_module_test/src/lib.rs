@@ -97,6 +97,14 @@ fn freestanding_func_ret_rust_result(throw_err: Bool) -> JlrsResult<i32> {
     }
 }
 
+fn freestanding_func_panics(panic: Bool) -> i32 {
+    if panic.as_bool() {
+        panic!("freestanding_func_panics panicked");
+    }
+
+    3
+}
+
 #[derive(Clone, Debug)]
 struct OpaqueInt {
     a: i32,
@@ -126,6 +134,10 @@ impl OpaqueInt {
     fn get_cloned(self) -> i32 {
         self.a
     }
+
+    fn increment_panics(&mut self) {
+        panic!("increment_panics panicked");
+    }
 }
 
 #[derive(Clone)]
@@ -331,6 +343,13 @@ julia_module! {
     fn freestanding_func_ret_array(dt: DataType) -> ArrayRet;
     fn freestanding_func_ret_rust_result(throw_err: Bool) -> JlrsResult<i32>;
 
+    #[catch_unwind]
+    fn freestanding_func_panics(panic: Bool) -> i32;
+
+    #[catch_unwind]
+    #[gc_safe]
+    fn freestanding_func_panics(panic: Bool) -> i32 as freestanding_func_panics_gc_safe;
+
     struct OpaqueInt;
     in OpaqueInt fn new(value: i32) -> TypedValueRet<OpaqueInt> as OpaqueInt;
     in OpaqueInt fn increment(&mut self) as increment!;
@@ -346,6 +365,9 @@ julia_module! {
     in OpaqueInt fn get(&self) -> i32 as unbox_opaque_untracked;
     in OpaqueInt fn get_cloned(self) -> i32;
 
+    #[catch_unwind]
+    in OpaqueInt fn increment_panics(&mut self) as increment_panics!;
+
     struct ForeignThing;
     in ForeignThing fn new(value: Value) -> TypedValueRet<ForeignThing> as ForeignThing;
 